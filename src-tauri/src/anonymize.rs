@@ -0,0 +1,188 @@
+// Anonymized exports, for sharing a database that reproduces a bug without sharing what it says.
+// Diagnostics bundles use the same scrambling for their optional copy of the data.
+//
+// Titles, notes, list names, subtasks and tags are scrambled word by word: every run of letters or digits becomes a
+// made-up word of the same length and letter case, and everything else (spaces, punctuation, line
+// breaks) is kept. The same word always gives the same token for the same seed, so duplicates and
+// word searches still match. Ids, dates, repeat rules, flags and order are left alone.
+//
+// The seed is the only secret: with it, anyone can hash a word list and read the tokens back. So
+// without one a random seed is used and handed back, for the caller to keep for repeat runs.
+
+use crate::bundle::BundleData;
+use crate::ExportData;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+pub fn random_seed() -> String {
+    let bytes: [u8; 16] = rand::random();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn anonymize_export(data: &mut ExportData, seed: &str) {
+    let mut scrambler = Scrambler::new(seed);
+    for list in &mut data.lists {
+        list.name = scrambler.text(&list.name);
+    }
+    for task in &mut data.tasks {
+        task.title = scrambler.text(&task.title);
+        task.content = task.content.as_deref().map(|content| scrambler.text(content));
+    }
+}
+
+pub fn anonymize_bundle_data(data: &mut BundleData, seed: &str) {
+    let mut scrambler = Scrambler::new(seed);
+    for list in &mut data.lists {
+        list.name = scrambler.text(&list.name);
+    }
+    for task in &mut data.tasks {
+        task.title = scrambler.text(&task.title);
+        task.content = task.content.as_deref().map(|content| scrambler.text(content));
+    }
+    for subtask in &mut data.subtasks {
+        subtask.title = scrambler.text(&subtask.title);
+    }
+    for tags in data.tags.values_mut() {
+        for tag in tags.iter_mut() {
+            *tag = scrambler.text(tag);
+        }
+    }
+}
+
+struct Scrambler<'a> {
+    seed: &'a str,
+    // Words seen so far, so repeated words aren't hashed again
+    tokens: HashMap<String, String>,
+}
+
+impl<'a> Scrambler<'a> {
+    fn new(seed: &'a str) -> Self {
+        Scrambler {
+            seed,
+            tokens: HashMap::new(),
+        }
+    }
+
+    fn text(&mut self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut word = String::new();
+        for ch in text.chars() {
+            if ch.is_alphanumeric() {
+                word.push(ch);
+                continue;
+            }
+            if !word.is_empty() {
+                out.push_str(&self.word(&word));
+                word.clear();
+            }
+            out.push(ch);
+        }
+        if !word.is_empty() {
+            out.push_str(&self.word(&word));
+        }
+        out
+    }
+
+    fn word(&mut self, word: &str) -> String {
+        if let Some(token) = self.tokens.get(word) {
+            return token.clone();
+        }
+
+        // Case is left out of the hash so "Milk" and "milk" scramble to the same letters
+        let key = word.to_lowercase();
+        let mut bytes = Vec::new();
+        let mut round: u32 = 0;
+        let count = word.chars().count();
+        while bytes.len() < count {
+            let digest = Sha256::new()
+                .chain_update(self.seed.as_bytes())
+                .chain_update([0])
+                .chain_update(key.as_bytes())
+                .chain_update(round.to_le_bytes())
+                .finalize();
+            bytes.extend_from_slice(&digest);
+            round += 1;
+        }
+
+        let token: String = word
+            .chars()
+            .zip(bytes)
+            .map(|(ch, byte)| {
+                if ch.is_numeric() {
+                    char::from(b'0' + byte % 10)
+                } else {
+                    let letter = char::from(LETTERS[byte as usize % LETTERS.len()]);
+                    if ch.is_uppercase() {
+                        letter.to_ascii_uppercase()
+                    } else {
+                        letter
+                    }
+                }
+            })
+            .collect();
+        self.tokens.insert(word.to_string(), token.clone());
+        token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "Buy milk, then call Milk & Co. at 5pm";
+
+    #[test]
+    fn same_seed_gives_same_text() {
+        let first = Scrambler::new("seed-a").text(TEXT);
+        let second = Scrambler::new("seed-a").text(TEXT);
+        assert_eq!(first, second);
+        assert_ne!(first, TEXT);
+        // Separators and letter case are kept
+        for (scrambled, original) in first.chars().zip(TEXT.chars()) {
+            if original.is_alphanumeric() {
+                assert_eq!(scrambled.is_uppercase(), original.is_uppercase());
+            } else {
+                assert_eq!(scrambled, original);
+            }
+        }
+        assert_eq!(first[4..8].to_lowercase(), first[20..24].to_lowercase());
+    }
+
+    #[test]
+    fn different_seeds_give_different_text() {
+        assert_ne!(Scrambler::new("seed-a").text(TEXT), Scrambler::new("seed-b").text(TEXT));
+    }
+
+    #[test]
+    fn bundle_data_covers_subtasks_and_tags() {
+        let subtask = crate::Subtask {
+            id: "s".to_string(),
+            task_id: "t".to_string(),
+            title: "Call the bank".to_string(),
+            is_completed: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let mut data = BundleData {
+            version: 1,
+            lists: Vec::new(),
+            tasks: Vec::new(),
+            subtasks: vec![subtask],
+            tags: HashMap::from([("t".to_string(), vec!["bank".to_string()])]),
+        };
+        anonymize_bundle_data(&mut data, "seed-a");
+        let title = &data.subtasks[0].title;
+        assert_ne!(title, "Call the bank");
+        // Same word, same token, wherever it appears
+        assert_eq!(&title[9..], data.tags["t"][0]);
+    }
+
+    #[test]
+    fn random_seeds_differ() {
+        let seed = random_seed();
+        assert_eq!(seed.len(), 32);
+        assert_ne!(seed, random_seed());
+    }
+}
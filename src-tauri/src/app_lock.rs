@@ -0,0 +1,144 @@
+// App lock: a passphrase that guards the window, with or without database encryption.
+//
+// Only a salted Argon2 hash of the passphrase is stored, in `lock.json` in the data folder. While
+// the app is locked, the command gate in `run` rejects everything but the few commands the lock
+// screen needs, and after `idle_minutes` without activity the app locks itself again.
+//
+// The hash is kept out of `settings.toml` on purpose: `get_settings` is open while locked, and
+// `update_settings` and bundle imports replace settings wholesale, which would let anyone read the
+// hash or switch the lock off without the passphrase. A `lock.json` that can't be read keeps the
+// app locked and is never written over, so damage can't turn the lock off.
+
+use crate::i18n::t;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub fn locked_error() -> String {
+    t!("iToDo is locked")
+}
+
+// Everything else is refused while locked
+const ALLOWED_WHILE_LOCKED: [&str; 6] = [
+    "get_lock_status",
+    "get_settings",
+    "verify_unlock",
+    "report_activity",
+    "get_about_info",
+    "get_log_path",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LockConfig {
+    // PHC string; None when no lock is set
+    pub password_hash: Option<String>,
+    // 0 turns the idle lock off
+    pub idle_minutes: u32,
+}
+
+impl Default for LockConfig {
+    fn default() -> Self {
+        LockConfig {
+            password_hash: None,
+            idle_minutes: 5,
+        }
+    }
+}
+
+impl LockConfig {
+    // Err when the file exists but can't be read; callers treat that as locked
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(LockConfig::default()),
+            Err(e) => return Err(damaged(path, e)),
+        };
+        serde_json::from_str(&content).map_err(|e| damaged(path, e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| t!("Failed to save lock settings: {}", e))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.password_hash.is_some()
+    }
+
+    pub fn validate_idle_minutes(minutes: u32) -> Result<(), String> {
+        if minutes > 24 * 60 {
+            return Err(t!("The idle time can't be longer than a day"));
+        }
+        Ok(())
+    }
+
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        (self.is_enabled() && self.idle_minutes > 0).then(|| Duration::from_secs(self.idle_minutes as u64 * 60))
+    }
+
+    pub fn verify(&self, passphrase: &str) -> bool {
+        let Some(hash) = self.password_hash.as_deref() else { return true };
+        PasswordHash::new(hash)
+            .map(|parsed| Argon2::default().verify_password(passphrase.as_bytes(), &parsed).is_ok())
+            .unwrap_or(false)
+    }
+}
+
+fn damaged(path: &Path, error: impl std::fmt::Display) -> String {
+    t!("The app lock settings in {} can't be read, so iToDo stays locked until the file is fixed or removed: {}", path.display(), error)
+}
+
+pub fn hash_passphrase(passphrase: &str) -> Result<String, String> {
+    if passphrase.chars().count() < 4 {
+        return Err(t!("The passphrase must be at least 4 characters long"));
+    }
+    let salt_bytes: [u8; 16] = rand::random();
+    let salt = SaltString::encode_b64(&salt_bytes).map_err(|e| e.to_string())?;
+    Argon2::default()
+        .hash_password(passphrase.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| t!("Failed to hash the passphrase: {}", e))
+}
+
+pub fn is_allowed_while_locked(command: &str) -> bool {
+    ALLOWED_WHILE_LOCKED.contains(&command)
+}
+
+pub struct LockState {
+    pub locked: bool,
+    pub last_activity: Instant,
+}
+
+// Shared with the command gate, which runs outside of managed state
+pub struct AppLock(pub Arc<Mutex<LockState>>);
+
+impl AppLock {
+    pub fn new(locked: bool) -> Self {
+        AppLock(Arc::new(Mutex::new(LockState {
+            locked,
+            last_activity: Instant::now(),
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn damaged_settings_are_reported_not_reset() {
+        let path = std::env::temp_dir().join(format!("itodo-lock-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert!(!LockConfig::load(&path).unwrap().is_enabled());
+
+        fs::write(&path, "{\"password_hash\": \"$argon2id$").unwrap();
+        assert!(LockConfig::load(&path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"password_hash\": \"$argon2id$");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,225 @@
+// Rotating database backups in the `backups/` folder of the data directory.
+//
+// Backups are taken with SQLite's online backup API, so they are consistent even while the app
+// is writing. Retention keeps the newest backup of each of the last `keep_daily` days and of each
+// of the last `keep_weekly` weeks; everything else is pruned after a new backup is made.
+
+use crate::encryption::{self, DbKey};
+use crate::i18n::t;
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, Utc};
+use rusqlite::backup::Backup;
+use rusqlite::OpenFlags;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const FILE_PREFIX: &str = "itodo-";
+const FILE_EXTENSION: &str = "db";
+const ID_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    pub enabled: bool,
+    // Minimum time between two automatic backups
+    pub interval_hours: u32,
+    pub keep_daily: usize,
+    pub keep_weekly: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        BackupConfig {
+            enabled: true,
+            interval_hours: 24,
+            keep_daily: 7,
+            keep_weekly: 4,
+        }
+    }
+}
+
+impl BackupConfig {
+    // Only for `backup.json`, where the policy was kept before the settings store
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=24 * 30).contains(&self.interval_hours) {
+            return Err(t!("Backup interval must be between 1 hour and 30 days"));
+        }
+        if self.keep_daily > 365 || self.keep_weekly > 520 {
+            return Err(t!("Too many backups to keep"));
+        }
+        if self.keep_daily + self.keep_weekly == 0 {
+            return Err(t!("At least one backup must be kept"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub id: String,
+    pub created_at: String,
+    pub size: u64,
+    pub path: String,
+}
+
+impl BackupInfo {
+    fn created(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.created_at).ok().map(|dt| dt.with_timezone(&Utc))
+    }
+}
+
+// Copies the database at `db_path` into a new backup file. Backups of an encrypted database are
+// encrypted with the same key.
+pub fn create_backup(db_path: &Path, backup_dir: &Path, key: Option<&DbKey>) -> Result<BackupInfo, String> {
+    fs::create_dir_all(backup_dir).map_err(|e| t!("Failed to create backup folder: {}", e))?;
+
+    let now = Utc::now();
+    let id = format!("{}{}", FILE_PREFIX, now.format(ID_FORMAT));
+    let target = backup_path(backup_dir, &id);
+    // Written under a temporary name so a crash never leaves a half-written backup in the list
+    let partial = target.with_extension("partial");
+
+    let source = encryption::open_file(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX, key)
+        .map_err(|e| t!("Failed to open database for backup: {}", e))?;
+    let _ = fs::remove_file(&partial);
+    {
+        let mut target_conn = encryption::open_file(&partial, OpenFlags::default(), key)?;
+        Backup::new(&source, &mut target_conn)
+            .and_then(|b| b.run_to_completion(256, Duration::ZERO, None))
+            .map_err(|e| t!("Backup failed: {}", e))?;
+    }
+    fs::rename(&partial, &target).map_err(|e| t!("Failed to store backup: {}", e))?;
+
+    let size = fs::metadata(&target).map(|m| m.len()).unwrap_or(0);
+    Ok(BackupInfo {
+        id,
+        created_at: now.to_rfc3339(),
+        size,
+        path: target.to_string_lossy().to_string(),
+    })
+}
+
+// Backups in the folder, newest first
+pub fn list_backups(backup_dir: &Path) -> Result<Vec<BackupInfo>, String> {
+    let entries = match fs::read_dir(backup_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut backups: Vec<BackupInfo> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(FILE_EXTENSION) {
+                return None;
+            }
+            let id = path.file_stem()?.to_str()?.to_string();
+            let created = parse_id(&id)?;
+            Some(BackupInfo {
+                id,
+                created_at: created.to_rfc3339(),
+                size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+                path: path.to_string_lossy().to_string(),
+            })
+        })
+        .collect();
+    backups.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(backups)
+}
+
+pub fn find_backup(backup_dir: &Path, id: &str) -> Result<PathBuf, String> {
+    // Ids come from the frontend; only accept the exact shape we generate
+    if parse_id(id).is_none() {
+        return Err(t!("Invalid backup id: {}", id));
+    }
+    let path = backup_path(backup_dir, id);
+    if path.is_file() {
+        Ok(path)
+    } else {
+        Err(t!("Backup not found: {}", id))
+    }
+}
+
+// Checks that a backup is a readable, intact iToDo database before it replaces the live one
+pub fn verify_backup(path: &Path, key: Option<&DbKey>) -> Result<(), String> {
+    let conn = encryption::open_file(path, OpenFlags::SQLITE_OPEN_READ_ONLY, key)
+        .map_err(|e| t!("Failed to open backup: {}", e))?;
+    let result: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| t!("Backup is not a valid database: {}", e))?;
+    if result != "ok" {
+        return Err(t!("Backup failed the integrity check: {}", result));
+    }
+    let tables: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name IN ('lists', 'tasks')",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if tables != 2 {
+        return Err(t!("Backup does not contain iToDo data"));
+    }
+    Ok(())
+}
+
+// Deletes backups that fall outside the retention policy, returning their ids
+pub fn prune_backups(backup_dir: &Path, config: &BackupConfig) -> Result<Vec<String>, String> {
+    let backups = list_backups(backup_dir)?;
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    let mut removed = Vec::new();
+
+    for backup in &backups {
+        let Some(created) = backup.created() else { continue };
+        let local = created.with_timezone(&Local).date_naive();
+        let week = (local.iso_week().year(), local.iso_week().week());
+
+        let mut keep = false;
+        if !days.contains(&local) && days.len() < config.keep_daily {
+            days.insert(local);
+            keep = true;
+        }
+        if !weeks.contains(&week) && weeks.len() < config.keep_weekly {
+            weeks.insert(week);
+            keep = true;
+        }
+        if !keep {
+            fs::remove_file(&backup.path).map_err(|e| t!("Failed to remove backup {}: {}", backup.id, e))?;
+            removed.push(backup.id.clone());
+        }
+    }
+    Ok(removed)
+}
+
+// Whether the newest backup is older than the configured interval
+pub fn is_backup_due(backup_dir: &Path, config: &BackupConfig) -> bool {
+    if !config.enabled {
+        return false;
+    }
+    let latest = list_backups(backup_dir)
+        .ok()
+        .and_then(|backups| backups.first().and_then(BackupInfo::created));
+    match latest {
+        Some(created) => Utc::now() - created >= chrono::Duration::hours(config.interval_hours as i64),
+        None => true,
+    }
+}
+
+fn backup_path(backup_dir: &Path, id: &str) -> PathBuf {
+    backup_dir.join(format!("{}.{}", id, FILE_EXTENSION))
+}
+
+fn parse_id(id: &str) -> Option<DateTime<Utc>> {
+    let stamp = id.strip_prefix(FILE_PREFIX)?;
+    NaiveDateTime::parse_from_str(stamp, ID_FORMAT).ok().map(|dt| dt.and_utc())
+}
@@ -0,0 +1,325 @@
+// `.itodo` bundles: a zip archive holding everything needed to move to another machine.
+//
+//   manifest.json      format and data versions, app version, creation time and counts
+//   data.json          lists, tasks, subtasks and tags (`BundleData`)
+//   settings.json      app settings (`Settings`), when passed in
+//   attachments/...    files from the attachments folder of the data directory
+//   checksums.sha256   SHA-256 of every other entry, in `sha256sum` format
+
+use crate::i18n::t;
+use crate::{List, Subtask, Task};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+pub const BUNDLE_FORMAT: &str = "itodo-bundle";
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+// Bumped whenever `BundleData` changes shape; newer bundles are refused on import
+pub const DATA_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const DATA_FILE: &str = "data.json";
+const SETTINGS_FILE: &str = "settings.json";
+const CHECKSUM_FILE: &str = "checksums.sha256";
+const ATTACHMENTS_DIR: &str = "attachments/";
+// Bundles are unpacked in memory, so a crafted archive mustn't be able to exhaust it
+const MAX_ENTRY_SIZE: u64 = 100 * 1024 * 1024;
+const MAX_BUNDLE_SIZE: u64 = 512 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format: String,
+    pub format_version: u32,
+    pub data_version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub lists: usize,
+    pub tasks: usize,
+    pub subtasks: usize,
+    pub attachments: usize,
+    pub has_settings: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleData {
+    pub version: u32,
+    pub lists: Vec<List>,
+    pub tasks: Vec<Task>,
+    pub subtasks: Vec<Subtask>,
+    // Task id -> tags
+    pub tags: HashMap<String, Vec<String>>,
+}
+
+pub struct BundleContents {
+    pub manifest: BundleManifest,
+    pub data: BundleData,
+    pub settings: Option<serde_json::Value>,
+    // Paths relative to the attachments folder, with their contents
+    pub attachments: Vec<(PathBuf, Vec<u8>)>,
+}
+
+// ============== Export ==============
+
+pub fn write_bundle(
+    path: &Path,
+    data: &BundleData,
+    settings: Option<&serde_json::Value>,
+    attachments_dir: &Path,
+) -> Result<BundleManifest, String> {
+    let attachments = collect_attachments(attachments_dir)?;
+    let manifest = BundleManifest {
+        format: BUNDLE_FORMAT.to_string(),
+        format_version: BUNDLE_FORMAT_VERSION,
+        data_version: DATA_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        lists: data.lists.len(),
+        tasks: data.tasks.len(),
+        subtasks: data.subtasks.len(),
+        attachments: attachments.len(),
+        has_settings: settings.is_some(),
+    };
+
+    let mut entries: Vec<(String, Vec<u8>)> = vec![
+        (MANIFEST_FILE.to_string(), to_json(&manifest)?),
+        (DATA_FILE.to_string(), to_json(data)?),
+    ];
+    if let Some(settings) = settings {
+        entries.push((SETTINGS_FILE.to_string(), to_json(settings)?));
+    }
+    for (relative, file) in attachments {
+        let bytes = fs::read(&file).map_err(|e| t!("Failed to read attachment {}: {}", file.display(), e))?;
+        entries.push((format!("{}{}", ATTACHMENTS_DIR, relative), bytes));
+    }
+
+    let checksums: String = entries
+        .iter()
+        .map(|(name, bytes)| format!("{}  {}\n", sha256_hex(bytes), name))
+        .collect();
+    entries.push((CHECKSUM_FILE.to_string(), checksums.into_bytes()));
+
+    let file = File::create(path).map_err(|e| t!("Failed to create bundle: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, bytes) in &entries {
+        zip.start_file(name.as_str(), options).map_err(|e| e.to_string())?;
+        zip.write_all(bytes).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| t!("Failed to write bundle: {}", e))?;
+
+    Ok(manifest)
+}
+
+// Files under the attachments folder as ("dir/name", full path), with "/" separators
+fn collect_attachments(dir: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let mut found = Vec::new();
+    if !dir.is_dir() {
+        return Ok(found);
+    }
+
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let entries = fs::read_dir(&current).map_err(|e| t!("Failed to read {}: {}", current.display(), e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Ok(relative) = path.strip_prefix(dir) {
+                let name: Vec<String> = relative.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+                found.push((name.join("/"), path));
+            }
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(value).map_err(|e| e.to_string())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+// ============== Import ==============
+
+pub fn read_bundle(path: &Path) -> Result<BundleContents, String> {
+    let file = File::open(path).map_err(|e| t!("Failed to open bundle: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| t!("Not a valid .itodo bundle: {}", e))?;
+
+    let mut entries: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+    let mut total: u64 = 0;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        if entry.is_dir() {
+            continue;
+        }
+        // Refuse names like "../x" or absolute paths instead of writing outside the data dir later
+        if entry.enclosed_name().is_none() {
+            return Err(t!("Bundle contains an unsafe path: {}", entry.name()));
+        }
+        // The declared size can lie, so the read itself is capped too
+        let name = entry.name().to_string();
+        let too_large = || t!("Bundle entry {} is larger than {} MB", name, MAX_ENTRY_SIZE / 1024 / 1024);
+        if entry.size() > MAX_ENTRY_SIZE {
+            return Err(too_large());
+        }
+        let mut bytes = Vec::new();
+        entry.by_ref().take(MAX_ENTRY_SIZE + 1).read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+        if bytes.len() as u64 > MAX_ENTRY_SIZE {
+            return Err(too_large());
+        }
+        total += bytes.len() as u64;
+        if total > MAX_BUNDLE_SIZE {
+            return Err(t!("Bundle is larger than {} MB unpacked", MAX_BUNDLE_SIZE / 1024 / 1024));
+        }
+        entries.insert(name, bytes);
+    }
+
+    verify_checksums(&entries)?;
+
+    let manifest: BundleManifest = from_json(&entries, MANIFEST_FILE)?;
+    if manifest.format != BUNDLE_FORMAT {
+        return Err(t!("Unknown bundle format: {}", manifest.format));
+    }
+    if manifest.format_version > BUNDLE_FORMAT_VERSION || manifest.data_version > DATA_VERSION {
+        return Err(t!(
+            "This bundle was created by a newer version of iToDo ({}); please update the app",
+            manifest.app_version
+        ));
+    }
+
+    let data: BundleData = from_json(&entries, DATA_FILE)?;
+    let settings = if entries.contains_key(SETTINGS_FILE) {
+        Some(from_json(&entries, SETTINGS_FILE)?)
+    } else {
+        None
+    };
+    let attachments = entries
+        .iter()
+        .filter_map(|(name, bytes)| {
+            let relative = name.strip_prefix(ATTACHMENTS_DIR)?;
+            Some((PathBuf::from(relative), bytes.clone()))
+        })
+        .collect();
+
+    Ok(BundleContents {
+        manifest,
+        data,
+        settings,
+        attachments,
+    })
+}
+
+// Writes imported attachments into `dir`. Each file goes to a temporary name first and is then
+// renamed into place, and existing files are never replaced: an identical one is left alone and a
+// different one keeps its name while the import gets " (2)", " (3)", ... Returns how many were written.
+pub fn write_attachments(dir: &Path, attachments: &[(PathBuf, Vec<u8>)]) -> Result<usize, String> {
+    let mut written = 0;
+    for (relative, bytes) in attachments {
+        let target = dir.join(relative);
+        let Some(target) = free_attachment_path(&target, bytes) else {
+            continue;
+        };
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| t!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let file_name = target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let partial = target.with_file_name(format!(".{}.importing", file_name));
+        fs::write(&partial, bytes)
+            .and_then(|_| fs::rename(&partial, &target))
+            .map_err(|e| {
+                let _ = fs::remove_file(&partial);
+                t!("Failed to write attachment {}: {}", target.display(), e)
+            })?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+// None when `target` already holds exactly these bytes
+fn free_attachment_path(target: &Path, bytes: &[u8]) -> Option<PathBuf> {
+    let stem = target.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = target.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    let mut candidate = target.to_path_buf();
+    for n in 2.. {
+        if !candidate.exists() {
+            break;
+        }
+        if fs::read(&candidate).is_ok_and(|existing| existing == bytes) {
+            return None;
+        }
+        candidate = target.with_file_name(format!("{} ({}){}", stem, n, extension));
+    }
+    Some(candidate)
+}
+
+// Every entry except the checksum file itself must be listed and match
+fn verify_checksums(entries: &BTreeMap<String, Vec<u8>>) -> Result<(), String> {
+    let listing = entries
+        .get(CHECKSUM_FILE)
+        .ok_or_else(|| t!("Bundle is missing {}", CHECKSUM_FILE))?;
+    let listing = String::from_utf8_lossy(listing);
+
+    let mut expected: HashMap<&str, &str> = HashMap::new();
+    for line in listing.lines().filter(|l| !l.trim().is_empty()) {
+        let (hash, name) = line
+            .split_once("  ")
+            .ok_or_else(|| t!("Malformed line in {}: {}", CHECKSUM_FILE, line))?;
+        expected.insert(name, hash);
+    }
+
+    for (name, bytes) in entries.iter().filter(|(name, _)| name.as_str() != CHECKSUM_FILE) {
+        match expected.remove(name.as_str()) {
+            Some(hash) if hash.eq_ignore_ascii_case(&sha256_hex(bytes)) => {}
+            Some(_) => return Err(t!("Checksum mismatch for {}; the bundle is corrupted", name)),
+            None => return Err(t!("{} is not listed in {}", name, CHECKSUM_FILE)),
+        }
+    }
+    if let Some(name) = expected.keys().next() {
+        return Err(t!("Bundle is missing {}", name));
+    }
+    Ok(())
+}
+
+fn from_json<T: for<'de> Deserialize<'de>>(entries: &BTreeMap<String, Vec<u8>>, name: &str) -> Result<T, String> {
+    let bytes = entries.get(name).ok_or_else(|| t!("Bundle is missing {}", name))?;
+    serde_json::from_slice(bytes).map_err(|e| t!("Failed to parse {}: {}", name, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attachments_never_replace_existing_files() {
+        let dir = std::env::temp_dir().join(format!("itodo-bundle-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("receipts")).unwrap();
+        fs::write(dir.join("receipts/march.pdf"), b"mine").unwrap();
+        fs::write(dir.join("same.txt"), b"same").unwrap();
+
+        let attachments = vec![
+            (PathBuf::from("receipts/march.pdf"), b"theirs".to_vec()),
+            (PathBuf::from("same.txt"), b"same".to_vec()),
+            (PathBuf::from("new.txt"), b"new".to_vec()),
+        ];
+        assert_eq!(write_attachments(&dir, &attachments).unwrap(), 2);
+        assert_eq!(fs::read(dir.join("receipts/march.pdf")).unwrap(), b"mine");
+        assert_eq!(fs::read(dir.join("receipts/march (2).pdf")).unwrap(), b"theirs");
+        assert_eq!(fs::read(dir.join("new.txt")).unwrap(), b"new");
+
+        // Importing the same bundle again changes nothing
+        assert_eq!(write_attachments(&dir, &attachments).unwrap(), 0);
+        let names: Vec<_> = fs::read_dir(dir.join("receipts")).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(names.len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,393 @@
+// Command-line client for the terminal and scripts, installed as `itodo-cli`.
+//
+// It opens the same database as the app, even while the app is running: SQLite's file locking
+// keeps both sides consistent, and the busy timeout makes either side wait for the other's write
+// instead of failing. The app picks up the changes the next time it loads a view.
+//
+// Tasks are referred to by id, or by any unique start of it such as the 8 characters `list` prints.
+
+use chrono::{Duration as Days, Local, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use itodo_lib::i18n::t;
+use itodo_lib::{CreateTaskInput, ExportData, List, Task};
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::fmt::Display;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+const PASSPHRASE_VAR: &str = "ITODO_PASSPHRASE";
+// Longer than the app's, since a script would rather wait than fail
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+const SHORT_ID_LEN: usize = 8;
+
+#[derive(Parser)]
+#[command(
+    name = "itodo-cli",
+    version,
+    about = "Manage iToDo tasks from the terminal",
+    after_help = "Encrypted databases are unlocked with the passphrase in the ITODO_PASSPHRASE environment variable."
+)]
+struct Cli {
+    /// Print JSON instead of text
+    #[arg(long, global = true)]
+    json: bool,
+
+    // Both are read by the data folder lookup in the library, like the app's own flags
+    /// Use this data folder instead of the app's
+    #[arg(long = "data-dir", global = true, value_name = "PATH")]
+    _data_dir: Option<PathBuf>,
+    /// Use the `data` folder next to the executable
+    #[arg(long = "portable", global = true)]
+    _portable: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Add a task
+    Add(AddArgs),
+    /// List tasks, optionally limited to a smart view or a list
+    List(ListArgs),
+    /// Mark tasks as completed
+    Done {
+        #[arg(required = true)]
+        ids: Vec<String>,
+        /// Mark them as not completed instead
+        #[arg(long)]
+        undo: bool,
+    },
+    /// Mark tasks as important
+    Star {
+        #[arg(required = true)]
+        ids: Vec<String>,
+        /// Remove the mark instead
+        #[arg(long)]
+        undo: bool,
+    },
+    /// Search task titles and notes
+    Search {
+        query: String,
+        /// Include completed tasks
+        #[arg(long)]
+        all: bool,
+    },
+    /// Show all lists
+    Lists,
+    /// Export tasks in the app's JSON export format
+    Export {
+        /// Only this list, by name or id
+        #[arg(long)]
+        list: Option<String>,
+        /// Write to this file instead of standard output
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Import tasks from a JSON export; "-" reads standard input
+    Import { file: PathBuf },
+}
+
+#[derive(Args)]
+struct AddArgs {
+    /// Task title; several words are joined with spaces
+    #[arg(required = true)]
+    title: Vec<String>,
+    /// List name or id; the default list when left out
+    #[arg(long)]
+    list: Option<String>,
+    #[arg(long)]
+    notes: Option<String>,
+    /// Due date: YYYY-MM-DD, "today" or "tomorrow"
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    due: Option<NaiveDate>,
+    /// Start date: YYYY-MM-DD, "today" or "tomorrow"
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    start: Option<NaiveDate>,
+    #[arg(long, value_enum)]
+    repeat: Option<Repeat>,
+    /// Mark the task as important
+    #[arg(long)]
+    important: bool,
+}
+
+#[derive(Args)]
+struct ListArgs {
+    /// Due today, like the My Day view
+    #[arg(long, group = "view")]
+    today: bool,
+    /// Marked as important
+    #[arg(long, group = "view")]
+    important: bool,
+    /// Due or starting after today
+    #[arg(long, group = "view")]
+    planned: bool,
+    /// Completed tasks only
+    #[arg(long, group = "view")]
+    completed: bool,
+    /// Only this list, by name or id
+    #[arg(long)]
+    list: Option<String>,
+    /// Include completed tasks
+    #[arg(long)]
+    all: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Repeat {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+fn main() -> ExitCode {
+    // Before parsing, so errors in the arguments are translated too
+    itodo_lib::use_app_language();
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("itodo-cli: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    let passphrase = std::env::var(PASSPHRASE_VAR).ok();
+    let mut conn = itodo_lib::open_current_database(passphrase.as_deref(), BUSY_TIMEOUT)?;
+    let json = cli.json;
+
+    match cli.command {
+        Command::Add(args) => {
+            let list_id = match &args.list {
+                Some(list) => resolve_list(&conn, list)?.id,
+                None => itodo_lib::default_list_id(&conn)?,
+            };
+            let tx = write_transaction(&mut conn)?;
+            let mut task = itodo_lib::add_task(
+                &tx,
+                CreateTaskInput {
+                    title: args.title.join(" "),
+                    content: args.notes,
+                    list_id,
+                    due_date: args.due.map(stored_date),
+                    start_date: args.start.map(stored_date),
+                    remind_time: None,
+                    repeat_rule: args.repeat.map(repeat_rule),
+                },
+            )?;
+            if args.important {
+                task = itodo_lib::set_task_important(&tx, &task.id, true)?;
+            }
+            tx.commit().map_err(|e| e.to_string())?;
+            print_tasks(&conn, &[task], json)
+        }
+        Command::List(args) => {
+            let mut tasks = if args.today {
+                itodo_lib::load_today_tasks(&conn)?
+            } else if args.important {
+                itodo_lib::load_important_tasks(&conn)?
+            } else if args.planned {
+                itodo_lib::load_planned_tasks(&conn)?
+            } else if args.completed {
+                itodo_lib::load_completed_tasks(&conn)?
+            } else {
+                itodo_lib::load_tasks(&conn, None)?
+            };
+            if let Some(list) = &args.list {
+                let list_id = resolve_list(&conn, list)?.id;
+                tasks.retain(|t| t.list_id == list_id);
+            }
+            if !args.all && !args.completed {
+                tasks.retain(|t| !t.is_completed);
+            }
+            print_tasks(&conn, &tasks, json)
+        }
+        Command::Done { ids, undo } => {
+            let tx = write_transaction(&mut conn)?;
+            let tasks = ids
+                .iter()
+                .map(|id| itodo_lib::set_task_completed(&tx, &resolve_task_id(&tx, id)?, !undo))
+                .collect::<Result<Vec<_>, _>>()?;
+            tx.commit().map_err(|e| e.to_string())?;
+            print_tasks(&conn, &tasks, json)
+        }
+        Command::Star { ids, undo } => {
+            let tx = write_transaction(&mut conn)?;
+            let tasks = ids
+                .iter()
+                .map(|id| itodo_lib::set_task_important(&tx, &resolve_task_id(&tx, id)?, !undo))
+                .collect::<Result<Vec<_>, _>>()?;
+            tx.commit().map_err(|e| e.to_string())?;
+            print_tasks(&conn, &tasks, json)
+        }
+        Command::Search { query, all } => {
+            let mut tasks = itodo_lib::find_tasks(&conn, &query)?;
+            if !all {
+                tasks.retain(|t| !t.is_completed);
+            }
+            print_tasks(&conn, &tasks, json)
+        }
+        Command::Lists => {
+            let lists = itodo_lib::load_lists(&conn)?;
+            if json {
+                return print_json(&lists);
+            }
+            let tasks = itodo_lib::load_tasks(&conn, None)?;
+            for list in &lists {
+                let open = tasks.iter().filter(|t| t.list_id == list.id && !t.is_completed).count();
+                let default = if list.is_default { "  (default)" } else { "" };
+                emit(format_args!("{}  {}  {} open{}", short_id(&list.id), list.name, open, default));
+            }
+            Ok(())
+        }
+        Command::Export { list, output } => {
+            let list_id = list.map(|l| resolve_list(&conn, &l).map(|l| l.id)).transpose()?;
+            let (tasks, lists) = itodo_lib::load_export_data(&conn, list_id.as_deref())?;
+            let data = ExportData {
+                version: "1.0".to_string(),
+                export_date: Utc::now().to_rfc3339(),
+                tasks,
+                lists,
+            };
+            let content = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
+            match output {
+                Some(path) => {
+                    fs::write(&path, content).map_err(|e| t!("Failed to write {}: {}", path.display(), e))?;
+                    if !json {
+                        emit(t!("Exported {} tasks to {}", data.tasks.len(), path.display()));
+                    }
+                    Ok(())
+                }
+                None => {
+                    emit(content);
+                    Ok(())
+                }
+            }
+        }
+        Command::Import { file } => {
+            let content = if file.as_os_str() == "-" {
+                let mut content = String::new();
+                io::stdin().read_to_string(&mut content).map_err(|e| e.to_string())?;
+                content
+            } else {
+                fs::read_to_string(&file).map_err(|e| t!("Failed to read {}: {}", file.display(), e))?
+            };
+            let data: ExportData = serde_json::from_str(&content).map_err(|e| t!("Not an iToDo export: {}", e))?;
+
+            let tx = write_transaction(&mut conn)?;
+            let tasks = itodo_lib::import_export_data(&tx, &data)?;
+            tx.commit().map_err(|e| e.to_string())?;
+            if json {
+                return print_json(&tasks);
+            }
+            emit(t!("Imported {} tasks", tasks.len()));
+            Ok(())
+        }
+    }
+}
+
+// Takes the write lock up front: a deferred transaction that turns into a write while the app holds
+// the lock fails at once instead of waiting out the busy timeout
+fn write_transaction(conn: &mut Connection) -> Result<Transaction<'_>, String> {
+    conn.transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())
+}
+
+fn resolve_task_id(conn: &Connection, id: &str) -> Result<String, String> {
+    let mut stmt = conn
+        .prepare("SELECT id FROM tasks WHERE substr(id, 1, length(?1)) = ?1 LIMIT 2")
+        .map_err(|e| e.to_string())?;
+    let matches: Vec<String> = stmt
+        .query_map([id], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+    match matches.as_slice() {
+        [only] => Ok(only.clone()),
+        [] => Err(t!("No task with id {}", id)),
+        _ => Err(t!("More than one task id starts with {}", id)),
+    }
+}
+
+// By id, then by name ignoring case
+fn resolve_list(conn: &Connection, name_or_id: &str) -> Result<List, String> {
+    let lists = itodo_lib::load_lists(conn)?;
+    let wanted = name_or_id.trim();
+    lists
+        .iter()
+        .find(|l| l.id == wanted)
+        .or_else(|| lists.iter().find(|l| l.name.to_lowercase() == wanted.to_lowercase()))
+        .cloned()
+        .ok_or_else(|| t!("No list named {}", name_or_id))
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    let today = Local::now().date_naive();
+    match value.to_lowercase().as_str() {
+        "today" => Ok(today),
+        "tomorrow" => Ok(today + Days::days(1)),
+        _ => NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| t!("Expected YYYY-MM-DD, today or tomorrow")),
+    }
+}
+
+// The form the app writes
+fn stored_date(date: NaiveDate) -> String {
+    format!("{}T00:00:00Z", date.format("%Y-%m-%d"))
+}
+
+fn repeat_rule(repeat: Repeat) -> String {
+    let kind = match repeat {
+        Repeat::Daily => "daily",
+        Repeat::Weekly => "weekly",
+        Repeat::Monthly => "monthly",
+        Repeat::Yearly => "yearly",
+    };
+    format!("{{\"type\":\"{}\"}}", kind)
+}
+
+fn short_id(id: &str) -> &str {
+    id.get(..SHORT_ID_LEN).unwrap_or(id)
+}
+
+// Like `println!`, but a closed pipe (`itodo-cli list | head`) ends the program quietly
+fn emit(line: impl Display) {
+    if let Err(e) = writeln!(io::stdout().lock(), "{}", line) {
+        if e.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("itodo-cli: {}", e);
+        }
+        std::process::exit(if e.kind() == io::ErrorKind::BrokenPipe { 0 } else { 1 });
+    }
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<(), String> {
+    emit(serde_json::to_string_pretty(value).map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+fn print_tasks(conn: &Connection, tasks: &[Task], json: bool) -> Result<(), String> {
+    if json {
+        return print_json(tasks);
+    }
+    let lists: HashMap<String, String> = itodo_lib::load_lists(conn)?.into_iter().map(|l| (l.id, l.name)).collect();
+    for task in tasks {
+        let mark = if task.is_completed { "[x]" } else { "[ ]" };
+        let star = if task.is_important { " ★" } else { "" };
+        let due = task
+            .due_date
+            .as_deref()
+            .and_then(|d| d.get(..10))
+            .map(|d| format!("  due {}", d))
+            .unwrap_or_default();
+        let list = lists.get(&task.list_id).map(|name| format!("  @{}", name)).unwrap_or_default();
+        emit(format_args!("{}  {} {}{}{}{}", short_id(&task.id), mark, task.title, star, due, list));
+    }
+    Ok(())
+}
@@ -0,0 +1,345 @@
+// CSV export and import.
+//
+// Exports use the column names below. Imports accept any layout through a user supplied
+// column mapping; the default mapping reads back what the exporter writes.
+
+use crate::i18n::t;
+use crate::{List, Task};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const EXPORT_HEADERS: [&str; 12] = [
+    "id",
+    "title",
+    "notes",
+    "list",
+    "completed",
+    "important",
+    "due_date",
+    "start_date",
+    "remind_time",
+    "repeat_rule",
+    "created_at",
+    "updated_at",
+];
+
+// ============== Export ==============
+
+pub fn export_tasks(tasks: &[Task], lists: &[List], delimiter: u8) -> Result<String, String> {
+    let list_names: HashMap<&str, &str> = lists.iter().map(|l| (l.id.as_str(), l.name.as_str())).collect();
+
+    let mut writer = csv::WriterBuilder::new().delimiter(delimiter).from_writer(Vec::new());
+    writer.write_record(EXPORT_HEADERS).map_err(|e| e.to_string())?;
+
+    for task in tasks {
+        let list_name = list_names.get(task.list_id.as_str()).copied().unwrap_or("");
+        writer
+            .write_record([
+                task.id.as_str(),
+                task.title.as_str(),
+                task.content.as_deref().unwrap_or(""),
+                list_name,
+                bool_str(task.is_completed),
+                bool_str(task.is_important),
+                task.due_date.as_deref().unwrap_or(""),
+                task.start_date.as_deref().unwrap_or(""),
+                task.remind_time.as_deref().unwrap_or(""),
+                task.repeat_rule.as_deref().unwrap_or(""),
+                task.created_at.as_str(),
+                task.updated_at.as_str(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+fn bool_str(value: bool) -> &'static str {
+    if value {
+        "true"
+    } else {
+        "false"
+    }
+}
+
+pub fn parse_delimiter(delimiter: Option<&str>) -> Result<u8, String> {
+    match delimiter {
+        None | Some("") => Ok(b','),
+        Some("\\t") | Some("tab") => Ok(b'\t'),
+        Some(d) if d.len() == 1 => Ok(d.as_bytes()[0]),
+        Some(d) => Err(t!("Invalid CSV delimiter: \"{}\"", d)),
+    }
+}
+
+// ============== Import ==============
+
+// A column is referenced either by its header name or by its zero-based position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CsvColumn {
+    Index(usize),
+    Name(String),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CsvColumnMapping {
+    pub title: Option<CsvColumn>,
+    pub notes: Option<CsvColumn>,
+    pub list: Option<CsvColumn>,
+    pub completed: Option<CsvColumn>,
+    pub important: Option<CsvColumn>,
+    pub due_date: Option<CsvColumn>,
+    pub start_date: Option<CsvColumn>,
+    pub remind_time: Option<CsvColumn>,
+    pub repeat_rule: Option<CsvColumn>,
+}
+
+impl CsvColumnMapping {
+    // Matches the columns written by `export_tasks`
+    fn export_layout() -> Self {
+        let name = |n: &str| Some(CsvColumn::Name(n.to_string()));
+        CsvColumnMapping {
+            title: name("title"),
+            notes: name("notes"),
+            list: name("list"),
+            completed: name("completed"),
+            important: name("important"),
+            due_date: name("due_date"),
+            start_date: name("start_date"),
+            remind_time: name("remind_time"),
+            repeat_rule: name("repeat_rule"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CsvImportOptions {
+    pub mapping: Option<CsvColumnMapping>,
+    pub delimiter: Option<String>,
+    // chrono format string, e.g. "%d/%m/%Y"; RFC 3339 and ISO dates are accepted when unset
+    pub date_format: Option<String>,
+    pub has_header: Option<bool>,
+    // Target for rows without a list column; falls back to the default list
+    pub list_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvRowError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvImportReport {
+    pub imported: Vec<Task>,
+    pub errors: Vec<CsvRowError>,
+}
+
+pub struct CsvRow {
+    pub list_name: Option<String>,
+    pub task: Task,
+}
+
+struct ResolvedMapping {
+    title: usize,
+    notes: Option<usize>,
+    list: Option<usize>,
+    completed: Option<usize>,
+    important: Option<usize>,
+    due_date: Option<usize>,
+    start_date: Option<usize>,
+    remind_time: Option<usize>,
+    repeat_rule: Option<usize>,
+}
+
+pub fn parse_tasks(data: &str, options: &CsvImportOptions) -> Result<(Vec<CsvRow>, Vec<CsvRowError>), String> {
+    let delimiter = parse_delimiter(options.delimiter.as_deref())?;
+    let has_header = options.has_header.unwrap_or(true);
+    // Without a mapping the export layout is assumed, and its optional columns may be missing
+    let explicit = options.mapping.is_some();
+    let mapping = options.mapping.clone().unwrap_or_else(CsvColumnMapping::export_layout);
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(has_header)
+        .flexible(true)
+        .from_reader(data.trim_start_matches('\u{feff}').as_bytes());
+
+    let headers: Vec<String> = if has_header {
+        reader
+            .headers()
+            .map_err(|e| t!("Failed to read CSV header: {}", e))?
+            .iter()
+            .map(|h| h.trim().to_string())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let columns = resolve_mapping(&mapping, &headers, explicit)?;
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let line = record
+            .as_ref()
+            .ok()
+            .and_then(|r| r.position())
+            .map(|p| p.line() as usize)
+            .unwrap_or(index + 1 + has_header as usize);
+
+        let result = record
+            .map_err(|e| e.to_string())
+            .and_then(|record| parse_row(&record, &columns, options.date_format.as_deref()));
+        match result {
+            Ok(Some(row)) => rows.push(row),
+            Ok(None) => {}
+            Err(message) => errors.push(CsvRowError { row: line, message }),
+        }
+    }
+
+    Ok((rows, errors))
+}
+
+fn resolve_mapping(mapping: &CsvColumnMapping, headers: &[String], explicit: bool) -> Result<ResolvedMapping, String> {
+    let resolve = |column: &Option<CsvColumn>, field: &str| -> Result<Option<usize>, String> {
+        match column {
+            None => Ok(None),
+            Some(CsvColumn::Index(i)) => Ok(Some(*i)),
+            Some(CsvColumn::Name(name)) => headers
+                .iter()
+                .position(|h| h.eq_ignore_ascii_case(name.trim()))
+                .map(Some)
+                .ok_or_else(|| t!("Column \"{}\" mapped to {} not found in CSV header", name, field)),
+        }
+    };
+
+    let title = resolve(&mapping.title, "title")?.ok_or_else(|| t!("A title column mapping is required"))?;
+    // A column the user mapped must exist; ones assumed from the export layout are skipped if missing
+    let optional = |column: &Option<CsvColumn>, field: &str| match resolve(column, field) {
+        Err(_) if !explicit => Ok(None),
+        result => result,
+    };
+
+    Ok(ResolvedMapping {
+        title,
+        notes: optional(&mapping.notes, "notes")?,
+        list: optional(&mapping.list, "list")?,
+        completed: optional(&mapping.completed, "completed")?,
+        important: optional(&mapping.important, "important")?,
+        due_date: optional(&mapping.due_date, "due_date")?,
+        start_date: optional(&mapping.start_date, "start_date")?,
+        remind_time: optional(&mapping.remind_time, "remind_time")?,
+        repeat_rule: optional(&mapping.repeat_rule, "repeat_rule")?,
+    })
+}
+
+fn parse_row(record: &csv::StringRecord, columns: &ResolvedMapping, date_format: Option<&str>) -> Result<Option<CsvRow>, String> {
+    if record.iter().all(|field| field.trim().is_empty()) {
+        return Ok(None);
+    }
+
+    let field = |index: Option<usize>| -> Option<String> {
+        index
+            .and_then(|i| record.get(i))
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+
+    let title = field(Some(columns.title)).ok_or_else(|| t!("Missing title"))?;
+    let date = |index: Option<usize>, name: &str| -> Result<Option<String>, String> {
+        field(index)
+            .map(|v| parse_date(&v, date_format).ok_or_else(|| t!("Invalid {} \"{}\"", name, v)))
+            .transpose()
+    };
+    let flag = |index: Option<usize>, name: &str| -> Result<bool, String> {
+        field(index)
+            .map(|v| parse_bool(&v).ok_or_else(|| t!("Invalid {} value \"{}\"", name, v)))
+            .transpose()
+            .map(|v| v.unwrap_or(false))
+    };
+
+    let now = Utc::now().to_rfc3339();
+    let task = Task {
+        id: Uuid::new_v4().to_string(),
+        title,
+        content: field(columns.notes),
+        is_completed: flag(columns.completed, "completed")?,
+        is_important: flag(columns.important, "important")?,
+        due_date: date(columns.due_date, &t!("due date"))?,
+        start_date: date(columns.start_date, &t!("start date"))?,
+        remind_time: date(columns.remind_time, &t!("remind time"))?,
+        repeat_rule: field(columns.repeat_rule),
+        list_id: String::new(),
+        created_at: now.clone(),
+        updated_at: now,
+    };
+
+    Ok(Some(CsvRow {
+        list_name: field(columns.list),
+        task,
+    }))
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" | "x" | "done" | "completed" | "是" => Some(true),
+        "false" | "no" | "n" | "0" | "" | "否" => Some(false),
+        _ => None,
+    }
+}
+
+// Dates are normalized to the "YYYY-MM-DDTHH:MM:SSZ" form the frontend writes
+fn parse_date(value: &str, format: Option<&str>) -> Option<String> {
+    let to_stored = |dt: NaiveDateTime| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    if let Some(format) = format {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(to_stored(dt));
+        }
+        return NaiveDate::parse_from_str(value, format)
+            .ok()
+            .map(|d| to_stored(d.and_time(NaiveTime::MIN)));
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(to_stored(dt.with_timezone(&Utc).naive_utc()));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(to_stored(dt));
+        }
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .map(|d| to_stored(d.and_time(NaiveTime::MIN)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_missing_columns_of_the_export_layout() {
+        let (rows, errors) = parse_tasks("title,notes\nBuy milk,Semi-skimmed\n", &CsvImportOptions::default()).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(rows[0].task.content.as_deref(), Some("Semi-skimmed"));
+    }
+
+    #[test]
+    fn rejects_mapped_columns_missing_from_the_header() {
+        let options = CsvImportOptions {
+            mapping: Some(CsvColumnMapping {
+                title: Some(CsvColumn::Name("Task".to_string())),
+                due_date: Some(CsvColumn::Name("Deadline".to_string())),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let error = parse_tasks("Task,Due\nBuy milk,2026-10-22\n", &options).err().unwrap();
+        assert!(error.contains("Deadline"), "{}", error);
+    }
+}
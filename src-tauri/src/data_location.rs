@@ -0,0 +1,152 @@
+// Where the database and the other data files live.
+//
+// In order of precedence: the `--data-dir <path>` command-line flag, the `ITODO_DATA_DIR`
+// environment variable, portable mode (a `data` folder next to the executable, enabled with
+// `--portable` or a file named `portable` beside the executable), the folder chosen in the app,
+// and finally the platform data directory.
+
+use crate::i18n::t;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const ENV_VAR: &str = "ITODO_DATA_DIR";
+const FLAG: &str = "--data-dir";
+const PORTABLE_FLAG: &str = "--portable";
+const PORTABLE_MARKER: &str = "portable";
+const PORTABLE_DIR: &str = "data";
+// Kept in the platform config directory, since it can't live inside the folder it points to
+const SETTING_FILE: &str = "location.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataDirSource {
+    CommandLine,
+    Environment,
+    Portable,
+    Setting,
+    Default,
+}
+
+impl DataDirSource {
+    // Whether the location can be changed from inside the app
+    pub fn is_override(self) -> bool {
+        matches!(self, DataDirSource::CommandLine | DataDirSource::Environment | DataDirSource::Portable)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataLocation {
+    pub dir: PathBuf,
+    pub source: DataDirSource,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LocationSetting {
+    data_dir: PathBuf,
+}
+
+pub fn resolve() -> DataLocation {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if let Some(dir) = flag_value(&args) {
+        return DataLocation { dir, source: DataDirSource::CommandLine };
+    }
+    if let Some(dir) = std::env::var_os(ENV_VAR).filter(|v| !v.is_empty()) {
+        return DataLocation { dir: PathBuf::from(dir), source: DataDirSource::Environment };
+    }
+    if let Some(exe_dir) = exe_dir() {
+        if args.iter().any(|a| a == PORTABLE_FLAG) || exe_dir.join(PORTABLE_MARKER).is_file() {
+            return DataLocation { dir: exe_dir.join(PORTABLE_DIR), source: DataDirSource::Portable };
+        }
+    }
+    if let Some(dir) = load_setting() {
+        return DataLocation { dir, source: DataDirSource::Setting };
+    }
+    DataLocation { dir: default_dir(), source: DataDirSource::Default }
+}
+
+pub fn default_dir() -> PathBuf {
+    match ProjectDirs::from("com", "itodo", "iToDo") {
+        Some(proj_dirs) => proj_dirs.data_dir().to_path_buf(),
+        None => PathBuf::from("."),
+    }
+}
+
+// Persists the folder chosen in the app; None goes back to the default location
+pub fn save_setting(dir: Option<&Path>) -> Result<(), String> {
+    let path = setting_path().ok_or_else(|| t!("No config directory available"))?;
+    match dir {
+        Some(dir) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let setting = LocationSetting { data_dir: dir.to_path_buf() };
+            let content = serde_json::to_string_pretty(&setting).map_err(|e| e.to_string())?;
+            fs::write(&path, content).map_err(|e| t!("Failed to save data folder setting: {}", e))
+        }
+        None => match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+            _ => Ok(()),
+        },
+    }
+}
+
+fn load_setting() -> Option<PathBuf> {
+    let content = fs::read_to_string(setting_path()?).ok()?;
+    let setting: LocationSetting = serde_json::from_str(&content).ok()?;
+    Some(setting.data_dir).filter(|d| !d.as_os_str().is_empty())
+}
+
+fn setting_path() -> Option<PathBuf> {
+    ProjectDirs::from("com", "itodo", "iToDo").map(|p| p.config_dir().join(SETTING_FILE))
+}
+
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe().ok()?.parent().map(Path::to_path_buf)
+}
+
+// "--data-dir <path>" or "--data-dir=<path>"
+fn flag_value(args: &[String]) -> Option<PathBuf> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == FLAG {
+            return iter.next().map(PathBuf::from);
+        }
+        if let Some(value) = arg.strip_prefix(FLAG).and_then(|v| v.strip_prefix('=')) {
+            return Some(PathBuf::from(value));
+        }
+    }
+    None
+}
+
+// Copies everything except the open database and its journal files, which the caller copies
+// with the backup API. `live_db` is relative to `from`.
+pub fn copy_data_files(from: &Path, to: &Path, live_db: &Path) -> Result<usize, String> {
+    let live_db = live_db.to_string_lossy();
+    let mut copied = 0;
+    let mut pending = vec![from.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir).map_err(|e| t!("Failed to read {}: {}", dir.display(), e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(relative) = path.strip_prefix(from) else { continue };
+            if relative.to_string_lossy().starts_with(live_db.as_ref()) {
+                continue;
+            }
+            let target = to.join(relative);
+            if path.is_dir() {
+                // Don't recurse into the destination when it sits inside the source
+                if path != to {
+                    fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+                    pending.push(path);
+                }
+            } else {
+                fs::copy(&path, &target).map_err(|e| t!("Failed to copy {}: {}", path.display(), e))?;
+                copied += 1;
+            }
+        }
+    }
+    Ok(copied)
+}
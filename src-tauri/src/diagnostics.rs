@@ -0,0 +1,132 @@
+// Diagnostics bundles for bug reports: a zip with what we need to look into a problem.
+//
+//   diagnostics.json   app, system and workspace info, schema, row counts, settings, startup error
+//   integrity.json     result of `integrity::check`, or the error it failed with
+//   logs/...           the current and rotated log files
+//   data.json          optional; the data with every text scrambled by `anonymize`, unseeded
+
+use crate::bundle::BundleData;
+use crate::i18n::t;
+use crate::integrity::IntegrityReport;
+use crate::settings::Settings;
+use crate::AboutInfo;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const DIAGNOSTICS_FILE: &str = "diagnostics.json";
+const INTEGRITY_FILE: &str = "integrity.json";
+const DATA_FILE: &str = "data.json";
+const LOGS_DIR: &str = "logs/";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TableInfo {
+    pub name: String,
+    pub sql: Option<String>,
+    pub rows: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaInfo {
+    // `PRAGMA user_version`
+    pub user_version: i64,
+    // Shape of exported data, see `bundle::DATA_VERSION`
+    pub data_version: u32,
+    pub sqlite_version: String,
+    pub tables: Vec<TableInfo>,
+}
+
+#[derive(Serialize)]
+pub struct Diagnostics {
+    pub created_at: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub about: AboutInfo,
+    pub workspace: String,
+    pub encrypted: bool,
+    // Why the app started in recovery mode, if it did
+    pub startup_error: Option<String>,
+    // None when the schema could not be read
+    pub schema: Option<SchemaInfo>,
+    pub settings: Settings,
+    pub log_files: Vec<String>,
+    pub includes_data: bool,
+}
+
+pub fn read_schema(conn: &Connection) -> Result<SchemaInfo, String> {
+    let user_version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let sqlite_version: String = conn
+        .query_row("SELECT sqlite_version()", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT name, sql FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let tables: Vec<(String, Option<String>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut infos = Vec::new();
+    for (name, sql) in tables {
+        let rows: i64 = conn
+            .query_row(&format!("SELECT COUNT(*) FROM \"{}\"", name.replace('"', "\"\"")), [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        infos.push(TableInfo { name, sql, rows });
+    }
+
+    Ok(SchemaInfo {
+        user_version,
+        data_version: crate::bundle::DATA_VERSION,
+        sqlite_version,
+        tables: infos,
+    })
+}
+
+pub fn write_diagnostics(
+    path: &Path,
+    diagnostics: &Diagnostics,
+    integrity: &Result<IntegrityReport, String>,
+    log_files: &[PathBuf],
+    data: Option<&BundleData>,
+) -> Result<(), String> {
+    let integrity = match integrity {
+        Ok(report) => serde_json::to_value(report).map_err(|e| e.to_string())?,
+        Err(e) => serde_json::json!({ "error": e }),
+    };
+
+    let mut entries: Vec<(String, Vec<u8>)> = vec![
+        (DIAGNOSTICS_FILE.to_string(), to_json(diagnostics)?),
+        (INTEGRITY_FILE.to_string(), to_json(&integrity)?),
+    ];
+    if let Some(data) = data {
+        entries.push((DATA_FILE.to_string(), to_json(data)?));
+    }
+    for file in log_files {
+        let Some(name) = file.file_name() else { continue };
+        let bytes = fs::read(file).map_err(|e| t!("Failed to read {}: {}", file.display(), e))?;
+        entries.push((format!("{}{}", LOGS_DIR, name.to_string_lossy()), bytes));
+    }
+
+    let file = File::create(path).map_err(|e| t!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, bytes) in &entries {
+        zip.start_file(name.as_str(), options).map_err(|e| e.to_string())?;
+        zip.write_all(bytes).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| t!("Failed to write file: {}", e))?;
+    Ok(())
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(value).map_err(|e| e.to_string())
+}
@@ -0,0 +1,204 @@
+// Optional encryption at rest with SQLCipher, available when built with `--features encryption`.
+//
+// The passphrase never reaches SQLCipher: a 256-bit key is derived from it with Argon2id and a
+// random salt, and the raw key is used for the database. The salt and cost parameters live next to
+// the database in `<file>.key.json`, which is also what marks a database as encrypted. Backups of
+// an encrypted database use the same key.
+
+use crate::i18n::t;
+use argon2::{Algorithm, Argon2, Params, Version};
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const KEY_FILE_SUFFIX: &str = "key.json";
+const KDF_ALGORITHM: &str = "argon2id";
+const KEY_LENGTH: usize = 32;
+const SALT_LENGTH: usize = 16;
+// OWASP's recommended minimum for Argon2id
+const MEMORY_COST_KIB: u32 = 19 * 1024;
+const TIME_COST: u32 = 2;
+const PARALLELISM: u32 = 1;
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+// Keys of the databases unlocked during this session, by canonical path
+static UNLOCKED: Mutex<Option<HashMap<PathBuf, DbKey>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyParams {
+    pub algorithm: String,
+    pub salt: String,
+    pub memory_cost: u32,
+    pub time_cost: u32,
+    pub parallelism: u32,
+}
+
+impl KeyParams {
+    pub fn generate() -> Self {
+        let salt: [u8; SALT_LENGTH] = rand::random();
+        KeyParams {
+            algorithm: KDF_ALGORITHM.to_string(),
+            salt: to_hex(&salt),
+            memory_cost: MEMORY_COST_KIB,
+            time_cost: TIME_COST,
+            parallelism: PARALLELISM,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct DbKey(String);
+
+impl DbKey {
+    // SQLCipher's raw key syntax, which skips its own passphrase derivation
+    fn sql_value(&self) -> String {
+        format!("x'{}'", self.0)
+    }
+}
+
+impl fmt::Debug for DbKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DbKey(..)")
+    }
+}
+
+pub fn is_supported() -> bool {
+    cfg!(feature = "encryption")
+}
+
+pub fn key_file_path(db_path: &Path) -> PathBuf {
+    let file_name = db_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    db_path.with_file_name(format!("{}.{}", file_name, KEY_FILE_SUFFIX))
+}
+
+pub fn is_encrypted(db_path: &Path) -> bool {
+    key_file_path(db_path).is_file()
+}
+
+pub fn load_params(db_path: &Path) -> Result<KeyParams, String> {
+    let content = fs::read_to_string(key_file_path(db_path)).map_err(|e| t!("Failed to read key parameters: {}", e))?;
+    let params: KeyParams = serde_json::from_str(&content).map_err(|e| t!("Invalid key parameters: {}", e))?;
+    if params.algorithm != KDF_ALGORITHM {
+        return Err(t!("Unsupported key derivation: {}", params.algorithm));
+    }
+    Ok(params)
+}
+
+pub fn save_params(db_path: &Path, params: &KeyParams) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(params).map_err(|e| e.to_string())?;
+    fs::write(key_file_path(db_path), content).map_err(|e| t!("Failed to save key parameters: {}", e))
+}
+
+pub fn validate_passphrase(passphrase: &str) -> Result<(), String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+        return Err(t!("The passphrase must be at least {} characters long", MIN_PASSPHRASE_LENGTH));
+    }
+    Ok(())
+}
+
+pub fn derive_key(passphrase: &str, params: &KeyParams) -> Result<DbKey, String> {
+    let salt = from_hex(&params.salt).ok_or_else(|| t!("Invalid salt in key parameters"))?;
+    let cost = Params::new(params.memory_cost, params.time_cost, params.parallelism, Some(KEY_LENGTH))
+        .map_err(|e| t!("Invalid key parameters: {}", e))?;
+    let mut key = [0u8; KEY_LENGTH];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, cost)
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| t!("Key derivation failed: {}", e))?;
+    Ok(DbKey(to_hex(&key)))
+}
+
+pub fn remember_key(db_path: &Path, key: DbKey) {
+    if let Ok(mut unlocked) = UNLOCKED.lock() {
+        unlocked.get_or_insert_with(HashMap::new).insert(canonical(db_path), key);
+    }
+}
+
+pub fn key_for(db_path: &Path) -> Option<DbKey> {
+    let unlocked = UNLOCKED.lock().ok()?;
+    unlocked.as_ref()?.get(&canonical(db_path)).cloned()
+}
+
+// Must run before anything else on the connection. Reading the schema is what tells a wrong
+// key apart, since SQLCipher only decrypts on first access.
+pub fn apply_key(conn: &Connection, key: &DbKey) -> Result<(), String> {
+    if !is_supported() {
+        return Err(t!("This build of iToDo can't open encrypted databases"));
+    }
+    conn.execute_batch(&format!("PRAGMA key = \"{}\";", key.sql_value()))
+        .map_err(|e| e.to_string())?;
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .map_err(|_| t!("Wrong passphrase, or the database is damaged"))?;
+    Ok(())
+}
+
+// Opens a database file with its key when it has one, for backups and verification
+pub fn open_file(path: &Path, flags: OpenFlags, key: Option<&DbKey>) -> Result<Connection, String> {
+    let conn = Connection::open_with_flags(path, flags).map_err(|e| e.to_string())?;
+    if let Some(key) = key {
+        apply_key(&conn, key)?;
+    }
+    Ok(conn)
+}
+
+// Writes an encrypted copy of a plaintext connection's main database to `target`
+pub fn export_encrypted(conn: &Connection, target: &Path, key: &DbKey) -> Result<(), String> {
+    if !is_supported() {
+        return Err(t!("This build of iToDo was made without encryption support"));
+    }
+    let _ = fs::remove_file(target);
+    conn.execute(
+        "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+        params![target.to_string_lossy(), key.sql_value()],
+    )
+    .map_err(|e| t!("Failed to create the encrypted database: {}", e))?;
+    let exported = conn
+        .query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+        .map_err(|e| t!("Failed to encrypt the database: {}", e));
+    conn.execute_batch("DETACH DATABASE encrypted").map_err(|e| e.to_string())?;
+    exported
+}
+
+// Encrypts a closed plaintext database file in place
+pub fn encrypt_file(path: &Path, key: &DbKey) -> Result<(), String> {
+    let encrypted = path.with_extension("encrypting");
+    {
+        let conn = Connection::open(path).map_err(|e| e.to_string())?;
+        export_encrypted(&conn, &encrypted, key)?;
+    }
+    open_file(&encrypted, OpenFlags::SQLITE_OPEN_READ_ONLY, Some(key))?;
+    fs::rename(&encrypted, path).map_err(|e| t!("Failed to replace {}: {}", path.display(), e))
+}
+
+// Changes the key of an open encrypted database
+pub fn rekey(conn: &Connection, key: &DbKey) -> Result<(), String> {
+    conn.execute_batch(&format!("PRAGMA rekey = \"{}\";", key.sql_value()))
+        .map_err(|e| t!("Failed to change the key: {}", e))
+}
+
+// Changes the key of a closed encrypted database file, e.g. a backup
+pub fn rekey_file(path: &Path, old_key: &DbKey, new_key: &DbKey) -> Result<(), String> {
+    let conn = open_file(path, OpenFlags::default(), Some(old_key))?;
+    rekey(&conn, new_key)
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,189 @@
+// Read-only HTML report for sharing lists with people who don't use the app.
+//
+// The page is a single file with inline CSS and no scripts or external resources. Each list
+// shows its open tasks first, then the completed ones; overdue tasks are highlighted.
+
+use crate::i18n::{self, t};
+use crate::repeat::{RepeatKind, RepeatRule};
+use crate::settings::Language;
+use crate::{List, Subtask, Task};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use std::collections::HashMap;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", "Microsoft YaHei", sans-serif; color: #1f2328; background: #f6f8fa; margin: 0; padding: 32px 16px; }
+main { max-width: 820px; margin: 0 auto; }
+h1 { font-size: 26px; margin: 0 0 4px; }
+.generated { color: #656d76; font-size: 13px; margin: 0 0 28px; }
+section.list { background: #fff; border: 1px solid #d0d7de; border-radius: 8px; margin-bottom: 24px; overflow: hidden; }
+section.list > h2 { font-size: 18px; margin: 0; padding: 14px 18px; border-left: 6px solid var(--list-color, #0078d4); border-bottom: 1px solid #d0d7de; }
+section.list > h2 .count { color: #656d76; font-weight: normal; font-size: 14px; margin-left: 8px; }
+h3 { font-size: 13px; text-transform: uppercase; letter-spacing: .04em; color: #656d76; margin: 0; padding: 12px 18px 4px; }
+ul.tasks { list-style: none; margin: 0; padding: 0 0 8px; }
+li.task { padding: 10px 18px; border-top: 1px solid #eaeef2; }
+li.task:first-child { border-top: none; }
+.title { font-weight: 600; }
+.important { color: #d29922; margin-left: 6px; }
+.meta { font-size: 13px; color: #656d76; margin-top: 4px; }
+.meta span + span::before { content: "·"; margin: 0 6px; }
+li.overdue { background: #fff5f5; border-left: 4px solid #cf222e; }
+li.overdue .due { color: #cf222e; font-weight: 600; }
+li.completed .title { text-decoration: line-through; color: #656d76; font-weight: normal; }
+.notes { white-space: pre-wrap; font-size: 14px; margin-top: 6px; padding: 8px 10px; background: #f6f8fa; border-radius: 6px; }
+ul.subtasks { margin: 6px 0 0; padding-left: 20px; font-size: 14px; }
+ul.subtasks .done { text-decoration: line-through; color: #656d76; }
+.empty { color: #656d76; padding: 12px 18px; margin: 0; }
+"#;
+
+pub fn render_report(title: &str, lists: &[List], tasks: &[Task], subtasks: &HashMap<String, Vec<Subtask>>) -> String {
+    let today = Local::now().date_naive();
+    let mut out = String::new();
+
+    let lang = match i18n::language() {
+        Language::ZhCn => "zh-CN",
+        Language::EnUs => "en-US",
+    };
+    out.push_str(&format!("<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n", lang));
+    out.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    out.push_str(&format!("<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<main>\n", escape(title), STYLE));
+    out.push_str(&format!("<h1>{}</h1>\n", escape(title)));
+    out.push_str(&format!(
+        "<p class=\"generated\">{}</p>\n",
+        t!("Generated {}", Local::now().format("%Y-%m-%d %H:%M"))
+    ));
+
+    for list in lists {
+        let list_tasks: Vec<&Task> = tasks.iter().filter(|t| t.list_id == list.id).collect();
+        let (completed, open): (Vec<&Task>, Vec<&Task>) = list_tasks.iter().partition(|t| t.is_completed);
+
+        let color = list.color.as_deref().filter(|c| is_css_color(c)).unwrap_or("#0078d4");
+        out.push_str(&format!("<section class=\"list\" style=\"--list-color: {}\">\n", color));
+        out.push_str(&format!(
+            "<h2>{}<span class=\"count\">{}</span></h2>\n",
+            escape(&list.name),
+            t!("{} open, {} completed", open.len(), completed.len())
+        ));
+
+        if list_tasks.is_empty() {
+            out.push_str(&format!("<p class=\"empty\">{}</p>\n", t!("No tasks")));
+        }
+        for (heading, group) in [(t!("Open"), &open), (t!("Completed"), &completed)] {
+            if group.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("<h3>{}</h3>\n<ul class=\"tasks\">\n", heading));
+            for task in group.iter() {
+                render_task(&mut out, task, subtasks.get(&task.id), today);
+            }
+            out.push_str("</ul>\n");
+        }
+        out.push_str("</section>\n");
+    }
+
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+fn render_task(out: &mut String, task: &Task, subtasks: Option<&Vec<Subtask>>, today: NaiveDate) {
+    let due = task.due_date.as_deref().and_then(parse_date);
+    let overdue = !task.is_completed && due.is_some_and(|d| d < today);
+
+    let mut classes = vec!["task"];
+    if task.is_completed {
+        classes.push("completed");
+    }
+    if overdue {
+        classes.push("overdue");
+    }
+    out.push_str(&format!("<li class=\"{}\">\n", classes.join(" ")));
+    out.push_str(&format!("<span class=\"title\">{}</span>", escape(&task.title)));
+    if task.is_important {
+        out.push_str(&format!("<span class=\"important\" title=\"{}\">★</span>", t!("Important")));
+    }
+    out.push('\n');
+
+    let mut meta = Vec::new();
+    if let Some(date) = due {
+        let date = date.format("%Y-%m-%d");
+        let label = if overdue { t!("Overdue {}", date) } else { t!("Due {}", date) };
+        meta.push(format!("<span class=\"due\">{}</span>", label));
+    }
+    if let Some(date) = task.start_date.as_deref().and_then(parse_date) {
+        meta.push(format!("<span>{}</span>", t!("Starts {}", date.format("%Y-%m-%d"))));
+    }
+    if let Some(rule) = task.repeat_rule.as_deref().and_then(RepeatRule::parse) {
+        meta.push(format!("<span>{}</span>", describe_repeat(&rule)));
+    }
+    if task.is_completed {
+        if let Ok(done) = DateTime::parse_from_rfc3339(&task.updated_at) {
+            meta.push(format!(
+                "<span>{}</span>",
+                t!("Completed {}", done.with_timezone(&Local).format("%Y-%m-%d"))
+            ));
+        }
+    }
+    if !meta.is_empty() {
+        out.push_str(&format!("<div class=\"meta\">{}</div>\n", meta.join("")));
+    }
+
+    if let Some(notes) = task.content.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        out.push_str(&format!("<div class=\"notes\">{}</div>\n", escape(notes)));
+    }
+
+    if let Some(subtasks) = subtasks.filter(|s| !s.is_empty()) {
+        out.push_str("<ul class=\"subtasks\">\n");
+        for subtask in subtasks {
+            let (mark, class) = if subtask.is_completed { ("☑", " class=\"done\"") } else { ("☐", "") };
+            out.push_str(&format!("<li{}>{} {}</li>\n", class, mark, escape(&subtask.title)));
+        }
+        out.push_str("</ul>\n");
+    }
+    out.push_str("</li>\n");
+}
+
+fn describe_repeat(rule: &RepeatRule) -> String {
+    let interval = rule.interval();
+    let mut text = match (rule.kind, interval) {
+        (RepeatKind::Daily, 1) => t!("Repeats every day"),
+        (RepeatKind::Weekly, 1) => t!("Repeats every week"),
+        (RepeatKind::Monthly, 1) => t!("Repeats every month"),
+        (RepeatKind::Yearly, 1) => t!("Repeats every year"),
+        (RepeatKind::Daily, n) => t!("Repeats every {} days", n),
+        (RepeatKind::Weekly, n) => t!("Repeats every {} weeks", n),
+        (RepeatKind::Monthly, n) => t!("Repeats every {} months", n),
+        (RepeatKind::Yearly, n) => t!("Repeats every {} years", n),
+    };
+    if let Some(days) = rule.days.as_ref().filter(|d| !d.is_empty()) {
+        text.push_str(&t!(" on {}", days.join(", ")));
+    }
+    escape(&text)
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc).date_naive());
+    }
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+// List colors end up in a style attribute, so only plain hex colors are let through
+fn is_css_color(value: &str) -> bool {
+    value
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
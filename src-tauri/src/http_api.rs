@@ -0,0 +1,438 @@
+// Local REST API for scripts and dashboards, off until it is turned on in the app.
+//
+// The server only listens on 127.0.0.1 and wants `Authorization: Bearer <token>` on every request.
+// Port and token are kept in `api.json` in the data folder. Bodies are the JSON of the `Task`,
+// `List` and input types the app uses; errors are `{"error": "..."}` with a matching status code.
+// While the app is locked every request gets 423, and 503 while it has no database open.
+//
+//   GET    /api/lists           all lists
+//   POST   /api/lists           create a list from a `CreateListInput`                     201
+//   GET    /api/lists/{id}      one list
+//   PATCH  /api/lists/{id}      change a list, with the fields of `UpdateListInput`
+//   DELETE /api/lists/{id}      delete a list and its tasks; 409 for the default list      204
+//   GET    /api/tasks           tasks, narrowed by `view=today|planned|important|completed`
+//                               or `q=<text>` (search), and by `list_id=<id>`
+//   POST   /api/tasks           create a task from a `CreateTaskInput`                     201
+//   GET    /api/tasks/{id}      one task
+//   PATCH  /api/tasks/{id}      change a task, with the fields of `UpdateTaskInput`
+//   DELETE /api/tasks/{id}      delete a task with its subtasks and tags                   204
+
+use crate::i18n::t;
+use crate::{CreateListInput, CreateTaskInput, UpdateListInput, UpdateTaskInput};
+use rusqlite::Connection;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use tiny_http::{Header, Response, Server};
+
+pub const DEFAULT_PORT: u16 = 7654;
+const MAX_BODY: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub enabled: bool,
+    pub port: u16,
+    // Generated the first time the API is turned on
+    pub token: String,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        ApiConfig {
+            enabled: false,
+            port: DEFAULT_PORT,
+            token: String::new(),
+        }
+    }
+}
+
+impl ApiConfig {
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| t!("Failed to save API settings: {}", e))
+    }
+
+    pub fn validate_port(port: u16) -> Result<(), String> {
+        if port < 1024 {
+            return Err(t!("Choose a port from 1024 to 65535"));
+        }
+        Ok(())
+    }
+}
+
+pub fn generate_token() -> String {
+    let bytes: [u8; 32] = rand::random();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: Option<Value>,
+}
+
+impl ApiResponse {
+    fn json<T: Serialize>(status: u16, value: &T) -> Self {
+        match serde_json::to_value(value) {
+            Ok(body) => ApiResponse { status, body: Some(body) },
+            Err(e) => ApiResponse::error(500, e.to_string()),
+        }
+    }
+
+    fn no_content() -> Self {
+        ApiResponse { status: 204, body: None }
+    }
+
+    pub fn error(status: u16, message: impl Into<String>) -> Self {
+        ApiResponse {
+            status,
+            body: Some(serde_json::json!({ "error": message.into() })),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+// ============== Server ==============
+
+pub fn bind(port: u16) -> Result<Server, String> {
+    Server::http(("127.0.0.1", port)).map_err(|e| t!("Failed to start the API server on port {}: {}", port, e))
+}
+
+// Answers requests until `Server::unblock` is called. `handle` gets the method, the URL with its
+// query string and the body of every authorized request.
+pub fn serve(server: &Server, token: &str, mut handle: impl FnMut(&str, &str, &str) -> ApiResponse) {
+    for mut request in server.incoming_requests() {
+        let authorization = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .map(|h| h.value.as_str().to_string());
+
+        let response = if !is_authorized(authorization.as_deref(), token) {
+            ApiResponse::error(401, t!("Missing or wrong API token"))
+        } else {
+            let mut body = String::new();
+            match request.as_reader().take(MAX_BODY + 1).read_to_string(&mut body) {
+                Err(_) => ApiResponse::error(400, t!("The request body is not valid UTF-8")),
+                Ok(_) if body.len() as u64 > MAX_BODY => ApiResponse::error(413, t!("The request body is too large")),
+                Ok(_) => handle(request.method().as_str(), request.url(), &body),
+            }
+        };
+
+        let status = response.status;
+        let bytes = response.body.map(|b| b.to_string().into_bytes()).unwrap_or_default();
+        let mut reply = Response::from_data(bytes).with_status_code(status);
+        if status != 204 {
+            reply.add_header(header("Content-Type", "application/json"));
+        }
+        if status == 401 {
+            reply.add_header(header("WWW-Authenticate", "Bearer"));
+        }
+        // The client may be gone already; nothing to do about it
+        let _ = request.respond(reply);
+    }
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("static header is valid")
+}
+
+// Compares in constant time, so the token can't be guessed byte by byte from response times
+fn is_authorized(authorization: Option<&str>, token: &str) -> bool {
+    let Some(given) = authorization.and_then(|a| a.strip_prefix("Bearer ")) else { return false };
+    let (given, token) = (given.trim().as_bytes(), token.as_bytes());
+    !token.is_empty() && given.len() == token.len() && given.iter().zip(token).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+// ============== Routes ==============
+
+pub fn respond(conn: &Connection, locked: bool, recovering: bool, method: &str, url: &str, body: &str) -> ApiResponse {
+    if locked {
+        return ApiResponse::error(423, crate::app_lock::locked_error());
+    }
+    if recovering {
+        return ApiResponse::error(503, t!("The database could not be opened; recover it in the app first"));
+    }
+    route(conn, method, url, body)
+}
+
+pub fn route(conn: &Connection, method: &str, url: &str, body: &str) -> ApiResponse {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let result = match segments.as_slice() {
+        ["api", "lists"] => lists(conn, method, body),
+        ["api", "lists", id] => list(conn, method, id, body),
+        ["api", "tasks"] => tasks(conn, method, &parse_query(query), body),
+        ["api", "tasks", id] => task(conn, method, id, body),
+        _ => Err(ApiResponse::error(404, t!("No such resource: {}", path))),
+    };
+    result.unwrap_or_else(|response| response)
+}
+
+type Handled = Result<ApiResponse, ApiResponse>;
+
+fn lists(conn: &Connection, method: &str, body: &str) -> Handled {
+    match method {
+        "GET" => Ok(ApiResponse::json(200, &crate::load_lists(conn).map_err(server_error)?)),
+        "POST" => {
+            let input: CreateListInput = parse_body(body)?;
+            if input.name.trim().is_empty() {
+                return Err(ApiResponse::error(400, t!("A list needs a name")));
+            }
+            Ok(ApiResponse::json(201, &crate::add_list(conn, input).map_err(server_error)?))
+        }
+        _ => Err(method_not_allowed()),
+    }
+}
+
+fn list(conn: &Connection, method: &str, id: &str, body: &str) -> Handled {
+    let list = crate::load_list(conn, id).map_err(|_| ApiResponse::error(404, t!("No list with id {}", id)))?;
+    match method {
+        "GET" => Ok(ApiResponse::json(200, &list)),
+        "PATCH" => {
+            let input: UpdateListInput = parse_body(&with_id(body, id)?)?;
+            Ok(ApiResponse::json(200, &crate::edit_list(conn, input).map_err(server_error)?))
+        }
+        "DELETE" => {
+            if list.is_default {
+                return Err(ApiResponse::error(409, t!("Cannot delete default list")));
+            }
+            crate::remove_list(conn, id).map_err(server_error)?;
+            Ok(ApiResponse::no_content())
+        }
+        _ => Err(method_not_allowed()),
+    }
+}
+
+fn tasks(conn: &Connection, method: &str, query: &HashMap<String, String>, body: &str) -> Handled {
+    match method {
+        "GET" => {
+            let view = query.get("view").map(String::as_str);
+            let mut tasks = match (view, query.get("q")) {
+                (Some(_), Some(_)) => return Err(ApiResponse::error(400, t!("Use either view or q, not both"))),
+                (None, Some(text)) => crate::find_tasks(conn, text),
+                (Some("today"), None) => crate::load_today_tasks(conn),
+                (Some("planned"), None) => crate::load_planned_tasks(conn),
+                (Some("important"), None) => crate::load_important_tasks(conn),
+                (Some("completed"), None) => crate::load_completed_tasks(conn),
+                (Some(other), None) => return Err(ApiResponse::error(400, t!("Unknown view: {}", other))),
+                (None, None) => crate::load_tasks(conn, None),
+            }
+            .map_err(server_error)?;
+            if let Some(list_id) = query.get("list_id") {
+                tasks.retain(|t| &t.list_id == list_id);
+            }
+            Ok(ApiResponse::json(200, &tasks))
+        }
+        "POST" => {
+            let input: CreateTaskInput = parse_body(body)?;
+            if input.title.trim().is_empty() {
+                return Err(ApiResponse::error(400, t!("A task needs a title")));
+            }
+            crate::load_list(conn, &input.list_id).map_err(|_| ApiResponse::error(400, t!("No list with id {}", input.list_id)))?;
+            Ok(ApiResponse::json(201, &crate::add_task(conn, input).map_err(server_error)?))
+        }
+        _ => Err(method_not_allowed()),
+    }
+}
+
+fn task(conn: &Connection, method: &str, id: &str, body: &str) -> Handled {
+    let task = crate::load_task(conn, id).map_err(|_| ApiResponse::error(404, t!("No task with id {}", id)))?;
+    match method {
+        "GET" => Ok(ApiResponse::json(200, &task)),
+        "PATCH" => {
+            let input: UpdateTaskInput = parse_body(&with_id(body, id)?)?;
+            if let Some(list_id) = &input.list_id {
+                crate::load_list(conn, list_id).map_err(|_| ApiResponse::error(400, t!("No list with id {}", list_id)))?;
+            }
+            Ok(ApiResponse::json(200, &crate::edit_task(conn, input).map_err(server_error)?))
+        }
+        "DELETE" => {
+            crate::remove_task(conn, id).map_err(server_error)?;
+            Ok(ApiResponse::no_content())
+        }
+        _ => Err(method_not_allowed()),
+    }
+}
+
+fn parse_body<T: DeserializeOwned>(body: &str) -> Result<T, ApiResponse> {
+    serde_json::from_str(body).map_err(|e| ApiResponse::error(400, t!("Invalid request body: {}", e)))
+}
+
+// PATCH bodies leave out the id, which comes from the path
+fn with_id(body: &str, id: &str) -> Result<String, ApiResponse> {
+    let mut value: Value = parse_body(body)?;
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| ApiResponse::error(400, t!("The request body must be a JSON object")))?;
+    object.insert("id".to_string(), Value::String(id.to_string()));
+    Ok(value.to_string())
+}
+
+fn method_not_allowed() -> ApiResponse {
+    ApiResponse::error(405, t!("Method not allowed"))
+}
+
+fn server_error(message: String) -> ApiResponse {
+    ApiResponse::error(500, message)
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpStream;
+    use std::sync::Arc;
+
+    fn database() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::init_database(&conn).unwrap();
+        conn
+    }
+
+    fn call(conn: &Connection, method: &str, url: &str, body: &str) -> (u16, Value) {
+        let response = route(conn, method, url, body);
+        (response.status, response.body.unwrap_or(Value::Null))
+    }
+
+    #[test]
+    fn creates_changes_and_deletes_lists() {
+        let conn = database();
+        let (status, lists) = call(&conn, "GET", "/api/lists", "");
+        assert_eq!(status, 200);
+        let default_id = lists[0]["id"].as_str().unwrap().to_string();
+
+        assert_eq!(call(&conn, "POST", "/api/lists", r#"{"name": " "}"#).0, 400);
+        let (status, list) = call(&conn, "POST", "/api/lists", r#"{"name": "Errands"}"#);
+        assert_eq!(status, 201);
+        let id = list["id"].as_str().unwrap().to_string();
+        let (status, list) = call(&conn, "PATCH", &format!("/api/lists/{}", id), r#"{"name": "Chores"}"#);
+        assert_eq!((status, list["name"].as_str()), (200, Some("Chores")));
+
+        assert_eq!(call(&conn, "DELETE", &format!("/api/lists/{}", default_id), "").0, 409);
+        assert_eq!(call(&conn, "DELETE", &format!("/api/lists/{}", id), ""), (204, Value::Null));
+        assert_eq!(call(&conn, "GET", &format!("/api/lists/{}", id), "").0, 404);
+    }
+
+    #[test]
+    fn creates_filters_and_deletes_tasks() {
+        let conn = database();
+        let list_id = crate::default_list_id(&conn).unwrap();
+
+        assert_eq!(call(&conn, "POST", "/api/tasks", "{not json").0, 400);
+        assert_eq!(call(&conn, "POST", "/api/tasks", r#"{"list_id": "nope", "title": "Call Sam"}"#).0, 400);
+        let body = serde_json::json!({ "list_id": list_id, "title": "Call Sam" }).to_string();
+        let (status, task) = call(&conn, "POST", "/api/tasks", &body);
+        assert_eq!(status, 201);
+        let id = task["id"].as_str().unwrap().to_string();
+        let (status, task) = call(&conn, "PATCH", &format!("/api/tasks/{}", id), r#"{"is_important": true}"#);
+        assert_eq!((status, task["is_important"].as_bool()), (200, Some(true)));
+
+        let (status, found) = call(&conn, "GET", "/api/tasks?view=important", "");
+        assert_eq!((status, found.as_array().unwrap().len()), (200, 1));
+        assert_eq!(call(&conn, "GET", "/api/tasks?q=call+sam", "").1.as_array().unwrap().len(), 1);
+        assert_eq!(call(&conn, "GET", "/api/tasks?view=someday", "").0, 400);
+        assert_eq!(call(&conn, "GET", "/api/tasks?view=today&q=x", "").0, 400);
+
+        assert_eq!(call(&conn, "PATCH", &format!("/api/tasks/{}", id), "[]").0, 400);
+        assert_eq!(call(&conn, "DELETE", &format!("/api/tasks/{}", id), "").0, 204);
+        assert_eq!(call(&conn, "DELETE", &format!("/api/tasks/{}", id), "").0, 404);
+    }
+
+    #[test]
+    fn rejects_unknown_paths_and_methods() {
+        let conn = database();
+        assert_eq!(call(&conn, "GET", "/api/everything", "").0, 404);
+        assert_eq!(call(&conn, "PUT", "/api/lists", "").0, 405);
+        assert_eq!(call(&conn, "POST", "/api/tasks/x/y", "").0, 404);
+    }
+
+    #[test]
+    fn refuses_requests_while_locked_or_recovering() {
+        let conn = database();
+        assert_eq!(respond(&conn, true, false, "GET", "/api/lists", "").status, 423);
+        assert_eq!(respond(&conn, false, true, "GET", "/api/lists", "").status, 503);
+        assert_eq!(respond(&conn, false, false, "GET", "/api/lists", "").status, 200);
+    }
+
+    // Sends a raw request to `serve` and returns the status code
+    fn send(port: u16, authorization: Option<&str>, body: &str) -> u16 {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let mut request = format!("POST /api/lists HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n", body.len());
+        if let Some(authorization) = authorization {
+            request.push_str(&format!("Authorization: {}\r\n", authorization));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).unwrap();
+        // The server may answer before reading a body that is too large
+        let _ = stream.write_all(body.as_bytes());
+        let mut status_line = String::new();
+        BufReader::new(stream).read_line(&mut status_line).unwrap();
+        status_line.split_whitespace().nth(1).unwrap().parse().unwrap()
+    }
+
+    #[test]
+    fn checks_the_token_and_body_size() {
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let port = server.server_addr().to_ip().unwrap().port();
+        let serving = server.clone();
+        let thread = std::thread::spawn(move || serve(&serving, "secret", |_, _, _| ApiResponse::no_content()));
+
+        assert_eq!(send(port, None, "{}"), 401);
+        assert_eq!(send(port, Some("Bearer wrong!"), "{}"), 401);
+        assert_eq!(send(port, Some("Bearer secret"), "{}"), 204);
+        assert_eq!(send(port, Some("Bearer secret"), &"x".repeat(MAX_BODY as usize + 1)), 413);
+
+        server.unblock();
+        thread.join().unwrap();
+    }
+}
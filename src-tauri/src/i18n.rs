@@ -0,0 +1,237 @@
+// Translations for the text the backend shows to people: error messages, the default list name and
+// report headings. Log messages stay in English.
+//
+// Lookups are keyed by the English text, gettext style, so a missing translation falls back to
+// English. `t!` translates a literal and fills its `{}` placeholders in order. The language follows
+// the `language` setting.
+
+use crate::settings::Language;
+use std::fmt::Display;
+use std::sync::RwLock;
+
+static LANGUAGE: RwLock<Language> = RwLock::new(Language::ZhCn);
+
+// Exported for itodo-cli, which shares the catalog and the language setting
+#[macro_export]
+macro_rules! t {
+    ($text:literal) => {
+        $crate::i18n::translate($text).to_string()
+    };
+    ($text:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill($crate::i18n::translate($text), &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+pub use t;
+
+pub fn set_language(language: Language) {
+    if let Ok(mut current) = LANGUAGE.write() {
+        *current = language;
+    }
+}
+
+pub fn language() -> Language {
+    LANGUAGE.read().map(|l| *l).unwrap_or(Language::ZhCn)
+}
+
+pub fn translate(text: &'static str) -> &'static str {
+    translate_to(language(), text)
+}
+
+pub fn translate_to(language: Language, text: &'static str) -> &'static str {
+    match language {
+        Language::EnUs => text,
+        Language::ZhCn => ZH_CN
+            .iter()
+            .find(|(en, _)| *en == text)
+            .map(|(_, zh)| *zh)
+            .unwrap_or(text),
+    }
+}
+
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(index) = rest.find("{}") {
+        out.push_str(&rest[..index]);
+        match args.next() {
+            Some(arg) => out.push_str(&arg.to_string()),
+            None => out.push_str("{}"),
+        }
+        rest = &rest[index + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+const ZH_CN: &[(&str, &str)] = &[
+    (" on {}", "，{}"),
+    ("A list needs a name", "列表需要名称"),
+    ("A task needs a title", "任务需要标题"),
+    ("A title column mapping is required", "必须映射标题列"),
+    ("A workspace named \"{}\" already exists", "已存在名为“{}”的工作区"),
+    ("At least one backup must be kept", "至少需要保留一个备份"),
+    ("Backup does not contain iToDo data", "备份中没有 iToDo 数据"),
+    ("Backup failed the integrity check: {}", "备份未通过完整性检查：{}"),
+    ("Backup failed: {}", "备份失败：{}"),
+    ("Backup interval must be between 1 hour and 30 days", "备份间隔必须在 1 小时到 30 天之间"),
+    ("Backup is not a valid database: {}", "备份不是有效的数据库：{}"),
+    ("Backup not found: {}", "找不到备份：{}"),
+    ("Bundle contains an unsafe path: {}", "数据包中包含不安全的路径：{}"),
+    ("Bundle entry {} is larger than {} MB", "数据包条目 {} 超过 {} MB"),
+    ("Bundle is larger than {} MB unpacked", "数据包解压后超过 {} MB"),
+    ("Bundle is missing {}", "数据包缺少 {}"),
+    ("Cannot delete default list", "无法删除默认列表"),
+    ("Checksum mismatch for {}; the bundle is corrupted", "{} 的校验和不匹配，数据包已损坏"),
+    ("Choose a port from 1024 to 65535", "请选择 1024 到 65535 之间的端口"),
+    ("Column \"{}\" mapped to {} not found in CSV header", "CSV 表头中找不到列“{}”（映射到 {}）"),
+    ("Completed", "已完成"),
+    ("Completed {}", "完成于 {}"),
+    ("Database is not writable: {}", "数据库不可写：{}"),
+    ("Due {}", "截止 {}"),
+    ("Expected YYYY-MM-DD, today or tomorrow", "应为 YYYY-MM-DD、today 或 tomorrow"),
+    ("Expected an object for \"{}\"", "“{}”应为对象"),
+    ("Exported {} tasks to {}", "已将 {} 个任务导出到 {}"),
+    ("Failed to change the key: {}", "更改密钥失败：{}"),
+    ("Failed to copy backup: {}", "复制备份失败：{}"),
+    ("Failed to copy the database: {}", "复制数据库失败：{}"),
+    ("Failed to copy the key parameters: {}", "复制密钥参数失败：{}"),
+    ("Failed to copy {}: {}", "复制 {} 失败：{}"),
+    ("Failed to create backup folder: {}", "创建备份文件夹失败：{}"),
+    ("Failed to create bundle: {}", "创建数据包失败：{}"),
+    ("Failed to create the encrypted database: {}", "创建加密数据库失败：{}"),
+    ("Failed to create {}: {}", "创建 {} 失败：{}"),
+    ("Failed to delete {}: {}", "删除 {} 失败：{}"),
+    ("Failed to encrypt the database: {}", "加密数据库失败：{}"),
+    ("Failed to hash the passphrase: {}", "处理密码失败：{}"),
+    ("Failed to initialize database: {}", "初始化数据库失败：{}"),
+    ("Failed to keep the key parameters: {}", "保存密钥参数失败：{}"),
+    ("Failed to move the damaged database aside: {}", "移走损坏的数据库失败：{}"),
+    ("Failed to move {} aside: {}", "无法将 {} 移到一旁：{}"),
+    ("Failed to move {} aside: {}", "移走 {} 失败：{}"),
+    ("Failed to open backup: {}", "打开备份失败：{}"),
+    ("Failed to open bundle: {}", "打开数据包失败：{}"),
+    ("Failed to open database for backup: {}", "打开数据库以备份失败：{}"),
+    ("Failed to open database: {}", "打开数据库失败：{}"),
+    ("Failed to parse Google Tasks data: {}", "解析 Google Tasks 数据失败：{}"),
+    ("Failed to parse Microsoft To Do data: {}", "解析 Microsoft To Do 数据失败：{}"),
+    ("Failed to parse Taskwarrior data on line {}: {}", "解析 Taskwarrior 数据第 {} 行失败：{}"),
+    ("Failed to parse Taskwarrior data: {}", "解析 Taskwarrior 数据失败：{}"),
+    ("Failed to parse Todoist backup: {}", "解析 Todoist 备份失败：{}"),
+    ("Failed to parse Trello board: {}", "解析 Trello 看板失败：{}"),
+    ("Failed to parse import data: {}", "解析导入数据失败：{}"),
+    ("Failed to parse {}: {}", "解析 {} 失败：{}"),
+    ("Failed to read CSV header: {}", "读取 CSV 表头失败：{}"),
+    ("Failed to read Todoist CSV header: {}", "读取 Todoist CSV 表头失败：{}"),
+    ("Failed to read Todoist CSV: {}", "读取 Todoist CSV 失败：{}"),
+    ("Failed to read attachment {}: {}", "读取附件 {} 失败：{}"),
+    ("Failed to read key parameters: {}", "读取密钥参数失败：{}"),
+    ("Failed to read {}: {}", "读取 {} 失败：{}"),
+    ("Failed to rebuild indexes: {}", "重建索引失败：{}"),
+    ("Failed to remove backup {}: {}", "删除备份 {} 失败：{}"),
+    ("Failed to replace the database: {}", "替换数据库失败：{}"),
+    ("Failed to replace {}: {}", "替换 {} 失败：{}"),
+    ("Failed to restore backup: {}", "恢复备份失败：{}"),
+    ("Failed to save API settings: {}", "保存 API 设置失败：{}"),
+    ("Failed to save data folder setting: {}", "保存数据文件夹设置失败：{}"),
+    ("Failed to save key parameters: {}", "保存密钥参数失败：{}"),
+    ("Failed to save lock settings: {}", "保存锁定设置失败：{}"),
+    ("Failed to save settings: {}", "保存设置失败：{}"),
+    ("Failed to save workspaces: {}", "保存工作区失败：{}"),
+    ("Failed to start the API server on port {}: {}", "无法在端口 {} 上启动 API 服务：{}"),
+    ("Failed to store backup: {}", "保存备份失败：{}"),
+    ("Failed to write attachment {}: {}", "写入附件 {} 失败：{}"),
+    ("Failed to write bundle: {}", "写入数据包失败：{}"),
+    ("Failed to write file: {}", "写入文件失败：{}"),
+    ("Failed to write {}: {}", "写入 {} 失败：{}"),
+    ("Generated {}", "生成于 {}"),
+    ("Important", "重要"),
+    ("Imported {} tasks", "已导入 {} 个任务"),
+    ("Invalid CSV delimiter: \"{}\"", "无效的 CSV 分隔符：“{}”"),
+    ("Invalid backup id: {}", "无效的备份 ID：{}"),
+    ("Invalid key parameters: {}", "无效的密钥参数：{}"),
+    ("Invalid request body: {}", "请求正文无效：{}"),
+    ("Invalid salt in key parameters", "密钥参数中的盐值无效"),
+    ("Invalid settings: {}", "无效的设置：{}"),
+    ("Invalid {} \"{}\"", "无效的{}“{}”"),
+    ("Invalid {} value \"{}\"", "无效的 {} 值“{}”"),
+    ("Key derivation failed: {}", "密钥派生失败：{}"),
+    ("List not found: {}", "未找到列表：{}"),
+    ("Malformed line in {}: {}", "{} 中有格式错误的行：{}"),
+    ("Method not allowed", "不允许使用此方法"),
+    ("Mismatched END:{} for BEGIN:{} in iCalendar data", "iCalendar 数据中 END:{} 与 BEGIN:{} 不匹配"),
+    ("Missing or wrong API token", "API 令牌缺失或错误"),
+    ("Missing title", "缺少标题"),
+    ("More than one task id starts with {}", "有多个任务 ID 以 {} 开头"),
+    ("My Day", "我的一天"),
+    ("No VCALENDAR found in iCalendar data", "iCalendar 数据中没有 VCALENDAR"),
+    ("No config directory available", "没有可用的配置目录"),
+    ("No database is open", "没有打开的数据库"),
+    ("No default list to move orphaned tasks to: {}", "没有可接收孤立任务的默认列表：{}"),
+    ("No list named {}", "没有名为 {} 的列表"),
+    ("No list with id {}", "没有 ID 为 {} 的列表"),
+    ("No lock passphrase is set", "尚未设置锁定密码"),
+    ("No such resource: {}", "没有此资源：{}"),
+    ("No task with id {}", "没有 ID 为 {} 的任务"),
+    ("No tasks", "没有任务"),
+    ("No usable backup was found", "没有找到可用的备份"),
+    ("Not a Todoist CSV export: TYPE and CONTENT columns are required", "不是 Todoist CSV 导出文件：需要 TYPE 和 CONTENT 列"),
+    ("Not a valid .itodo bundle: {}", "不是有效的 .itodo 数据包：{}"),
+    ("Not an iToDo database: {}", "不是 iToDo 数据库：{}"),
+    ("Not an iToDo export: {}", "不是 iToDo 导出文件：{}"),
+    ("Open", "未完成"),
+    ("Overdue {}", "已逾期 {}"),
+    ("Please choose an absolute folder path", "请选择绝对文件夹路径"),
+    ("Repeats every day", "每天重复"),
+    ("Repeats every month", "每月重复"),
+    ("Repeats every week", "每周重复"),
+    ("Repeats every year", "每年重复"),
+    ("Repeats every {} days", "每 {} 天重复"),
+    ("Repeats every {} months", "每 {} 个月重复"),
+    ("Repeats every {} weeks", "每 {} 周重复"),
+    ("Repeats every {} years", "每 {} 年重复"),
+    ("Starts {}", "开始于 {}"),
+    ("Switch to another workspace before deleting this one", "请先切换到其他工作区再删除此工作区"),
+    ("The app is not in recovery mode", "应用未处于恢复模式"),
+    ("The app lock settings in {} can't be read, so iToDo stays locked until the file is fixed or removed: {}", "无法读取 {} 中的应用锁设置，在修复或删除该文件之前 iToDo 将保持锁定：{}"),
+    ("The copied database could not be verified: {}", "无法校验复制的数据库：{}"),
+    ("The current passphrase is wrong", "当前密码错误"),
+    ("The data folder is set by --data-dir, {} or portable mode and can't be changed from the app", "数据文件夹由 --data-dir、{} 或便携模式指定，无法在应用中更改"),
+    ("The data is already stored in this folder", "数据已存放在此文件夹中"),
+    ("The database could not be opened; recover it in the app first", "无法打开数据库，请先在应用中恢复"),
+    ("The database is already encrypted", "数据库已加密"),
+    ("The database is encrypted. Enter the passphrase to unlock it.", "数据库已加密，请输入密码解锁。"),
+    ("The database is not encrypted", "数据库未加密"),
+    ("The default workspace can't be deleted", "无法删除默认工作区"),
+    ("The encrypted copy could not be verified: {}", "无法校验加密副本：{}"),
+    ("The idle time can't be longer than a day", "空闲时间不能超过一天"),
+    ("The passphrase must be at least 4 characters long", "密码至少需要 4 个字符"),
+    ("The passphrase must be at least {} characters long", "密码至少需要 {} 个字符"),
+    ("The request body is not valid UTF-8", "请求正文不是有效的 UTF-8"),
+    ("The request body is too large", "请求正文过大"),
+    ("The request body must be a JSON object", "请求正文必须是 JSON 对象"),
+    ("The tasks were imported, but not all attachments: {}", "任务已导入，但部分附件未能写入：{}"),
+    ("This build of iToDo can't open encrypted databases", "此版本的 iToDo 无法打开加密数据库"),
+    ("This build of iToDo was made without encryption support", "此版本的 iToDo 未包含加密支持"),
+    ("This bundle was created by a newer version of iToDo ({}); please update the app", "此数据包由更新版本的 iToDo（{}）创建，请先更新应用"),
+    ("Too many backups to keep", "保留的备份数量过多"),
+    ("Unexpected END:{} in iCalendar data", "iCalendar 数据中出现意外的 END:{}"),
+    ("Unknown bundle format: {}", "未知的数据包格式：{}"),
+    ("Unknown import source: {}", "未知的导入来源：{}"),
+    ("Unknown setting: {}", "未知的设置项：{}"),
+    ("Unknown view: {}", "未知视图：{}"),
+    ("Unsupported key derivation: {}", "不支持的密钥派生算法：{}"),
+    ("Unterminated BEGIN:{} in iCalendar data", "iCalendar 数据中的 BEGIN:{} 未结束"),
+    ("Use either view or q, not both", "view 和 q 只能使用其中一个"),
+    ("Workspace name can't be empty", "工作区名称不能为空"),
+    ("Workspace not found: {}", "找不到工作区：{}"),
+    ("Wrong passphrase, or the database is damaged", "密码错误，或数据库已损坏"),
+    ("due date", "截止日期"),
+    ("iToDo is locked", "iToDo 已锁定"),
+    ("remind time", "提醒时间"),
+    ("start date", "开始日期"),
+    ("{} already contains an iToDo database", "{} 中已有 iToDo 数据库"),
+    ("{} is not a workspace database, so it has no backups", "{} 不是工作区数据库，没有备份"),
+    ("{} is not listed in {}", "{} 未列在 {} 中"),
+    ("{} open, {} completed", "{} 项未完成，{} 项已完成"),
+];
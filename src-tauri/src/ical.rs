@@ -0,0 +1,788 @@
+// iCalendar (RFC 5545) VTODO export and import.
+//
+// Every list becomes its own VCALENDAR; a file may contain several of them back to back.
+
+use crate::i18n::t;
+use crate::repeat::{RepeatKind, RepeatRule, WEEKDAYS};
+use crate::{List, Task};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use log::warn;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const PRODID: &str = "-//iToDo//iToDo 1.0//EN";
+const ICAL_DAYS: [&str; 7] = ["SU", "MO", "TU", "WE", "TH", "FR", "SA"];
+
+// ============== Export ==============
+
+pub fn export_calendars(lists: &[List], tasks: &[Task]) -> String {
+    let mut out = String::new();
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+    for list in lists {
+        push_line(&mut out, "BEGIN:VCALENDAR");
+        push_line(&mut out, "VERSION:2.0");
+        push_line(&mut out, &format!("PRODID:{}", PRODID));
+        push_line(&mut out, "CALSCALE:GREGORIAN");
+        push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(&list.name)));
+        if let Some(color) = &list.color {
+            push_line(&mut out, &format!("X-APPLE-CALENDAR-COLOR:{}", escape_text(color)));
+        }
+
+        for task in tasks.iter().filter(|t| t.list_id == list.id) {
+            write_todo(&mut out, task, &stamp);
+        }
+
+        push_line(&mut out, "END:VCALENDAR");
+    }
+
+    out
+}
+
+fn write_todo(out: &mut String, task: &Task, stamp: &str) {
+    push_line(out, "BEGIN:VTODO");
+    push_line(out, &format!("UID:{}", task.id));
+    push_line(out, &format!("DTSTAMP:{}", stamp));
+    if let Some(created) = format_utc(&task.created_at) {
+        push_line(out, &format!("CREATED:{}", created));
+    }
+    if let Some(modified) = format_utc(&task.updated_at) {
+        push_line(out, &format!("LAST-MODIFIED:{}", modified));
+    }
+    push_line(out, &format!("SUMMARY:{}", escape_text(&task.title)));
+    if let Some(content) = task.content.as_deref().filter(|c| !c.is_empty()) {
+        push_line(out, &format!("DESCRIPTION:{}", escape_text(content)));
+    }
+    // Due and start dates are date-only in the UI
+    if let Some(start) = task.start_date.as_deref().and_then(format_date) {
+        push_line(out, &format!("DTSTART;VALUE=DATE:{}", start));
+    }
+    if let Some(due) = task.due_date.as_deref().and_then(format_date) {
+        push_line(out, &format!("DUE;VALUE=DATE:{}", due));
+    }
+    if task.is_important {
+        push_line(out, "PRIORITY:1");
+    }
+    if task.is_completed {
+        push_line(out, "STATUS:COMPLETED");
+        push_line(out, "PERCENT-COMPLETE:100");
+        if let Some(completed) = format_utc(&task.updated_at) {
+            push_line(out, &format!("COMPLETED:{}", completed));
+        }
+    } else {
+        push_line(out, "STATUS:NEEDS-ACTION");
+    }
+    if let Some(rrule) = task.repeat_rule.as_deref().and_then(RepeatRule::parse).map(|r| to_rrule(&r)) {
+        push_line(out, &format!("RRULE:{}", rrule));
+    }
+    if let Some(remind) = task.remind_time.as_deref().and_then(format_utc) {
+        push_line(out, "BEGIN:VALARM");
+        push_line(out, "ACTION:DISPLAY");
+        push_line(out, &format!("DESCRIPTION:{}", escape_text(&task.title)));
+        push_line(out, &format!("TRIGGER;VALUE=DATE-TIME:{}", remind));
+        push_line(out, "END:VALARM");
+    }
+    push_line(out, "END:VTODO");
+}
+
+fn to_rrule(rule: &RepeatRule) -> String {
+    let freq = match rule.kind {
+        RepeatKind::Daily => "DAILY",
+        RepeatKind::Weekly => "WEEKLY",
+        RepeatKind::Monthly => "MONTHLY",
+        RepeatKind::Yearly => "YEARLY",
+    };
+    let mut parts = vec![format!("FREQ={}", freq)];
+    if rule.interval() > 1 {
+        parts.push(format!("INTERVAL={}", rule.interval()));
+    }
+    if let Some(days) = rule.days.as_ref().filter(|d| !d.is_empty()) {
+        let by_day: Vec<&str> = days
+            .iter()
+            .filter_map(|d| WEEKDAYS.iter().position(|w| w == d).map(|i| ICAL_DAYS[i]))
+            .collect();
+        if !by_day.is_empty() {
+            parts.push(format!("BYDAY={}", by_day.join(",")));
+        }
+    }
+    let month_days = match rule.kind {
+        RepeatKind::Yearly => rule.month_days.as_ref(),
+        _ => rule.days_of_month.as_ref(),
+    };
+    if let Some(days) = month_days.filter(|d| !d.is_empty()) {
+        let by_month_day: Vec<String> = days.iter().map(|d| d.to_string()).collect();
+        parts.push(format!("BYMONTHDAY={}", by_month_day.join(",")));
+    }
+    parts.join(";")
+}
+
+// Writes one content line, folded at 75 octets as required by RFC 5545
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for ch in line.chars() {
+        let len = ch.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += len;
+    }
+    out.push_str("\r\n");
+}
+
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn parse_stored(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .map(|d| d.and_time(NaiveTime::MIN).and_utc())
+}
+
+fn format_date(value: &str) -> Option<String> {
+    parse_stored(value).map(|dt| dt.format("%Y%m%d").to_string())
+}
+
+fn format_utc(value: &str) -> Option<String> {
+    parse_stored(value).map(|dt| dt.format("%Y%m%dT%H%M%SZ").to_string())
+}
+
+// ============== Import ==============
+
+pub struct IcsCalendar {
+    pub name: Option<String>,
+    pub color: Option<String>,
+    pub tasks: Vec<Task>,
+}
+
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+struct Component {
+    name: String,
+    properties: Vec<Property>,
+    children: Vec<Component>,
+}
+
+impl Component {
+    fn property(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.property(name).map(|p| p.value.as_str())
+    }
+
+    fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Component> + 'a {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+}
+
+pub fn parse_calendars(data: &str) -> Result<Vec<IcsCalendar>, String> {
+    let components = parse_components(data)?;
+    let calendars: Vec<IcsCalendar> = components
+        .iter()
+        .filter(|c| c.name == "VCALENDAR")
+        .map(read_calendar)
+        .collect();
+
+    if calendars.is_empty() {
+        return Err(t!("No VCALENDAR found in iCalendar data"));
+    }
+    Ok(calendars)
+}
+
+fn read_calendar(calendar: &Component) -> IcsCalendar {
+    let timezones: HashMap<String, VTimezone> = calendar
+        .children_named("VTIMEZONE")
+        .filter_map(VTimezone::from_component)
+        .map(|tz| (tz.id.clone(), tz))
+        .collect();
+
+    let tasks = calendar
+        .children_named("VTODO")
+        .filter_map(|todo| read_todo(todo, &timezones))
+        .collect();
+
+    IcsCalendar {
+        name: calendar.value("X-WR-CALNAME").map(unescape_text).filter(|n| !n.trim().is_empty()),
+        color: calendar.value("X-APPLE-CALENDAR-COLOR").map(unescape_text),
+        tasks,
+    }
+}
+
+fn read_todo(todo: &Component, timezones: &HashMap<String, VTimezone>) -> Option<Task> {
+    let title = todo.value("SUMMARY").map(unescape_text).unwrap_or_default();
+    if title.trim().is_empty() {
+        return None;
+    }
+
+    let due = todo.property("DUE").and_then(|p| property_datetime(p, timezones));
+    let start = todo.property("DTSTART").and_then(|p| property_datetime(p, timezones));
+
+    let status = todo.value("STATUS").unwrap_or("").to_ascii_uppercase();
+    let is_completed = status == "COMPLETED" || todo.property("COMPLETED").is_some();
+    // RFC 5545: 1-4 is high priority, 0 is undefined
+    let is_important = todo
+        .value("PRIORITY")
+        .and_then(|p| p.trim().parse::<u8>().ok())
+        .map(|p| (1..=4).contains(&p))
+        .unwrap_or(false);
+
+    let remind = todo
+        .children_named("VALARM")
+        .find_map(|alarm| alarm_time(alarm, start, due, timezones));
+
+    let repeat_rule = todo
+        .value("RRULE")
+        .and_then(parse_rrule)
+        .map(|r| r.to_json());
+
+    let now = Utc::now().to_rfc3339();
+    Some(Task {
+        id: Uuid::new_v4().to_string(),
+        title,
+        content: todo.value("DESCRIPTION").map(unescape_text).filter(|c| !c.is_empty()),
+        is_completed,
+        is_important,
+        due_date: due.map(format_stored),
+        start_date: start.map(format_stored),
+        remind_time: remind.map(format_stored),
+        repeat_rule,
+        list_id: String::new(),
+        created_at: now.clone(),
+        updated_at: now,
+    })
+}
+
+fn alarm_time(
+    alarm: &Component,
+    start: Option<DateTime<Utc>>,
+    due: Option<DateTime<Utc>>,
+    timezones: &HashMap<String, VTimezone>,
+) -> Option<DateTime<Utc>> {
+    let trigger = alarm.property("TRIGGER")?;
+    if trigger.param("VALUE") == Some("DATE-TIME") {
+        return property_datetime(trigger, timezones);
+    }
+
+    let offset = parse_duration(&trigger.value)?;
+    let anchor = match trigger.param("RELATED") {
+        Some("END") => due.or(start),
+        _ => start.or(due),
+    }?;
+    anchor.checked_add_signed(offset)
+}
+
+fn format_stored(dt: DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn parse_rrule(value: &str) -> Option<RepeatRule> {
+    let parts: HashMap<String, String> = value
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(k, v)| (k.trim().to_ascii_uppercase(), v.trim().to_string()))
+        .collect();
+
+    let kind = match parts.get("FREQ")?.to_ascii_uppercase().as_str() {
+        "DAILY" => RepeatKind::Daily,
+        "WEEKLY" => RepeatKind::Weekly,
+        "MONTHLY" => RepeatKind::Monthly,
+        "YEARLY" => RepeatKind::Yearly,
+        _ => return None,
+    };
+    let mut rule = RepeatRule::new(kind);
+    rule.interval = parts
+        .get("INTERVAL")
+        .and_then(|i| i.parse::<u32>().ok())
+        .filter(|i| *i > 1);
+
+    if let Some(by_day) = parts.get("BYDAY") {
+        // Ordinals like "1MO" or "-1FR" have no equivalent in iToDo and are dropped
+        let days: Vec<String> = by_day
+            .split(',')
+            .filter_map(|d| {
+                let code = d.trim_start_matches(|c: char| c == '+' || c == '-' || c.is_ascii_digit());
+                ICAL_DAYS
+                    .iter()
+                    .position(|w| w.eq_ignore_ascii_case(code))
+                    .map(|i| WEEKDAYS[i].to_string())
+            })
+            .collect();
+        if !days.is_empty() {
+            rule.days = Some(days);
+        }
+    }
+
+    if let Some(by_month_day) = parts.get("BYMONTHDAY") {
+        let days: Vec<u32> = by_month_day
+            .split(',')
+            .filter_map(|d| d.trim().parse::<u32>().ok())
+            .filter(|d| (1..=31).contains(d))
+            .collect();
+        if !days.is_empty() {
+            match kind {
+                RepeatKind::Yearly => rule.month_days = Some(days),
+                _ => rule.days_of_month = Some(days),
+            }
+        }
+    }
+
+    Some(rule)
+}
+
+// Parses an RFC 5545 duration such as "-PT15M", "P1D" or "-P1W"
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, rest) = match value.as_bytes().first()? {
+        b'-' => (true, &value[1..]),
+        b'+' => (false, &value[1..]),
+        _ => (false, value),
+    };
+    let rest = rest.strip_prefix('P')?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for ch in rest.chars() {
+        match ch {
+            'T' => in_time = true,
+            '0'..='9' => number.push(ch),
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                // Out-of-range amounts such as "P99999999999999W" make the alarm unreadable
+                let part = match (unit, in_time) {
+                    ('W', false) => Duration::try_weeks(n),
+                    ('D', false) => Duration::try_days(n),
+                    ('H', true) => Duration::try_hours(n),
+                    ('M', true) => Duration::try_minutes(n),
+                    ('S', true) => Duration::try_seconds(n),
+                    _ => return None,
+                }?;
+                total = total.checked_add(&part)?;
+            }
+        }
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(if negative { -total } else { total })
+}
+
+fn parse_naive(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim().trim_end_matches('Z');
+    if value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(|d| d.and_time(NaiveTime::MIN));
+    }
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()
+}
+
+fn property_datetime(prop: &Property, timezones: &HashMap<String, VTimezone>) -> Option<DateTime<Utc>> {
+    let value = prop.value.trim();
+    let naive = parse_naive(value)?;
+
+    // All-day values stay on their calendar date, like the dates the UI stores
+    if prop.param("VALUE") == Some("DATE") || value.len() == 8 {
+        return Some(naive.and_utc());
+    }
+    if value.ends_with('Z') {
+        return Some(naive.and_utc());
+    }
+    if let Some(tzid) = prop.param("TZID") {
+        if let Some(tz) = timezones.get(tzid) {
+            return Some(tz.to_utc(naive));
+        }
+        warn!("Unknown TZID {:?} in iCalendar data, treating time as UTC", tzid);
+        return Some(naive.and_utc());
+    }
+    // Floating time: interpret in the local timezone
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        .or_else(|| Some(naive.and_utc()))
+}
+
+// ============== VTIMEZONE ==============
+
+struct VTimezone {
+    id: String,
+    observances: Vec<Observance>,
+}
+
+struct Observance {
+    start: NaiveDateTime,
+    offset_from: i64,
+    offset_to: i64,
+    yearly: Option<YearlyRule>,
+}
+
+// Only the yearly transition rules used by real-world VTIMEZONE blocks are supported,
+// e.g. "FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU"
+struct YearlyRule {
+    month: u32,
+    weekday: Option<(i32, Weekday)>,
+    month_day: Option<u32>,
+    until: Option<NaiveDateTime>,
+}
+
+impl VTimezone {
+    fn from_component(component: &Component) -> Option<Self> {
+        let id = component.value("TZID")?.to_string();
+        let observances: Vec<Observance> = component
+            .children
+            .iter()
+            .filter(|c| c.name == "STANDARD" || c.name == "DAYLIGHT")
+            .filter_map(Observance::from_component)
+            .collect();
+        if observances.is_empty() {
+            return None;
+        }
+        Some(VTimezone { id, observances })
+    }
+
+    fn to_utc(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        let current = self
+            .observances
+            .iter()
+            .filter_map(|o| o.last_onset(local).map(|onset| (onset, o.offset_to)))
+            .max_by_key(|(onset, _)| *onset)
+            .map(|(_, offset)| offset);
+
+        // Before the first known transition, the earliest observance's "from" offset applies
+        let offset = current.unwrap_or_else(|| {
+            self.observances
+                .iter()
+                .min_by_key(|o| o.start)
+                .map(|o| o.offset_from)
+                .unwrap_or(0)
+        });
+
+        (local - Duration::seconds(offset)).and_utc()
+    }
+}
+
+impl Observance {
+    fn from_component(component: &Component) -> Option<Self> {
+        let start = parse_naive(component.value("DTSTART")?)?;
+        let offset_to = parse_utc_offset(component.value("TZOFFSETTO")?)?;
+        let offset_from = component
+            .value("TZOFFSETFROM")
+            .and_then(parse_utc_offset)
+            .unwrap_or(offset_to);
+        let yearly = component.value("RRULE").and_then(YearlyRule::parse);
+        Some(Observance {
+            start,
+            offset_from,
+            offset_to,
+            yearly,
+        })
+    }
+
+    // The latest time this observance took effect at or before `local`
+    fn last_onset(&self, local: NaiveDateTime) -> Option<NaiveDateTime> {
+        if local < self.start {
+            return None;
+        }
+        let rule = match &self.yearly {
+            Some(rule) => rule,
+            None => return Some(self.start),
+        };
+
+        for year in (self.start.year()..=local.year()).rev() {
+            let onset = match rule.onset_in(year, self.start.time()) {
+                Some(onset) => onset,
+                None => continue,
+            };
+            if onset < self.start || onset > local {
+                continue;
+            }
+            if rule.until.is_some_and(|until| onset > until) {
+                continue;
+            }
+            return Some(onset);
+        }
+        Some(self.start)
+    }
+}
+
+impl YearlyRule {
+    fn parse(value: &str) -> Option<Self> {
+        let parts: HashMap<String, String> = value
+            .split(';')
+            .filter_map(|part| part.split_once('='))
+            .map(|(k, v)| (k.trim().to_ascii_uppercase(), v.trim().to_ascii_uppercase()))
+            .collect();
+        if parts.get("FREQ").map(String::as_str) != Some("YEARLY") {
+            return None;
+        }
+        let month = parts.get("BYMONTH")?.parse::<u32>().ok()?;
+        let weekday = parts.get("BYDAY").and_then(|d| {
+            let split = d.len().checked_sub(2)?;
+            let (ordinal, code) = d.split_at(split);
+            let index = ICAL_DAYS.iter().position(|w| *w == code)?;
+            let ordinal = match ordinal.trim_start_matches('+') {
+                "" => 1,
+                n => n.parse::<i32>().ok()?,
+            };
+            // A month has at most five of each weekday
+            if ordinal == 0 || !(-5..=5).contains(&ordinal) {
+                return None;
+            }
+            Some((ordinal, Weekday::try_from(((index + 6) % 7) as u8).ok()?))
+        });
+        let month_day = parts.get("BYMONTHDAY").and_then(|d| d.parse::<u32>().ok());
+        let until = parts.get("UNTIL").and_then(|u| parse_naive(u));
+        Some(YearlyRule {
+            month,
+            weekday,
+            month_day,
+            until,
+        })
+    }
+
+    fn onset_in(&self, year: i32, time: NaiveTime) -> Option<NaiveDateTime> {
+        let date = match (self.weekday, self.month_day) {
+            (Some((ordinal, weekday)), _) if ordinal > 0 => {
+                NaiveDate::from_weekday_of_month_opt(year, self.month, weekday, ordinal as u8)?
+            }
+            (Some((ordinal, weekday)), _) => {
+                // Negative ordinals count back from the end of the month
+                let first_of_next = if self.month == 12 {
+                    NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+                } else {
+                    NaiveDate::from_ymd_opt(year, self.month + 1, 1)?
+                };
+                let mut date = first_of_next.pred_opt()?;
+                while date.weekday() != weekday {
+                    date = date.pred_opt()?;
+                }
+                date.checked_sub_signed(Duration::weeks((-ordinal - 1) as i64))?
+            }
+            (None, Some(day)) => NaiveDate::from_ymd_opt(year, self.month, day)?,
+            (None, None) => return None,
+        };
+        Some(date.and_time(time))
+    }
+}
+
+// Parses "+0100", "-0530" or "+013000" into seconds east of UTC
+fn parse_utc_offset(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let sign = match value.as_bytes().first()? {
+        b'-' => -1,
+        b'+' => 1,
+        _ => return None,
+    };
+    let digits = &value[1..];
+    if digits.len() < 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i64 = digits[0..2].parse().ok()?;
+    let minutes: i64 = digits[2..4].parse().ok()?;
+    let seconds: i64 = digits.get(4..6).and_then(|s| s.parse().ok()).unwrap_or(0);
+    Some(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+
+// ============== Content lines ==============
+
+fn parse_components(data: &str) -> Result<Vec<Component>, String> {
+    let mut roots = Vec::new();
+    let mut stack: Vec<Component> = Vec::new();
+
+    for line in unfold_lines(data) {
+        let prop = match parse_content_line(&line) {
+            Some(prop) => prop,
+            None => continue,
+        };
+
+        match prop.name.as_str() {
+            "BEGIN" => stack.push(Component {
+                name: prop.value.trim().to_ascii_uppercase(),
+                properties: Vec::new(),
+                children: Vec::new(),
+            }),
+            "END" => {
+                let component = stack
+                    .pop()
+                    .ok_or_else(|| t!("Unexpected END:{} in iCalendar data", prop.value))?;
+                if !component.name.eq_ignore_ascii_case(prop.value.trim()) {
+                    return Err(t!(
+                        "Mismatched END:{} for BEGIN:{} in iCalendar data",
+                        prop.value, component.name
+                    ));
+                }
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(component),
+                    None => roots.push(component),
+                }
+            }
+            _ => {
+                if let Some(current) = stack.last_mut() {
+                    current.properties.push(prop);
+                }
+            }
+        }
+    }
+
+    if let Some(open) = stack.last() {
+        return Err(t!("Unterminated BEGIN:{} in iCalendar data", open.name));
+    }
+    Ok(roots)
+}
+
+fn unfold_lines(data: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in data.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        if let Some(continuation) = raw.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(continuation);
+                continue;
+            }
+        }
+        if !raw.is_empty() {
+            lines.push(raw.to_string());
+        }
+    }
+    lines
+}
+
+fn parse_content_line(line: &str) -> Option<Property> {
+    // Split "NAME;PARAM=a;PARAM2="b:c":value" on the first colon outside quotes
+    let mut in_quotes = false;
+    let mut colon = None;
+    for (i, ch) in line.char_indices() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                colon = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let colon = colon?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    in_quotes = false;
+    for ch in head.chars() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => segments.push(std::mem::take(&mut current)),
+            _ => current.push(ch),
+        }
+    }
+    segments.push(current);
+
+    let mut segments = segments.into_iter();
+    let name = segments.next()?.trim().to_ascii_uppercase();
+    let params = segments
+        .filter_map(|s| {
+            s.split_once('=')
+                .map(|(k, v)| (k.trim().to_ascii_uppercase(), v.trim().to_string()))
+        })
+        .collect();
+
+    Some(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn todo_with(lines: &str) -> String {
+        format!(
+            "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nSUMMARY:Pay rent\r\nDUE:20240301T090000Z\r\n{}END:VTODO\r\nEND:VCALENDAR\r\n",
+            lines
+        )
+    }
+
+    #[test]
+    fn reads_relative_alarms() {
+        let data = todo_with("BEGIN:VALARM\r\nTRIGGER;RELATED=END:-P1DT2H\r\nEND:VALARM\r\n");
+        let calendars = parse_calendars(&data).unwrap();
+        assert_eq!(calendars[0].tasks[0].remind_time.as_deref(), Some("2024-02-29T07:00:00Z"));
+    }
+
+    #[test]
+    fn skips_alarms_out_of_range() {
+        for trigger in ["-P99999999999999W", "P9999999999999999D", "PT9223372036854775807S", "-P100000000W"] {
+            let data = todo_with(&format!("BEGIN:VALARM\r\nTRIGGER:{}\r\nEND:VALARM\r\n", trigger));
+            let calendars = parse_calendars(&data).unwrap();
+            assert_eq!(calendars[0].tasks[0].remind_time, None, "{}", trigger);
+        }
+    }
+
+    #[test]
+    fn finds_weekdays_counted_from_the_end() {
+        let rule = YearlyRule::parse("FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU").unwrap();
+        let onset = rule.onset_in(2024, NaiveTime::from_hms_opt(3, 0, 0).unwrap()).unwrap();
+        assert_eq!(onset.date(), NaiveDate::from_ymd_opt(2024, 10, 27).unwrap());
+    }
+
+    #[test]
+    fn rejects_ordinals_outside_a_month() {
+        let time = NaiveTime::from_hms_opt(3, 0, 0).unwrap();
+        for byday in ["0SU", "6SU", "-6SU", "-100000000SU", "-2147483648SU"] {
+            let rule = YearlyRule::parse(&format!("FREQ=YEARLY;BYMONTH=10;BYDAY={}", byday)).unwrap();
+            assert_eq!(rule.weekday, None, "{}", byday);
+            assert_eq!(rule.onset_in(2024, time), None, "{}", byday);
+        }
+    }
+}
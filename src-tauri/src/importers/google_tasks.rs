@@ -0,0 +1,125 @@
+// Google Tasks, from the Tasks.json file in a Google Takeout archive.
+//
+// Takeout writes `{"kind": "tasks#taskLists", "items": [taskList...]}` where every task list
+// embeds its tasks in `items`. Subtasks are ordinary tasks pointing at their parent.
+
+use super::{parse_day, parse_timestamp, ImportedList, ImportedTask, Importer};
+use crate::i18n::t;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+pub struct GoogleTasksImporter;
+
+impl Importer for GoogleTasksImporter {
+    fn id(&self) -> &'static str {
+        "google_tasks"
+    }
+
+    fn name(&self) -> &'static str {
+        "Google Tasks"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn parse(&self, data: &str) -> Result<Vec<ImportedList>, String> {
+        let takeout: Takeout = serde_json::from_str(data)
+            .map_err(|e| t!("Failed to parse Google Tasks data: {}", e))?;
+        Ok(takeout.items.into_iter().map(convert_list).collect())
+    }
+}
+
+#[derive(Deserialize)]
+struct Takeout {
+    #[serde(default)]
+    items: Vec<TaskList>,
+}
+
+#[derive(Deserialize)]
+struct TaskList {
+    title: String,
+    #[serde(default)]
+    items: Vec<GoogleTask>,
+}
+
+#[derive(Deserialize)]
+struct GoogleTask {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    due: Option<String>,
+    #[serde(default)]
+    completed: Option<String>,
+    #[serde(default)]
+    parent: Option<String>,
+    #[serde(default)]
+    position: Option<String>,
+    #[serde(default)]
+    deleted: bool,
+    #[serde(default)]
+    created: Option<String>,
+    #[serde(default)]
+    updated: Option<String>,
+}
+
+fn convert_list(list: TaskList) -> ImportedList {
+    let mut items: Vec<GoogleTask> = list
+        .items
+        .into_iter()
+        .filter(|t| !t.deleted && !t.title.trim().is_empty())
+        .collect();
+    // Positions are zero-padded strings, so they sort lexically
+    items.sort_by(|a, b| a.position.cmp(&b.position));
+
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut tasks: Vec<ImportedTask> = Vec::new();
+    for item in items.iter().filter(|t| t.parent.is_none()) {
+        let mut imported = ImportedTask::new(&item.title);
+        let task = &mut imported.task;
+        task.content = item.notes.clone().filter(|n| !n.trim().is_empty());
+        task.is_completed = item.status.as_deref() == Some("completed");
+        // Google Tasks only keeps the date part of due dates
+        task.due_date = item.due.as_deref().and_then(parse_day);
+        if let Some(created) = item.created.as_deref().and_then(parse_timestamp) {
+            task.created_at = created;
+        }
+        if let Some(updated) = item
+            .completed
+            .as_deref()
+            .filter(|_| task.is_completed)
+            .or(item.updated.as_deref())
+            .and_then(parse_timestamp)
+        {
+            task.updated_at = updated;
+        }
+        index.insert(item.id.clone(), tasks.len());
+        tasks.push(imported);
+    }
+
+    for item in items.iter().filter(|t| t.parent.is_some()) {
+        let parent = item.parent.as_ref().and_then(|p| index.get(p));
+        match parent {
+            Some(&i) => tasks[i].add_subtask(&item.title, item.status.as_deref() == Some("completed")),
+            // A subtask whose parent was deleted is kept as a task of its own
+            None => {
+                let mut imported = ImportedTask::new(&item.title);
+                imported.task.content = item.notes.clone().filter(|n| !n.trim().is_empty());
+                imported.task.is_completed = item.status.as_deref() == Some("completed");
+                imported.task.due_date = item.due.as_deref().and_then(parse_day);
+                tasks.push(imported);
+            }
+        }
+    }
+
+    ImportedList {
+        name: Some(list.title),
+        color: None,
+        tasks,
+    }
+}
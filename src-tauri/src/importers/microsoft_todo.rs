@@ -0,0 +1,278 @@
+// Microsoft To Do, as returned by the Microsoft Graph API.
+//
+// Accepts the `todoTaskList` collection (`{"value": [...]}`, `{"lists": [...]}` or a bare array)
+// where every list carries its `todoTask`s in a `tasks` property, either as an array or as the
+// `{"value": [...]}` page returned by `/me/todo/lists/{id}/tasks?$expand=checklistItems`.
+
+use super::{html_to_text, parse_timestamp, ImportedList, ImportedTask, Importer};
+use crate::i18n::t;
+use crate::repeat::{RepeatKind, RepeatRule, WEEKDAYS};
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Collection<T> {
+    Page { value: Vec<T> },
+    Items(Vec<T>),
+}
+
+impl<T> Collection<T> {
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            Collection::Page { value } => value,
+            Collection::Items(items) => items,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Document {
+    Lists { lists: Collection<TodoTaskList> },
+    Collection(Collection<TodoTaskList>),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TodoTaskList {
+    display_name: String,
+    #[serde(default)]
+    wellknown_list_name: Option<String>,
+    #[serde(default)]
+    tasks: Option<Collection<TodoTask>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TodoTask {
+    title: String,
+    #[serde(default)]
+    body: Option<ItemBody>,
+    #[serde(default)]
+    importance: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    is_reminder_on: Option<bool>,
+    #[serde(default)]
+    reminder_date_time: Option<DateTimeTimeZone>,
+    #[serde(default)]
+    due_date_time: Option<DateTimeTimeZone>,
+    #[serde(default)]
+    start_date_time: Option<DateTimeTimeZone>,
+    #[serde(default)]
+    completed_date_time: Option<DateTimeTimeZone>,
+    #[serde(default)]
+    created_date_time: Option<String>,
+    #[serde(default)]
+    last_modified_date_time: Option<String>,
+    #[serde(default)]
+    recurrence: Option<PatternedRecurrence>,
+    #[serde(default)]
+    checklist_items: Vec<ChecklistItem>,
+    #[serde(default)]
+    categories: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ItemBody {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    content_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DateTimeTimeZone {
+    date_time: String,
+    #[serde(default)]
+    time_zone: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PatternedRecurrence {
+    pattern: RecurrencePattern,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecurrencePattern {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    interval: Option<u32>,
+    #[serde(default)]
+    days_of_week: Vec<String>,
+    #[serde(default)]
+    day_of_month: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChecklistItem {
+    display_name: String,
+    #[serde(default)]
+    is_checked: bool,
+}
+
+pub struct MicrosoftTodoImporter;
+
+impl Importer for MicrosoftTodoImporter {
+    fn id(&self) -> &'static str {
+        "microsoft_todo"
+    }
+
+    fn name(&self) -> &'static str {
+        "Microsoft To Do"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn parse(&self, data: &str) -> Result<Vec<ImportedList>, String> {
+        parse(data)
+    }
+}
+
+pub fn parse(data: &str) -> Result<Vec<ImportedList>, String> {
+    let document: Document = serde_json::from_str(data)
+        .map_err(|e| t!("Failed to parse Microsoft To Do data: {}", e))?;
+    let lists = match document {
+        Document::Lists { lists } => lists.into_vec(),
+        Document::Collection(lists) => lists.into_vec(),
+    };
+
+    Ok(lists.into_iter().map(convert_list).collect())
+}
+
+fn convert_list(list: TodoTaskList) -> ImportedList {
+    // The built-in "Tasks" list corresponds to iToDo's default list
+    let is_default = list.wellknown_list_name.as_deref() == Some("defaultList");
+    ImportedList {
+        name: if is_default { None } else { Some(list.display_name) },
+        color: None,
+        tasks: list
+            .tasks
+            .map(Collection::into_vec)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|t| !t.title.trim().is_empty())
+            .map(convert_task)
+            .collect(),
+    }
+}
+
+fn convert_task(source: TodoTask) -> ImportedTask {
+    let mut imported = ImportedTask::new(&source.title);
+    let task = &mut imported.task;
+
+    task.content = source
+        .body
+        .map(|body| match body.content_type.as_deref() {
+            Some(t) if t.eq_ignore_ascii_case("html") => html_to_text(&body.content),
+            _ => body.content.trim().to_string(),
+        })
+        .filter(|c| !c.is_empty());
+    task.is_important = source.importance.as_deref() == Some("high");
+    task.is_completed = source.status.as_deref() == Some("completed");
+    task.due_date = source.due_date_time.as_ref().and_then(to_day);
+    task.start_date = source.start_date_time.as_ref().and_then(to_day);
+    if source.is_reminder_on.unwrap_or(true) {
+        task.remind_time = source.reminder_date_time.as_ref().and_then(to_timestamp);
+    }
+    task.repeat_rule = source.recurrence.and_then(|r| convert_pattern(&r.pattern)).map(|r| r.to_json());
+
+    if let Some(created) = source.created_date_time.as_deref().and_then(parse_timestamp) {
+        task.created_at = created;
+    }
+    let completed = source.completed_date_time.as_ref().and_then(to_timestamp);
+    if let Some(modified) = completed
+        .filter(|_| task.is_completed)
+        .or_else(|| source.last_modified_date_time.as_deref().and_then(parse_timestamp))
+    {
+        task.updated_at = modified;
+    }
+
+    for item in &source.checklist_items {
+        imported.add_subtask(&item.display_name, item.is_checked);
+    }
+    imported.tags = source.categories;
+    imported
+}
+
+// None for a pattern type Graph may add later; the task is imported without repeating
+fn convert_pattern(pattern: &RecurrencePattern) -> Option<RepeatRule> {
+    let kind = match pattern.kind.as_str() {
+        "daily" => RepeatKind::Daily,
+        "weekly" => RepeatKind::Weekly,
+        "absoluteMonthly" | "relativeMonthly" => RepeatKind::Monthly,
+        "absoluteYearly" | "relativeYearly" => RepeatKind::Yearly,
+        _ => return None,
+    };
+    let mut rule = RepeatRule::new(kind);
+    rule.interval = pattern.interval.filter(|i| *i > 1);
+
+    if kind == RepeatKind::Weekly {
+        let days: Vec<String> = pattern
+            .days_of_week
+            .iter()
+            .filter_map(|d| WEEKDAYS.iter().find(|w| d.to_ascii_lowercase().starts_with(&w.to_ascii_lowercase())))
+            .map(|w| w.to_string())
+            .collect();
+        if !days.is_empty() {
+            rule.days = Some(days);
+        }
+    }
+    // Relative patterns ("first Monday") have no equivalent and fall back to the plain frequency
+    if let Some(day) = pattern.day_of_month.filter(|d| (1..=31).contains(d)) {
+        match pattern.kind.as_str() {
+            "absoluteMonthly" => rule.days_of_month = Some(vec![day]),
+            "absoluteYearly" => rule.month_days = Some(vec![day]),
+            _ => {}
+        }
+    }
+    Some(rule)
+}
+
+// Graph returns wall-clock times such as "2026-10-05T00:00:00.0000000" plus the name of their
+// time zone, IANA or Windows style. There is no time zone database to look the names up in, so
+// any zone but UTC is taken as the local one; Graph uses the user's own zone unless asked otherwise.
+fn wall_clock(value: &DateTimeTimeZone) -> Option<NaiveDateTime> {
+    let text = value.date_time.trim().trim_end_matches('Z');
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f").ok()
+}
+
+fn to_instant(value: &DateTimeTimeZone) -> Option<DateTime<Utc>> {
+    let naive = wall_clock(value)?;
+    let zone = value.time_zone.as_deref().unwrap_or("UTC").trim();
+    if value.date_time.trim().ends_with('Z') || ["UTC", "Etc/UTC", "GMT", "Etc/GMT"].iter().any(|z| z.eq_ignore_ascii_case(zone)) {
+        return Some(naive.and_utc());
+    }
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        .or_else(|| Some(naive.and_utc()))
+}
+
+// Reminders and completion times are moments, stored in UTC
+fn to_timestamp(value: &DateTimeTimeZone) -> Option<String> {
+    to_instant(value).map(|dt| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+}
+
+// Due and start dates are calendar dates, stored as "YYYY-MM-DDT00:00:00Z" like the UI writes
+// them. To Do sends midnight in the user's zone, which keeps its date; other times are moved to
+// the local date first.
+fn to_day(value: &DateTimeTimeZone) -> Option<String> {
+    let naive = wall_clock(value)?;
+    let date = if naive.time() == NaiveTime::MIN {
+        naive.date()
+    } else {
+        to_instant(value)?.with_timezone(&Local).date_naive()
+    };
+    Some(format!("{}T00:00:00Z", date.format("%Y-%m-%d")))
+}
@@ -0,0 +1,147 @@
+// Importers for data exported from other task managers.
+//
+// Each importer turns its source format into `ImportedList`s; storing them is shared in lib.rs.
+// New sources implement `Importer` and are registered in `all()`.
+
+pub mod google_tasks;
+pub mod microsoft_todo;
+pub mod todoist;
+pub mod trello;
+
+use crate::{Subtask, Task};
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+pub trait Importer: Send + Sync {
+    // Stable identifier passed from the frontend, e.g. "todoist"
+    fn id(&self) -> &'static str;
+    fn name(&self) -> &'static str;
+    // File extensions the source usually exports, for the open dialog
+    fn extensions(&self) -> &'static [&'static str];
+    fn parse(&self, data: &str) -> Result<Vec<ImportedList>, String>;
+}
+
+pub fn all() -> Vec<Box<dyn Importer>> {
+    vec![
+        Box::new(microsoft_todo::MicrosoftTodoImporter),
+        Box::new(todoist::TodoistImporter),
+        Box::new(google_tasks::GoogleTasksImporter),
+        Box::new(trello::TrelloImporter),
+    ]
+}
+
+pub fn find(id: &str) -> Option<Box<dyn Importer>> {
+    all().into_iter().find(|importer| importer.id() == id)
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportedList {
+    // None means the tasks belong in the default list
+    pub name: Option<String>,
+    pub color: Option<String>,
+    pub tasks: Vec<ImportedTask>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportedTask {
+    pub task: Task,
+    pub subtasks: Vec<Subtask>,
+    pub tags: Vec<String>,
+}
+
+impl ImportedTask {
+    pub fn new(title: &str) -> Self {
+        let now = Utc::now().to_rfc3339();
+        ImportedTask {
+            task: Task {
+                id: Uuid::new_v4().to_string(),
+                title: title.trim().to_string(),
+                content: None,
+                is_completed: false,
+                is_important: false,
+                due_date: None,
+                start_date: None,
+                remind_time: None,
+                repeat_rule: None,
+                list_id: String::new(),
+                created_at: now.clone(),
+                updated_at: now,
+            },
+            subtasks: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn add_tag(&mut self, tag: &str) {
+        let tag = tag.trim();
+        if !tag.is_empty() && !self.tags.iter().any(|t| t == tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    pub fn add_subtask(&mut self, title: &str, is_completed: bool) {
+        let title = title.trim();
+        if title.is_empty() {
+            return;
+        }
+        let now = Utc::now().to_rfc3339();
+        self.subtasks.push(Subtask {
+            id: Uuid::new_v4().to_string(),
+            task_id: self.task.id.clone(),
+            title: title.to_string(),
+            is_completed,
+            created_at: now.clone(),
+            updated_at: now,
+        });
+    }
+}
+
+// Strips tags and decodes the common entities from HTML task bodies
+pub(crate) fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    let mut tag = String::new();
+    for ch in html.chars() {
+        match ch {
+            '<' => {
+                in_tag = true;
+                tag.clear();
+            }
+            '>' if in_tag => {
+                in_tag = false;
+                let name = tag.trim_start_matches('/').split_whitespace().next().unwrap_or("").to_ascii_lowercase();
+                if matches!(name.as_str(), "br" | "br/" | "p" | "div" | "li" | "tr") && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+            }
+            _ if in_tag => tag.push(ch),
+            _ => text.push(ch),
+        }
+    }
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.lines().map(str::trim_end).collect::<Vec<_>>().join("\n").trim().to_string()
+}
+
+// Normalizes the timestamps other services export to the RFC 3339 form used for `created_at`
+pub(crate) fn parse_timestamp(value: &str) -> Option<String> {
+    DateTime::parse_from_rfc3339(value.trim())
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc).to_rfc3339())
+}
+
+// Due and start dates are stored as "YYYY-MM-DDT00:00:00Z", like the date pickers in the UI write them
+pub(crate) fn parse_day(value: &str) -> Option<String> {
+    let value = value.trim();
+    let date = match DateTime::parse_from_rfc3339(value) {
+        Ok(dt) => dt.with_timezone(&Utc).date_naive(),
+        Err(_) => NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()?,
+    };
+    Some(format!("{}T00:00:00Z", date.format("%Y-%m-%d")))
+}
@@ -0,0 +1,388 @@
+// Todoist backups.
+//
+// Two shapes are accepted: the JSON backup (Sync API layout with `projects` and `items`) and the
+// CSV template a single project is exported as (TYPE, CONTENT, DESCRIPTION, PRIORITY, INDENT, DATE, ...).
+
+use super::{parse_day, parse_timestamp, ImportedList, ImportedTask, Importer};
+use crate::i18n::t;
+use crate::repeat::{RepeatKind, RepeatRule, WEEKDAYS};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Name of the list a CSV template is imported into; the file itself doesn't name its project
+const CSV_LIST_NAME: &str = "Todoist";
+
+pub struct TodoistImporter;
+
+impl Importer for TodoistImporter {
+    fn id(&self) -> &'static str {
+        "todoist"
+    }
+
+    fn name(&self) -> &'static str {
+        "Todoist"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json", "csv"]
+    }
+
+    fn parse(&self, data: &str) -> Result<Vec<ImportedList>, String> {
+        let data = data.trim_start_matches('\u{feff}');
+        if data.trim_start().starts_with('{') {
+            parse_json(data)
+        } else {
+            parse_csv(data)
+        }
+    }
+}
+
+// ============== JSON backup ==============
+
+#[derive(Deserialize)]
+struct Backup {
+    #[serde(default)]
+    projects: Vec<Project>,
+    #[serde(default)]
+    items: Vec<Item>,
+    #[serde(default)]
+    labels: Vec<Label>,
+}
+
+#[derive(Deserialize)]
+struct Project {
+    id: Value,
+    name: String,
+    #[serde(default)]
+    inbox_project: bool,
+    #[serde(default)]
+    is_archived: bool,
+    #[serde(default)]
+    is_deleted: bool,
+}
+
+#[derive(Deserialize)]
+struct Item {
+    id: Value,
+    project_id: Value,
+    content: String,
+    #[serde(default)]
+    description: Option<String>,
+    // 4 is the highest priority (p1 in the app)
+    #[serde(default)]
+    priority: Option<u8>,
+    #[serde(default)]
+    due: Option<Due>,
+    #[serde(default)]
+    parent_id: Option<Value>,
+    #[serde(default)]
+    checked: Option<Value>,
+    #[serde(default)]
+    is_deleted: Option<Value>,
+    #[serde(default)]
+    labels: Vec<Value>,
+    #[serde(default, alias = "date_added")]
+    added_at: Option<String>,
+    #[serde(default, alias = "date_completed")]
+    completed_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Due {
+    #[serde(default)]
+    date: Option<String>,
+    #[serde(default)]
+    is_recurring: bool,
+    #[serde(default)]
+    string: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    id: Value,
+    name: String,
+}
+
+// Todoist ids are strings in newer backups and numbers in older ones
+fn id_key(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+// Flags are booleans in newer backups and 0/1 in older ones
+fn flag(value: &Option<Value>) -> bool {
+    match value {
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Number(n)) => n.as_i64().unwrap_or(0) != 0,
+        _ => false,
+    }
+}
+
+fn parse_json(data: &str) -> Result<Vec<ImportedList>, String> {
+    let backup: Backup = serde_json::from_str(data)
+        .map_err(|e| t!("Failed to parse Todoist backup: {}", e))?;
+
+    let label_names: HashMap<String, String> = backup.labels.iter().map(|l| (id_key(&l.id), l.name.clone())).collect();
+    let items: Vec<&Item> = backup.items.iter().filter(|i| !flag(&i.is_deleted)).collect();
+    let parents: HashMap<String, Option<String>> = items
+        .iter()
+        .map(|i| (id_key(&i.id), i.parent_id.as_ref().filter(|p| !p.is_null()).map(id_key)))
+        .collect();
+
+    // Sub-items at any depth become subtasks of their top-level ancestor
+    let root_of = |id: &str| -> String {
+        let mut current = id.to_string();
+        for _ in 0..parents.len() {
+            match parents.get(&current).cloned().flatten() {
+                Some(parent) if parents.contains_key(&parent) => current = parent,
+                _ => break,
+            }
+        }
+        current
+    };
+
+    let mut tasks: HashMap<String, ImportedTask> = HashMap::new();
+    let mut order: Vec<(String, String)> = Vec::new();
+    for item in items.iter().filter(|i| root_of(&id_key(&i.id)) == id_key(&i.id)) {
+        if item.content.trim().is_empty() {
+            continue;
+        }
+        let mut imported = ImportedTask::new(&strip_markdown_links(&item.content));
+        let task = &mut imported.task;
+        task.content = item.description.clone().filter(|d| !d.trim().is_empty());
+        task.is_important = item.priority == Some(4);
+        task.is_completed = flag(&item.checked);
+        if let Some(due) = &item.due {
+            task.due_date = due.date.as_deref().and_then(parse_day);
+            if due.is_recurring {
+                task.repeat_rule = due.string.as_deref().and_then(parse_recurrence).map(|r| r.to_json());
+            }
+        }
+        if let Some(added) = item.added_at.as_deref().and_then(parse_timestamp) {
+            task.created_at = added;
+        }
+        if let Some(completed) = item.completed_at.as_deref().and_then(parse_timestamp) {
+            task.updated_at = completed;
+        }
+        for label in &item.labels {
+            let name = match label {
+                Value::String(name) => Some(name.clone()),
+                other => label_names.get(&id_key(other)).cloned(),
+            };
+            if let Some(name) = name {
+                imported.add_tag(&name);
+            }
+        }
+
+        let id = id_key(&item.id);
+        order.push((id_key(&item.project_id), id.clone()));
+        tasks.insert(id, imported);
+    }
+
+    for item in items.iter().filter(|i| root_of(&id_key(&i.id)) != id_key(&i.id)) {
+        if let Some(parent) = tasks.get_mut(&root_of(&id_key(&item.id))) {
+            parent.add_subtask(&strip_markdown_links(&item.content), flag(&item.checked));
+        }
+    }
+
+    let mut lists = Vec::new();
+    for project in backup.projects.iter().filter(|p| !p.is_deleted && !p.is_archived) {
+        let project_id = id_key(&project.id);
+        let project_tasks = order
+            .iter()
+            .filter(|(pid, _)| *pid == project_id)
+            .filter_map(|(_, id)| tasks.remove(id))
+            .collect();
+        lists.push(ImportedList {
+            // The Inbox maps onto iToDo's default list
+            name: if project.inbox_project { None } else { Some(project.name.clone()) },
+            color: None,
+            tasks: project_tasks,
+        });
+    }
+
+    // Items whose project is missing from the backup still get imported; archived ones are skipped
+    let known: Vec<String> = backup.projects.iter().map(|p| id_key(&p.id)).collect();
+    let orphans: Vec<ImportedTask> = order
+        .iter()
+        .filter(|(pid, _)| !known.contains(pid))
+        .filter_map(|(_, id)| tasks.remove(id))
+        .collect();
+    if !orphans.is_empty() {
+        lists.push(ImportedList { name: None, color: None, tasks: orphans });
+    }
+
+    Ok(lists)
+}
+
+// ============== CSV template ==============
+
+fn parse_csv(data: &str) -> Result<Vec<ImportedList>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(data.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| t!("Failed to read Todoist CSV header: {}", e))?
+        .iter()
+        .map(|h| h.trim().to_ascii_uppercase())
+        .collect();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let (type_col, content_col) = match (column("TYPE"), column("CONTENT")) {
+        (Some(t), Some(c)) => (t, c),
+        _ => return Err(t!("Not a Todoist CSV export: TYPE and CONTENT columns are required")),
+    };
+    let description_col = column("DESCRIPTION");
+    let priority_col = column("PRIORITY");
+    let indent_col = column("INDENT");
+    let date_col = column("DATE");
+
+    let mut tasks: Vec<ImportedTask> = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| t!("Failed to read Todoist CSV: {}", e))?;
+        let field = |index: Option<usize>| index.and_then(|i| record.get(i)).map(str::trim).unwrap_or("");
+
+        let content = field(Some(content_col));
+        match field(Some(type_col)).to_ascii_lowercase().as_str() {
+            "task" => {}
+            // Comments follow the task they belong to
+            "note" => {
+                if let Some(task) = tasks.last_mut().filter(|_| !content.is_empty()) {
+                    let notes = task.task.content.get_or_insert_with(String::new);
+                    if !notes.is_empty() {
+                        notes.push('\n');
+                    }
+                    notes.push_str(content);
+                }
+                continue;
+            }
+            _ => continue,
+        }
+        if content.is_empty() {
+            continue;
+        }
+
+        let (title, labels) = split_labels(content);
+        let indent: u32 = field(indent_col).parse().unwrap_or(1);
+        if indent > 1 {
+            if let Some(parent) = tasks.last_mut() {
+                parent.add_subtask(&title, false);
+                continue;
+            }
+        }
+
+        let mut imported = ImportedTask::new(&title);
+        imported.task.content = Some(field(description_col).to_string()).filter(|d| !d.is_empty());
+        // Templates number priorities like the app: 1 is the highest
+        imported.task.is_important = field(priority_col) == "1";
+        let date = field(date_col);
+        imported.task.due_date = parse_day(date);
+        imported.task.repeat_rule = parse_recurrence(date).map(|r| r.to_json());
+        for label in labels {
+            imported.add_tag(&label);
+        }
+        tasks.push(imported);
+    }
+
+    Ok(vec![ImportedList {
+        name: Some(CSV_LIST_NAME.to_string()),
+        color: None,
+        tasks,
+    }])
+}
+
+// Pulls inline "@label" words out of a task title
+fn split_labels(content: &str) -> (String, Vec<String>) {
+    let mut words = Vec::new();
+    let mut labels = Vec::new();
+    for word in content.split_whitespace() {
+        match word.strip_prefix('@').filter(|l| !l.is_empty()) {
+            Some(label) => labels.push(label.to_string()),
+            None => words.push(word),
+        }
+    }
+    (strip_markdown_links(&words.join(" ")), labels)
+}
+
+// Todoist stores links as "[text](url)"; keep the text
+fn strip_markdown_links(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(open) = rest.find('[') {
+        let after = &rest[open + 1..];
+        let parsed = after.find("](").and_then(|close| {
+            let tail = &after[close + 2..];
+            tail.find(')').map(|end| (&after[..close], &tail[end + 1..]))
+        });
+        match parsed {
+            Some((text, remainder)) => {
+                out.push_str(&rest[..open]);
+                out.push_str(text);
+                rest = remainder;
+            }
+            None => {
+                out.push_str(&rest[..=open]);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+// Understands the common English recurrence phrases: "every day", "every 2 weeks",
+// "every mon, fri", "every month", "every 15", "daily", "weekly", "monthly", "yearly"
+fn parse_recurrence(text: &str) -> Option<RepeatRule> {
+    let text = text.trim().to_ascii_lowercase();
+    match text.as_str() {
+        "daily" | "every day" => return Some(RepeatRule::new(RepeatKind::Daily)),
+        "weekly" | "every week" => return Some(RepeatRule::new(RepeatKind::Weekly)),
+        "monthly" | "every month" => return Some(RepeatRule::new(RepeatKind::Monthly)),
+        "yearly" | "every year" => return Some(RepeatRule::new(RepeatKind::Yearly)),
+        _ => {}
+    }
+
+    let rest = text.strip_prefix("every ")?;
+    let words: Vec<&str> = rest.split(|c: char| c == ',' || c.is_whitespace()).filter(|w| !w.is_empty()).collect();
+
+    if let [count, unit] = words.as_slice() {
+        if let Ok(interval) = count.parse::<u32>() {
+            let kind = match unit.trim_end_matches('s') {
+                "day" => RepeatKind::Daily,
+                "week" => RepeatKind::Weekly,
+                "month" => RepeatKind::Monthly,
+                "year" => RepeatKind::Yearly,
+                _ => return None,
+            };
+            let mut rule = RepeatRule::new(kind);
+            rule.interval = Some(interval).filter(|i| *i > 1);
+            return Some(rule);
+        }
+    }
+
+    // Not all weekdays means it may still be days of the month, as in "every 15"
+    let days: Option<Vec<String>> = words
+        .iter()
+        .filter(|w| **w != "and")
+        .map(|w| WEEKDAYS.iter().find(|d| w.starts_with(&d.to_ascii_lowercase())).map(|d| d.to_string()))
+        .collect();
+    if let Some(days) = days.filter(|d| !d.is_empty()) {
+        let mut rule = RepeatRule::new(RepeatKind::Weekly);
+        rule.days = Some(days);
+        return Some(rule);
+    }
+
+    let month_days: Vec<u32> = words
+        .iter()
+        .map(|w| w.trim_end_matches(|c: char| c.is_ascii_alphabetic()).parse::<u32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if !month_days.is_empty() && month_days.iter().all(|d| (1..=31).contains(d)) {
+        let mut rule = RepeatRule::new(RepeatKind::Monthly);
+        rule.days_of_month = Some(month_days);
+        return Some(rule);
+    }
+    None
+}
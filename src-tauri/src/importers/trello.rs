@@ -0,0 +1,171 @@
+// Trello, from a board's "Export as JSON".
+//
+// The board becomes one list. Cards become tasks, checklist items their subtasks, and labels
+// their tags; the name of the Trello column a card sits in is added as a tag as well.
+
+use super::{parse_day, parse_timestamp, ImportedList, ImportedTask, Importer};
+use crate::i18n::t;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+pub struct TrelloImporter;
+
+impl Importer for TrelloImporter {
+    fn id(&self) -> &'static str {
+        "trello"
+    }
+
+    fn name(&self) -> &'static str {
+        "Trello"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn parse(&self, data: &str) -> Result<Vec<ImportedList>, String> {
+        let board: Board = serde_json::from_str(data)
+            .map_err(|e| t!("Failed to parse Trello board: {}", e))?;
+        Ok(vec![convert_board(board)])
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Board {
+    name: String,
+    #[serde(default)]
+    prefs: Option<BoardPrefs>,
+    #[serde(default)]
+    lists: Vec<Column>,
+    #[serde(default)]
+    cards: Vec<Card>,
+    #[serde(default)]
+    checklists: Vec<Checklist>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BoardPrefs {
+    #[serde(default)]
+    background_color: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Column {
+    id: String,
+    name: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    pos: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Card {
+    id: String,
+    name: String,
+    #[serde(default)]
+    desc: String,
+    #[serde(default)]
+    id_list: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    pos: f64,
+    #[serde(default)]
+    due: Option<String>,
+    #[serde(default)]
+    due_complete: bool,
+    #[serde(default)]
+    start: Option<String>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    date_last_activity: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Checklist {
+    id_card: String,
+    #[serde(default)]
+    pos: f64,
+    #[serde(default)]
+    check_items: Vec<CheckItem>,
+}
+
+#[derive(Deserialize)]
+struct CheckItem {
+    name: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    pos: f64,
+}
+
+fn convert_board(board: Board) -> ImportedList {
+    // Archived columns and cards are left out, like in the board view
+    let columns: HashMap<&str, &Column> = board
+        .lists
+        .iter()
+        .filter(|c| !c.closed)
+        .map(|c| (c.id.as_str(), c))
+        .collect();
+
+    let mut checklists: Vec<&Checklist> = board.checklists.iter().collect();
+    checklists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+
+    let mut cards: Vec<(&Column, &Card)> = board
+        .cards
+        .iter()
+        .filter(|card| !card.closed && !card.name.trim().is_empty())
+        .filter_map(|card| columns.get(card.id_list.as_str()).map(|column| (*column, card)))
+        .collect();
+    cards.sort_by(|(col_a, a), (col_b, b)| col_a.pos.total_cmp(&col_b.pos).then(a.pos.total_cmp(&b.pos)));
+
+    let tasks = cards
+        .into_iter()
+        .map(|(column, card)| {
+            let mut imported = ImportedTask::new(&card.name);
+            let task = &mut imported.task;
+            task.content = Some(card.desc.trim().to_string()).filter(|d| !d.is_empty());
+            task.is_completed = card.due_complete;
+            task.due_date = card.due.as_deref().and_then(parse_day);
+            task.start_date = card.start.as_deref().and_then(parse_day);
+            if let Some(activity) = card.date_last_activity.as_deref().and_then(parse_timestamp) {
+                task.updated_at = activity;
+            }
+
+            for checklist in checklists.iter().filter(|c| c.id_card == card.id) {
+                let mut items: Vec<&CheckItem> = checklist.check_items.iter().collect();
+                items.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+                for item in items {
+                    imported.add_subtask(&item.name, item.state == "complete");
+                }
+            }
+
+            imported.add_tag(&column.name);
+            for label in &card.labels {
+                imported.add_tag(&label.name);
+            }
+            imported
+        })
+        .collect();
+
+    ImportedList {
+        name: Some(board.name),
+        color: board
+            .prefs
+            .and_then(|p| p.background_color)
+            .filter(|c| c.starts_with('#')),
+        tasks,
+    }
+}
@@ -0,0 +1,199 @@
+// Database health checks and repairs.
+//
+// `check` only reads; `repair` fixes what can be fixed without losing tasks: orphaned tasks move
+// to the default list, rows that belong to missing tasks are dropped, unreadable dates and repeat
+// rules are cleared and the indexes are rebuilt.
+
+use crate::i18n::t;
+use crate::repeat::RepeatRule;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+const DATE_FIELDS: [&str; 3] = ["due_date", "start_date", "remind_time"];
+const TIMESTAMP_FIELDS: [&str; 2] = ["created_at", "updated_at"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldIssue {
+    pub task_id: String,
+    pub field: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub is_ok: bool,
+    // Output of `PRAGMA integrity_check`; a single "ok" when SQLite found nothing
+    pub integrity_check: Vec<String>,
+    pub default_lists: i64,
+    pub orphan_tasks: Vec<String>,
+    pub orphan_subtasks: Vec<String>,
+    pub orphan_tags: i64,
+    pub invalid_dates: Vec<FieldIssue>,
+    pub invalid_repeat_rules: Vec<FieldIssue>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepairReport {
+    pub rehomed_tasks: usize,
+    pub removed_subtasks: usize,
+    pub removed_tags: usize,
+    pub cleared_dates: usize,
+    pub reset_timestamps: usize,
+    pub cleared_repeat_rules: usize,
+    pub fixed_default_list: bool,
+    pub reindexed: bool,
+    // Backup taken before anything was changed
+    pub backup_id: Option<String>,
+    pub after: Option<IntegrityReport>,
+}
+
+pub fn check(conn: &Connection) -> Result<IntegrityReport, String> {
+    let integrity_check = integrity_messages(conn)?;
+
+    let default_lists: i64 = conn
+        .query_row("SELECT COUNT(*) FROM lists WHERE is_default = 1", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let orphan_tasks = query_ids(conn, "SELECT id FROM tasks WHERE list_id NOT IN (SELECT id FROM lists)")?;
+    let orphan_subtasks = query_ids(conn, "SELECT id FROM subtasks WHERE task_id NOT IN (SELECT id FROM tasks)")?;
+    let orphan_tags: i64 = conn
+        .query_row("SELECT COUNT(*) FROM task_tags WHERE task_id NOT IN (SELECT id FROM tasks)", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+
+    let mut invalid_dates = Vec::new();
+    let mut invalid_repeat_rules = Vec::new();
+    let mut stmt = conn
+        .prepare("SELECT id, due_date, start_date, remind_time, created_at, updated_at, repeat_rule FROM tasks")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            let values: Vec<Option<String>> = (1..=6).map(|i| row.get(i)).collect::<Result<_, _>>()?;
+            Ok((row.get::<_, String>(0)?, values))
+        })
+        .map_err(|e| e.to_string())?;
+
+    for row in rows {
+        let (task_id, values) = row.map_err(|e| e.to_string())?;
+        let fields = DATE_FIELDS.iter().chain(TIMESTAMP_FIELDS.iter());
+        for (field, value) in fields.zip(values.iter()) {
+            if let Some(value) = value.as_deref().filter(|v| !is_valid_date(v)) {
+                invalid_dates.push(FieldIssue {
+                    task_id: task_id.clone(),
+                    field: field.to_string(),
+                    value: value.to_string(),
+                });
+            }
+        }
+        if let Some(rule) = values[5].as_deref().filter(|r| !r.trim().is_empty() && RepeatRule::parse(r).is_none()) {
+            invalid_repeat_rules.push(FieldIssue {
+                task_id,
+                field: "repeat_rule".to_string(),
+                value: rule.to_string(),
+            });
+        }
+    }
+
+    let is_ok = integrity_check == ["ok"]
+        && default_lists == 1
+        && orphan_tasks.is_empty()
+        && orphan_subtasks.is_empty()
+        && orphan_tags == 0
+        && invalid_dates.is_empty()
+        && invalid_repeat_rules.is_empty();
+
+    Ok(IntegrityReport {
+        is_ok,
+        integrity_check,
+        default_lists,
+        orphan_tasks,
+        orphan_subtasks,
+        orphan_tags,
+        invalid_dates,
+        invalid_repeat_rules,
+    })
+}
+
+// Fixes the problems `check` reports. The default list must exist (see `init_database`) unless
+// there are several, in which case the first one in sidebar order is kept as the default.
+pub fn repair(conn: &Connection) -> Result<RepairReport, String> {
+    let found = check(conn)?;
+    let mut report = RepairReport::default();
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    if found.default_lists > 1 {
+        tx.execute(
+            "UPDATE lists SET is_default = 0 WHERE is_default = 1 AND id != (SELECT id FROM lists WHERE is_default = 1 ORDER BY order_index ASC, created_at ASC LIMIT 1)",
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+        report.fixed_default_list = true;
+    }
+    let default_id: String = tx
+        .query_row("SELECT id FROM lists WHERE is_default = 1 LIMIT 1", [], |row| row.get(0))
+        .map_err(|e| t!("No default list to move orphaned tasks to: {}", e))?;
+
+    report.rehomed_tasks = tx
+        .execute("UPDATE tasks SET list_id = ?1 WHERE list_id NOT IN (SELECT id FROM lists)", [&default_id])
+        .map_err(|e| e.to_string())?;
+    report.removed_subtasks = tx
+        .execute("DELETE FROM subtasks WHERE task_id NOT IN (SELECT id FROM tasks)", [])
+        .map_err(|e| e.to_string())?;
+    report.removed_tags = tx
+        .execute("DELETE FROM task_tags WHERE task_id NOT IN (SELECT id FROM tasks)", [])
+        .map_err(|e| e.to_string())?;
+
+    let now = Utc::now().to_rfc3339();
+    for issue in &found.invalid_dates {
+        // Column names come from the fixed lists above, never from the database
+        if TIMESTAMP_FIELDS.contains(&issue.field.as_str()) {
+            tx.execute(&format!("UPDATE tasks SET {} = ?1 WHERE id = ?2", issue.field), params![now, issue.task_id])
+                .map_err(|e| e.to_string())?;
+            report.reset_timestamps += 1;
+        } else {
+            tx.execute(&format!("UPDATE tasks SET {} = NULL WHERE id = ?1", issue.field), [&issue.task_id])
+                .map_err(|e| e.to_string())?;
+            report.cleared_dates += 1;
+        }
+    }
+    for issue in &found.invalid_repeat_rules {
+        tx.execute("UPDATE tasks SET repeat_rule = NULL WHERE id = ?1", [&issue.task_id])
+            .map_err(|e| e.to_string())?;
+        report.cleared_repeat_rules += 1;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    conn.execute_batch("REINDEX").map_err(|e| t!("Failed to rebuild indexes: {}", e))?;
+    report.reindexed = true;
+    report.after = Some(check(conn)?);
+    Ok(report)
+}
+
+fn integrity_messages(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check").map_err(|e| e.to_string())?;
+    let messages = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(messages)
+}
+
+fn query_ids(conn: &Connection, sql: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(ids)
+}
+
+// RFC 3339 as written by the app, plus the plain date and local date-time forms older rows use
+fn is_valid_date(value: &str) -> bool {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value).is_ok()
+        || NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+        || NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").is_ok()
+        || NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M").is_ok()
+}
@@ -1,12 +1,36 @@
+mod anonymize;
+mod app_lock;
+mod backup;
+mod bundle;
+mod csv_io;
+mod data_location;
+mod diagnostics;
+mod encryption;
+mod html_report;
+mod http_api;
+pub mod i18n;
+mod ical;
+pub mod importers;
+mod integrity;
+mod logging;
+mod markdown;
+mod org;
+mod repeat;
+mod rpc;
+mod settings;
+mod taskwarrior;
+mod todotxt;
+mod workspaces;
+
 use chrono::Utc;
-use directories::ProjectDirs;
-use log::info;
+use log::{error, info};
 use rusqlite::{params, Connection, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use std::sync::Mutex;
-use tauri::{Manager, State};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use tauri::{Emitter, Manager, State};
 use uuid::Uuid;
 
 // ============== Models ==============
@@ -38,6 +62,16 @@ pub struct List {
     pub order: i32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subtask {
+    pub id: String,
+    pub task_id: String,
+    pub title: String,
+    pub is_completed: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTaskInput {
     pub title: String,
@@ -63,6 +97,19 @@ pub struct UpdateTaskInput {
     pub list_id: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSubtaskInput {
+    pub task_id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateSubtaskInput {
+    pub id: String,
+    pub title: Option<String>,
+    pub is_completed: Option<bool>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateListInput {
     pub name: String,
@@ -83,20 +130,157 @@ pub struct UpdateListInput {
 
 pub struct DbConnection(pub Mutex<Connection>);
 
-fn get_data_dir() -> PathBuf {
-    if let Some(proj_dirs) = ProjectDirs::from("com", "itodo", "iToDo") {
-        let data_dir = proj_dirs.data_dir().to_path_buf();
-        fs::create_dir_all(&data_dir).ok();
-        data_dir
-    } else {
-        PathBuf::from(".")
+const DB_FILE_NAME: &str = "itodo.db";
+// How long the app waits for a write by itodo-cli or another program before giving up
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+// Resolved on first use; afterwards only `relocate_data_dir` changes it
+static DATA_LOCATION: RwLock<Option<data_location::DataLocation>> = RwLock::new(None);
+
+fn current_data_location() -> data_location::DataLocation {
+    if let Some(location) = DATA_LOCATION.read().ok().and_then(|l| l.clone()) {
+        return location;
+    }
+    let location = data_location::resolve();
+    if let Ok(mut cached) = DATA_LOCATION.write() {
+        *cached = Some(location.clone());
     }
+    location
+}
+
+fn get_data_dir() -> PathBuf {
+    let data_dir = current_data_location().dir;
+    fs::create_dir_all(&data_dir).ok();
+    data_dir
 }
 
 fn get_log_dir() -> PathBuf {
     get_data_dir().join("logs")
 }
 
+fn get_attachments_dir() -> PathBuf {
+    get_data_dir().join("attachments")
+}
+
+// Database of the last-used workspace
+fn get_db_path() -> PathBuf {
+    let data_dir = get_data_dir();
+    let registry = workspaces::WorkspaceRegistry::load(&data_dir);
+    let db_path = workspaces::db_path(&data_dir, &registry.last_used, DB_FILE_NAME);
+    if let Some(parent) = db_path.parent() {
+        fs::create_dir_all(parent).ok();
+    }
+    db_path
+}
+
+// Backups of the workspace the database at `db_path` belongs to. Goes by the file, not by
+// `last_used`, which another process such as itodo-cli can change while the app keeps its database.
+fn backup_dir_for(db_path: &Path) -> Result<PathBuf, String> {
+    let data_dir = get_data_dir();
+    let id = workspaces::WorkspaceRegistry::load(&data_dir)
+        .id_for_db_path(&data_dir, db_path, DB_FILE_NAME)
+        .ok_or_else(|| t!("{} is not a workspace database, so it has no backups", db_path.display()))?;
+    Ok(workspaces::backup_dir(&data_dir, &id))
+}
+
+fn get_backup_dir(conn: &Connection) -> Result<PathBuf, String> {
+    backup_dir_for(&live_db_path(conn).ok_or_else(|| t!("No database is open"))?)
+}
+
+fn get_settings_path() -> PathBuf {
+    get_data_dir().join("settings.toml")
+}
+
+fn load_settings() -> settings::Settings {
+    let data_dir = get_data_dir();
+    match settings::Settings::load(&get_settings_path()) {
+        Ok(Some(settings)) => settings,
+        // Before settings.toml, the backup settings had a file of their own
+        Ok(None) => settings::Settings {
+            backup: backup::BackupConfig::load(&data_dir.join("backup.json")),
+            ..Default::default()
+        },
+        Err(e) => {
+            error!("Failed to read settings, using the defaults: {}", e);
+            settings::Settings::default()
+        }
+    }
+}
+
+// Saves the settings and applies the parts the backend uses itself
+fn save_settings(conn: &Connection, settings: &settings::Settings) -> Result<(), String> {
+    settings.save(&get_settings_path())?;
+    logging::set_level(settings.log_level.filter());
+    if i18n::language() != settings.language {
+        i18n::set_language(settings.language);
+        // Fails harmlessly in recovery mode, when no database is open
+        if let Err(e) = localize_default_list(conn) {
+            error!("Failed to rename the default list: {}", e);
+        }
+    }
+    Ok(())
+}
+
+
+fn get_lock_config_path() -> PathBuf {
+    get_data_dir().join("lock.json")
+}
+
+fn get_api_config_path() -> PathBuf {
+    get_data_dir().join("api.json")
+}
+
+// Opens and migrates a database. Locked or read-only files are reported here, instead of on
+// the first write after the window is up.
+fn open_database(path: &Path) -> Result<Connection, String> {
+    open_database_waiting(path, BUSY_TIMEOUT)
+}
+
+// The timeout also covers the write check below, so tools that would rather wait pass theirs in
+fn open_database_waiting(path: &Path, busy_timeout: std::time::Duration) -> Result<Connection, String> {
+    let conn = Connection::open(path).map_err(|e| t!("Failed to open database: {}", e))?;
+    if encryption::is_encrypted(path) {
+        let key = encryption::key_for(path).ok_or_else(|| t!("The database is encrypted. Enter the passphrase to unlock it."))?;
+        encryption::apply_key(&conn, &key)?;
+    }
+    conn.busy_timeout(busy_timeout).map_err(|e| e.to_string())?;
+    init_database(&conn).map_err(|e| t!("Failed to initialize database: {}", e))?;
+    conn.execute_batch("BEGIN IMMEDIATE; ROLLBACK;")
+        .map_err(|e| t!("Database is not writable: {}", e))?;
+    Ok(conn)
+}
+
+// Tools that run beside the app show their messages in the language it is set to
+pub fn use_app_language() {
+    i18n::set_language(load_settings().language);
+}
+
+// For tools that run beside the app, such as the command-line client: opens the database of the
+// last-used workspace, unlocking it with `passphrase` when it is encrypted and waiting up to
+// `busy_timeout` for the app's writes
+pub fn open_current_database(passphrase: Option<&str>, busy_timeout: std::time::Duration) -> Result<Connection, String> {
+    use_app_language();
+    let db_path = get_db_path();
+    if encryption::is_encrypted(&db_path) && encryption::key_for(&db_path).is_none() {
+        let passphrase = passphrase.ok_or_else(|| t!("The database is encrypted. Enter the passphrase to unlock it."))?;
+        unlock_with_passphrase(&db_path, passphrase)?;
+    }
+    open_database_waiting(&db_path, busy_timeout)
+}
+
+// File behind a connection; None for the in-memory placeholder used in recovery mode
+fn live_db_path(conn: &Connection) -> Option<PathBuf> {
+    conn.path().filter(|p| !p.is_empty()).map(PathBuf::from)
+}
+
+// Copies a live database with the backup API, keeping its encryption key
+fn copy_live_database(conn: &Connection, target: &Path, key: Option<&encryption::DbKey>) -> Result<(), String> {
+    let mut target_conn = encryption::open_file(target, rusqlite::OpenFlags::default(), key)?;
+    rusqlite::backup::Backup::new(conn, &mut target_conn)
+        .and_then(|b| b.run_to_completion(256, std::time::Duration::ZERO, None))
+        .map_err(|e| e.to_string())
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct AboutInfo {
     pub app_name: String,
@@ -155,6 +339,18 @@ fn get_about_info(app: tauri::AppHandle) -> AboutInfo {
     load_about_config()
 }
 
+// A default list that still has its original name follows the language setting
+fn localize_default_list(conn: &Connection) -> SqliteResult<usize> {
+    conn.execute(
+        "UPDATE lists SET name = ?1 WHERE is_default = 1 AND name != ?1 AND name IN (?2, ?3)",
+        params![
+            t!("My Day"),
+            i18n::translate_to(settings::Language::EnUs, "My Day"),
+            i18n::translate_to(settings::Language::ZhCn, "My Day")
+        ],
+    )
+}
+
 fn init_database(conn: &Connection) -> SqliteResult<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS lists (
@@ -188,15 +384,39 @@ fn init_database(conn: &Connection) -> SqliteResult<()> {
         [],
     )?;
 
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS subtasks (
+            id TEXT PRIMARY KEY,
+            task_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            is_completed INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS task_tags (
+            task_id TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (task_id, tag),
+            FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
     // Create default list if not exists
     let count: i32 = conn.query_row("SELECT COUNT(*) FROM lists WHERE is_default = 1", [], |row| row.get(0))?;
     if count == 0 {
         let now = Utc::now().to_rfc3339();
         conn.execute(
             "INSERT INTO lists (id, name, color, icon, is_default, created_at, order_index) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![Uuid::new_v4().to_string(), "我的一天", Some("#0078D4".to_string()), Some("sun".to_string()), 1, now, 0],
+            params![Uuid::new_v4().to_string(), t!("My Day"), Some("#0078D4".to_string()), Some("sun".to_string()), 1, now, 0],
         )?;
     }
+    localize_default_list(conn)?;
 
     Ok(())
 }
@@ -218,6 +438,17 @@ fn row_to_task(row: &rusqlite::Row) -> rusqlite::Result<Task> {
     })
 }
 
+fn row_to_subtask(row: &rusqlite::Row) -> rusqlite::Result<Subtask> {
+    Ok(Subtask {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        title: row.get(2)?,
+        is_completed: row.get::<_, i32>(3)? == 1,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
 fn row_to_list(row: &rusqlite::Row) -> rusqlite::Result<List> {
     Ok(List {
         id: row.get(0)?,
@@ -232,9 +463,7 @@ fn row_to_list(row: &rusqlite::Row) -> rusqlite::Result<List> {
 
 // ============== Tauri Commands - Lists ==============
 
-#[tauri::command]
-fn get_lists(db: State<DbConnection>) -> Result<Vec<List>, String> {
-    let conn = db.0.lock().map_err(|e| e.to_string())?;
+pub fn load_lists(conn: &Connection) -> Result<Vec<List>, String> {
     let mut stmt = conn
         .prepare("SELECT id, name, color, icon, is_default, created_at, order_index FROM lists ORDER BY order_index ASC")
         .map_err(|e| e.to_string())?;
@@ -248,9 +477,22 @@ fn get_lists(db: State<DbConnection>) -> Result<Vec<List>, String> {
     Ok(lists)
 }
 
+pub fn load_list(conn: &Connection, id: &str) -> Result<List, String> {
+    conn.query_row(
+        "SELECT id, name, color, icon, is_default, created_at, order_index FROM lists WHERE id = ?1",
+        [id],
+        row_to_list,
+    )
+    .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
-fn create_list(input: CreateListInput, db: State<DbConnection>) -> Result<List, String> {
+fn get_lists(db: State<DbConnection>) -> Result<Vec<List>, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_lists(&conn)
+}
+
+pub fn add_list(conn: &Connection, input: CreateListInput) -> Result<List, String> {
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
     let created_at = now.clone();
@@ -275,9 +517,12 @@ fn create_list(input: CreateListInput, db: State<DbConnection>) -> Result<List,
 }
 
 #[tauri::command]
-fn update_list(input: UpdateListInput, db: State<DbConnection>) -> Result<List, String> {
+fn create_list(input: CreateListInput, db: State<DbConnection>) -> Result<List, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    add_list(&conn, input)
+}
 
+pub fn edit_list(conn: &Connection, input: UpdateListInput) -> Result<List, String> {
     let mut stmt = conn
         .prepare("SELECT id, name, color, icon, is_default, created_at, order_index FROM lists WHERE id = ?1")
         .map_err(|e| e.to_string())?;
@@ -308,29 +553,37 @@ fn update_list(input: UpdateListInput, db: State<DbConnection>) -> Result<List,
 }
 
 #[tauri::command]
-fn delete_list(id: String, db: State<DbConnection>) -> Result<(), String> {
+fn update_list(input: UpdateListInput, db: State<DbConnection>) -> Result<List, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    edit_list(&conn, input)
+}
 
+pub fn remove_list(conn: &Connection, id: &str) -> Result<(), String> {
     let is_default: i32 = conn
         .query_row("SELECT is_default FROM lists WHERE id = ?1", [&id], |row| row.get(0))
         .map_err(|e| e.to_string())?;
 
     if is_default == 1 {
-        return Err("Cannot delete default list".to_string());
+        return Err(t!("Cannot delete default list"));
     }
 
+    conn.execute("DELETE FROM subtasks WHERE task_id IN (SELECT id FROM tasks WHERE list_id = ?1)", [&id]).map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM task_tags WHERE task_id IN (SELECT id FROM tasks WHERE list_id = ?1)", [&id]).map_err(|e| e.to_string())?;
     conn.execute("DELETE FROM tasks WHERE list_id = ?1", [&id]).map_err(|e| e.to_string())?;
     conn.execute("DELETE FROM lists WHERE id = ?1", [&id]).map_err(|e| e.to_string())?;
 
     Ok(())
 }
 
-// ============== Tauri Commands - Tasks ==============
-
 #[tauri::command]
-fn get_tasks(list_id: Option<String>, db: State<DbConnection>) -> Result<Vec<Task>, String> {
+fn delete_list(id: String, db: State<DbConnection>) -> Result<(), String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    remove_list(&conn, &id)
+}
+
+// ============== Tauri Commands - Tasks ==============
 
+pub fn load_tasks(conn: &Connection, list_id: Option<&str>) -> Result<Vec<Task>, String> {
     let tasks = match list_id {
         Some(lid) => {
             let mut stmt = conn
@@ -358,8 +611,12 @@ fn get_tasks(list_id: Option<String>, db: State<DbConnection>) -> Result<Vec<Tas
 }
 
 #[tauri::command]
-fn get_important_tasks(db: State<DbConnection>) -> Result<Vec<Task>, String> {
+fn get_tasks(list_id: Option<String>, db: State<DbConnection>) -> Result<Vec<Task>, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_tasks(&conn, list_id.as_deref())
+}
+
+pub fn load_important_tasks(conn: &Connection) -> Result<Vec<Task>, String> {
     let mut stmt = conn
         .prepare("SELECT id, title, content, is_completed, is_important, due_date, start_date, remind_time, repeat_rule, list_id, created_at, updated_at FROM tasks WHERE is_important = 1 ORDER BY is_completed ASC, created_at DESC")
         .map_err(|e| e.to_string())?;
@@ -374,8 +631,12 @@ fn get_important_tasks(db: State<DbConnection>) -> Result<Vec<Task>, String> {
 }
 
 #[tauri::command]
-fn get_today_tasks(db: State<DbConnection>) -> Result<Vec<Task>, String> {
+fn get_important_tasks(db: State<DbConnection>) -> Result<Vec<Task>, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_important_tasks(&conn)
+}
+
+pub fn load_today_tasks(conn: &Connection) -> Result<Vec<Task>, String> {
     let today = Utc::now().format("%Y-%m-%d").to_string();
 
     let mut stmt = conn
@@ -392,8 +653,12 @@ fn get_today_tasks(db: State<DbConnection>) -> Result<Vec<Task>, String> {
 }
 
 #[tauri::command]
-fn get_planned_tasks(db: State<DbConnection>) -> Result<Vec<Task>, String> {
+fn get_today_tasks(db: State<DbConnection>) -> Result<Vec<Task>, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_today_tasks(&conn)
+}
+
+pub fn load_planned_tasks(conn: &Connection) -> Result<Vec<Task>, String> {
     let today = Utc::now().format("%Y-%m-%d").to_string();
 
     let mut stmt = conn
@@ -410,8 +675,12 @@ fn get_planned_tasks(db: State<DbConnection>) -> Result<Vec<Task>, String> {
 }
 
 #[tauri::command]
-fn get_completed_tasks(db: State<DbConnection>) -> Result<Vec<Task>, String> {
+fn get_planned_tasks(db: State<DbConnection>) -> Result<Vec<Task>, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_planned_tasks(&conn)
+}
+
+pub fn load_completed_tasks(conn: &Connection) -> Result<Vec<Task>, String> {
     let mut stmt = conn
         .prepare("SELECT id, title, content, is_completed, is_important, due_date, start_date, remind_time, repeat_rule, list_id, created_at, updated_at FROM tasks WHERE is_completed = 1 ORDER BY updated_at DESC")
         .map_err(|e| e.to_string())?;
@@ -426,8 +695,12 @@ fn get_completed_tasks(db: State<DbConnection>) -> Result<Vec<Task>, String> {
 }
 
 #[tauri::command]
-fn search_tasks(query: String, db: State<DbConnection>) -> Result<Vec<Task>, String> {
+fn get_completed_tasks(db: State<DbConnection>) -> Result<Vec<Task>, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_completed_tasks(&conn)
+}
+
+pub fn find_tasks(conn: &Connection, query: &str) -> Result<Vec<Task>, String> {
     let search_pattern = format!("%{}%", query);
 
     let mut stmt = conn
@@ -444,8 +717,12 @@ fn search_tasks(query: String, db: State<DbConnection>) -> Result<Vec<Task>, Str
 }
 
 #[tauri::command]
-fn create_task(input: CreateTaskInput, db: State<DbConnection>) -> Result<Task, String> {
+fn search_tasks(query: String, db: State<DbConnection>) -> Result<Vec<Task>, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    find_tasks(&conn, &query)
+}
+
+pub fn add_task(conn: &Connection, input: CreateTaskInput) -> Result<Task, String> {
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
     let created_at = now.clone();
@@ -486,8 +763,12 @@ fn create_task(input: CreateTaskInput, db: State<DbConnection>) -> Result<Task,
 }
 
 #[tauri::command]
-fn update_task(input: UpdateTaskInput, db: State<DbConnection>) -> Result<Task, String> {
+fn create_task(input: CreateTaskInput, db: State<DbConnection>) -> Result<Task, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    add_task(&conn, input)
+}
+
+pub fn edit_task(conn: &Connection, input: UpdateTaskInput) -> Result<Task, String> {
     let now = Utc::now().to_rfc3339();
 
     let mut stmt = conn
@@ -548,16 +829,34 @@ fn update_task(input: UpdateTaskInput, db: State<DbConnection>) -> Result<Task,
 }
 
 #[tauri::command]
-fn delete_task(id: String, db: State<DbConnection>) -> Result<(), String> {
+fn update_task(input: UpdateTaskInput, db: State<DbConnection>) -> Result<Task, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    edit_task(&conn, input)
+}
+
+pub fn remove_task(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM subtasks WHERE task_id = ?1", [&id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM task_tags WHERE task_id = ?1", [&id])
+        .map_err(|e| e.to_string())?;
     conn.execute("DELETE FROM tasks WHERE id = ?1", [&id])
         .map_err(|e| e.to_string())?;
     Ok(())
 }
 
+#[tauri::command]
+fn delete_task(id: String, db: State<DbConnection>) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    remove_task(&conn, &id)
+}
+
 #[tauri::command]
 fn toggle_task_important(id: String, db: State<DbConnection>) -> Result<Task, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    toggle_important(&conn, &id)
+}
+
+fn toggle_important(conn: &Connection, id: &str) -> Result<Task, String> {
     let now = Utc::now().to_rfc3339();
 
     conn.execute(
@@ -576,6 +875,10 @@ fn toggle_task_important(id: String, db: State<DbConnection>) -> Result<Task, St
 #[tauri::command]
 fn toggle_task_completed(id: String, db: State<DbConnection>) -> Result<Task, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    toggle_completed(&conn, &id)
+}
+
+fn toggle_completed(conn: &Connection, id: &str) -> Result<Task, String> {
     let now = Utc::now().to_rfc3339();
 
     conn.execute(
@@ -591,117 +894,490 @@ fn toggle_task_completed(id: String, db: State<DbConnection>) -> Result<Task, St
         .map_err(|e| e.to_string())
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ExportData {
-    pub version: String,
-    pub export_date: String,
-    pub tasks: Vec<Task>,
-    pub lists: Vec<List>,
+pub fn load_task(conn: &Connection, id: &str) -> Result<Task, String> {
+    conn.query_row(
+        "SELECT id, title, content, is_completed, is_important, due_date, start_date, remind_time, repeat_rule, list_id, created_at, updated_at FROM tasks WHERE id = ?1",
+        [id],
+        row_to_task,
+    )
+    .map_err(|e| e.to_string())
+}
+
+// Unlike the toggles these are safe to repeat, for callers that may run twice
+pub fn set_task_completed(conn: &Connection, id: &str, completed: bool) -> Result<Task, String> {
+    conn.execute(
+        "UPDATE tasks SET is_completed = ?1, updated_at = ?2 WHERE id = ?3 AND is_completed != ?1",
+        params![completed as i32, Utc::now().to_rfc3339(), id],
+    ).map_err(|e| e.to_string())?;
+    load_task(conn, id)
+}
+
+pub fn set_task_important(conn: &Connection, id: &str, important: bool) -> Result<Task, String> {
+    conn.execute(
+        "UPDATE tasks SET is_important = ?1, updated_at = ?2 WHERE id = ?3 AND is_important != ?1",
+        params![important as i32, Utc::now().to_rfc3339(), id],
+    ).map_err(|e| e.to_string())?;
+    load_task(conn, id)
 }
 
+// ============== Tauri Commands - Subtasks ==============
+
 #[tauri::command]
-async fn export_tasks_to_file(list_id: Option<String>, db: State<'_, DbConnection>) -> Result<bool, String> {
-    let (tasks, lists) = {
-        let conn = db.0.lock().map_err(|e| e.to_string())?;
+fn get_subtasks(task_id: String, db: State<DbConnection>) -> Result<Vec<Subtask>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_task_subtasks(&conn, &task_id)
+}
 
-        let tasks = match list_id {
-            Some(lid) => {
-                let mut stmt = conn
-                    .prepare("SELECT id, title, content, is_completed, is_important, due_date, start_date, remind_time, repeat_rule, list_id, created_at, updated_at FROM tasks WHERE list_id = ?1 ORDER BY is_completed ASC, created_at DESC")
-                    .map_err(|e| e.to_string())?;
-                let result = stmt.query_map([&lid], row_to_task)
-                    .map_err(|e| e.to_string())?
-                    .collect::<Result<Vec<_>, _>>()
-                    .map_err(|e| e.to_string())?;
-                result
-            }
-            None => {
-                let mut stmt = conn
-                    .prepare("SELECT id, title, content, is_completed, is_important, due_date, start_date, remind_time, repeat_rule, list_id, created_at, updated_at FROM tasks ORDER BY is_completed ASC, created_at DESC")
-                    .map_err(|e| e.to_string())?;
-                let result = stmt.query_map([], row_to_task)
-                    .map_err(|e| e.to_string())?
-                    .collect::<Result<Vec<_>, _>>()
-                    .map_err(|e| e.to_string())?;
-                result
-            }
-        };
+fn load_task_subtasks(conn: &Connection, task_id: &str) -> Result<Vec<Subtask>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, task_id, title, is_completed, created_at, updated_at FROM subtasks WHERE task_id = ?1 ORDER BY created_at ASC")
+        .map_err(|e| e.to_string())?;
 
-        let mut stmt = conn
-            .prepare("SELECT id, name, color, icon, is_default, created_at, order_index FROM lists")
-            .map_err(|e| e.to_string())?;
-        let lists = stmt.query_map([], |row| {
-            Ok(List {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                color: row.get(2)?,
-                icon: row.get(3)?,
-                is_default: row.get(4)?,
-                created_at: row.get(5)?,
-                order: row.get(6)?,
-            })
-        })
+    let subtasks = stmt
+        .query_map([&task_id], row_to_subtask)
         .map_err(|e| e.to_string())?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| e.to_string())?;
 
-        (tasks, lists)
-    };
+    Ok(subtasks)
+}
 
-    let export_data = ExportData {
-        version: "1.0".to_string(),
-        export_date: Utc::now().to_rfc3339(),
-        tasks,
-        lists,
-    };
+#[tauri::command]
+fn get_all_subtasks(db: State<DbConnection>) -> Result<Vec<Subtask>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_all_subtasks(&conn)
+}
 
-    let json_data = serde_json::to_string_pretty(&export_data).map_err(|e| e.to_string())?;
+fn load_all_subtasks(conn: &Connection) -> Result<Vec<Subtask>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, task_id, title, is_completed, created_at, updated_at FROM subtasks ORDER BY created_at ASC")
+        .map_err(|e| e.to_string())?;
 
-    let data_dir = get_data_dir();
-    let file_name = format!("itodo-export-{}.json", chrono::Local::now().format("%Y-%m-%d_%H%M%S"));
-    let file_path = data_dir.join(&file_name);
+    let subtasks = stmt
+        .query_map([], row_to_subtask)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
 
-    fs::write(&file_path, json_data).map_err(|e| format!("Failed to write file: {}", e))?;
-    info!("Exported tasks to {:?}", file_path);
-    Ok(true)
+    Ok(subtasks)
 }
 
 #[tauri::command]
-async fn import_tasks(json_data: String, db: State<'_, DbConnection>) -> Result<Vec<Task>, String> {
-    let export_data: ExportData = serde_json::from_str(&json_data)
-        .map_err(|e| format!("Failed to parse import data: {}", e))?;
+fn create_subtask(input: CreateSubtaskInput, db: State<DbConnection>) -> Result<Subtask, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    add_subtask(&conn, input)
+}
+
+fn add_subtask(conn: &Connection, input: CreateSubtaskInput) -> Result<Subtask, String> {
+    let now = Utc::now().to_rfc3339();
+
+    let subtask = Subtask {
+        id: Uuid::new_v4().to_string(),
+        task_id: input.task_id,
+        title: input.title,
+        is_completed: false,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    insert_subtask(conn, &subtask)?;
+
+    Ok(subtask)
+}
 
+#[tauri::command]
+fn update_subtask(input: UpdateSubtaskInput, db: State<DbConnection>) -> Result<Subtask, String> {
     let conn = db.0.lock().map_err(|e| e.to_string())?;
+    edit_subtask(&conn, input)
+}
 
-    let mut imported_tasks = Vec::new();
+fn edit_subtask(conn: &Connection, input: UpdateSubtaskInput) -> Result<Subtask, String> {
+    let now = Utc::now().to_rfc3339();
 
-    for list in &export_data.lists {
-        let mut stmt = conn
-            .prepare("SELECT id FROM lists WHERE id = ?1")
-            .map_err(|e| e.to_string())?;
-        let exists: Result<String, _> = stmt.query_row([&list.id], |row| row.get(0));
+    let mut stmt = conn
+        .prepare("SELECT id, task_id, title, is_completed, created_at, updated_at FROM subtasks WHERE id = ?1")
+        .map_err(|e| e.to_string())?;
 
-        if exists.is_err() {
-            conn.execute(
-                "INSERT INTO lists (id, name, color, icon, is_default, created_at, order_index) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                (&list.id, &list.name, &list.color, &list.icon, &list.is_default, &list.created_at, &list.order),
-            ).map_err(|e| e.to_string())?;
-        }
+    let mut subtask = stmt
+        .query_row([&input.id], row_to_subtask)
+        .map_err(|e| e.to_string())?;
+
+    if let Some(title) = input.title {
+        subtask.title = title;
     }
+    if let Some(is_completed) = input.is_completed {
+        subtask.is_completed = is_completed;
+    }
+    subtask.updated_at = now;
 
-    for task in &export_data.tasks {
-        let new_id = Uuid::new_v4().to_string();
-        let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE subtasks SET title = ?1, is_completed = ?2, updated_at = ?3 WHERE id = ?4",
+        params![subtask.title, subtask.is_completed as i32, subtask.updated_at, subtask.id],
+    ).map_err(|e| e.to_string())?;
 
-        conn.execute(
-            "INSERT INTO tasks (id, title, content, is_completed, is_important, due_date, start_date, remind_time, repeat_rule, list_id, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
-            (
-                &new_id,
-                &task.title,
-                &task.content,
-                &task.is_completed,
-                &task.is_important,
-                &task.due_date,
+    Ok(subtask)
+}
+
+#[tauri::command]
+fn delete_subtask(id: String, db: State<DbConnection>) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    remove_subtask(&conn, &id)
+}
+
+fn remove_subtask(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM subtasks WHERE id = ?1", [&id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+fn toggle_subtask_completed(id: String, db: State<DbConnection>) -> Result<Subtask, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    toggle_subtask(&conn, &id)
+}
+
+fn toggle_subtask(conn: &Connection, id: &str) -> Result<Subtask, String> {
+    let now = Utc::now().to_rfc3339();
+
+    conn.execute(
+        "UPDATE subtasks SET is_completed = NOT is_completed, updated_at = ?1 WHERE id = ?2",
+        params![now, id],
+    ).map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT id, task_id, title, is_completed, created_at, updated_at FROM subtasks WHERE id = ?1")
+        .map_err(|e| e.to_string())?;
+
+    stmt.query_row([&id], row_to_subtask)
+        .map_err(|e| e.to_string())
+}
+
+fn insert_subtask(conn: &Connection, subtask: &Subtask) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO subtasks (id, task_id, title, is_completed, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            subtask.id,
+            subtask.task_id,
+            subtask.title,
+            subtask.is_completed as i32,
+            subtask.created_at,
+            subtask.updated_at
+        ],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn load_subtasks(conn: &Connection) -> Result<HashMap<String, Vec<Subtask>>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, task_id, title, is_completed, created_at, updated_at FROM subtasks ORDER BY created_at ASC")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], row_to_subtask)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut subtasks: HashMap<String, Vec<Subtask>> = HashMap::new();
+    for subtask in rows {
+        subtasks.entry(subtask.task_id.clone()).or_default().push(subtask);
+    }
+    Ok(subtasks)
+}
+
+// ============== Tauri Commands - Tags ==============
+
+fn load_task_tags(conn: &Connection) -> Result<HashMap<String, Vec<String>>, String> {
+    let mut stmt = conn
+        .prepare("SELECT task_id, tag FROM task_tags ORDER BY task_id, tag")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for (task_id, tag) in rows {
+        tags.entry(task_id).or_default().push(tag);
+    }
+    Ok(tags)
+}
+
+fn replace_task_tags(conn: &Connection, task_id: &str, tags: &[String]) -> Result<(), String> {
+    conn.execute("DELETE FROM task_tags WHERE task_id = ?1", [task_id])
+        .map_err(|e| e.to_string())?;
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        conn.execute(
+            "INSERT OR IGNORE INTO task_tags (task_id, tag) VALUES (?1, ?2)",
+            params![task_id, tag],
+        ).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn get_task_tags(task_id: String, db: State<DbConnection>) -> Result<Vec<String>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_tags(&conn, &task_id)
+}
+
+fn load_tags(conn: &Connection, task_id: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT tag FROM task_tags WHERE task_id = ?1 ORDER BY tag")
+        .map_err(|e| e.to_string())?;
+
+    let tags = stmt
+        .query_map([&task_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(tags)
+}
+
+#[tauri::command]
+fn set_task_tags(task_id: String, tags: Vec<String>, db: State<DbConnection>) -> Result<Vec<String>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    store_tags(&conn, &task_id, &tags)
+}
+
+fn store_tags(conn: &Connection, task_id: &str, tags: &[String]) -> Result<Vec<String>, String> {
+    replace_task_tags(conn, task_id, tags)?;
+
+    let mut tags: Vec<String> = tags.iter().map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
+    tags.sort();
+    tags.dedup();
+    Ok(tags)
+}
+
+// ============== Tauri Commands - Import/Export ==============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportData {
+    pub version: String,
+    pub export_date: String,
+    pub tasks: Vec<Task>,
+    pub lists: Vec<List>,
+}
+
+pub fn load_export_data(conn: &Connection, list_id: Option<&str>) -> Result<(Vec<Task>, Vec<List>), String> {
+    let tasks = match list_id {
+        Some(lid) => {
+            let mut stmt = conn
+                .prepare("SELECT id, title, content, is_completed, is_important, due_date, start_date, remind_time, repeat_rule, list_id, created_at, updated_at FROM tasks WHERE list_id = ?1 ORDER BY is_completed ASC, created_at DESC")
+                .map_err(|e| e.to_string())?;
+            let result = stmt.query_map([lid], row_to_task)
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            result
+        }
+        None => {
+            let mut stmt = conn
+                .prepare("SELECT id, title, content, is_completed, is_important, due_date, start_date, remind_time, repeat_rule, list_id, created_at, updated_at FROM tasks ORDER BY is_completed ASC, created_at DESC")
+                .map_err(|e| e.to_string())?;
+            let result = stmt.query_map([], row_to_task)
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            result
+        }
+    };
+
+    let mut stmt = conn
+        .prepare("SELECT id, name, color, icon, is_default, created_at, order_index FROM lists ORDER BY order_index ASC")
+        .map_err(|e| e.to_string())?;
+    let lists = stmt
+        .query_map([], row_to_list)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok((tasks, lists))
+}
+
+fn insert_task(conn: &Connection, task: &Task) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO tasks (id, title, content, is_completed, is_important, due_date, start_date, remind_time, repeat_rule, list_id, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            task.id,
+            task.title,
+            task.content,
+            task.is_completed as i32,
+            task.is_important as i32,
+            task.due_date,
+            task.start_date,
+            task.remind_time,
+            task.repeat_rule,
+            task.list_id,
+            task.created_at,
+            task.updated_at
+        ],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Inserts the task, or updates the existing row when its id is already known
+fn upsert_task(conn: &Connection, task: &Task) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO tasks (id, title, content, is_completed, is_important, due_date, start_date, remind_time, repeat_rule, list_id, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+         ON CONFLICT(id) DO UPDATE SET title = excluded.title, content = excluded.content, is_completed = excluded.is_completed, is_important = excluded.is_important, due_date = excluded.due_date, start_date = excluded.start_date, remind_time = excluded.remind_time, repeat_rule = excluded.repeat_rule, list_id = excluded.list_id, updated_at = excluded.updated_at",
+        params![
+            task.id,
+            task.title,
+            task.content,
+            task.is_completed as i32,
+            task.is_important as i32,
+            task.due_date,
+            task.start_date,
+            task.remind_time,
+            task.repeat_rule,
+            task.list_id,
+            task.created_at,
+            task.updated_at
+        ],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Imported lists are matched by name so repeated imports land in the same list
+fn find_or_create_list(conn: &Connection, name: &str, color: Option<String>) -> Result<String, String> {
+    let existing: Option<String> = conn
+        .query_row("SELECT id FROM lists WHERE name = ?1 ORDER BY order_index ASC LIMIT 1", [name], |row| row.get(0))
+        .ok();
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    let order: i32 = conn
+        .query_row("SELECT COALESCE(MAX(order_index), 0) FROM lists", [], |row| row.get(0))
+        .unwrap_or(0);
+    conn.execute(
+        "INSERT INTO lists (id, name, color, icon, is_default, created_at, order_index) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![id, name, color, None::<String>, 0, now, order + 1],
+    ).map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+fn cached_list_id(conn: &Connection, cache: &mut HashMap<String, String>, name: String, color: Option<String>) -> Result<String, String> {
+    if let Some(id) = cache.get(&name) {
+        return Ok(id.clone());
+    }
+    let id = find_or_create_list(conn, &name, color)?;
+    cache.insert(name, id.clone());
+    Ok(id)
+}
+
+pub fn default_list_id(conn: &Connection) -> Result<String, String> {
+    conn.query_row("SELECT id FROM lists WHERE is_default = 1 LIMIT 1", [], |row| row.get(0))
+        .map_err(|e| e.to_string())
+}
+
+// Stores lists produced by one of the importers, returning the created tasks
+fn store_imported_lists(conn: &Connection, lists: Vec<importers::ImportedList>) -> Result<Vec<Task>, String> {
+    // One transaction, so a row that fails doesn't leave half an import behind
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let default_id = default_list_id(&tx)?;
+    let mut list_ids: HashMap<String, String> = HashMap::new();
+    let mut imported_tasks = Vec::new();
+
+    for list in lists {
+        let list_id = match list.name {
+            Some(name) => cached_list_id(&tx, &mut list_ids, name, list.color)?,
+            None => default_id.clone(),
+        };
+
+        for item in list.tasks {
+            let mut task = item.task;
+            task.list_id = list_id.clone();
+            insert_task(&tx, &task)?;
+            for subtask in &item.subtasks {
+                insert_subtask(&tx, subtask)?;
+            }
+            replace_task_tags(&tx, &task.id, &item.tags)?;
+            imported_tasks.push(task);
+        }
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(imported_tasks)
+}
+
+#[tauri::command]
+async fn export_tasks_to_file(list_id: Option<String>, db: State<'_, DbConnection>) -> Result<bool, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    write_export_to_data_dir(&conn, list_id.as_deref())
+}
+
+fn write_export_to_data_dir(conn: &Connection, list_id: Option<&str>) -> Result<bool, String> {
+    let (tasks, lists) = load_export_data(conn, list_id)?;
+
+    let export_data = ExportData {
+        version: "1.0".to_string(),
+        export_date: Utc::now().to_rfc3339(),
+        tasks,
+        lists,
+    };
+
+    let json_data = serde_json::to_string_pretty(&export_data).map_err(|e| e.to_string())?;
+
+    let data_dir = get_data_dir();
+    let file_name = format!("itodo-export-{}.json", chrono::Local::now().format("%Y-%m-%d_%H%M%S"));
+    let file_path = data_dir.join(&file_name);
+
+    fs::write(&file_path, json_data).map_err(|e| t!("Failed to write file: {}", e))?;
+    info!("Exported tasks to {:?}", file_path);
+    Ok(true)
+}
+
+#[tauri::command]
+async fn import_tasks(json_data: String, db: State<'_, DbConnection>) -> Result<Vec<Task>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    import_json(&conn, &json_data)
+}
+
+fn import_json(conn: &Connection, json_data: &str) -> Result<Vec<Task>, String> {
+    let export_data: ExportData = serde_json::from_str(json_data)
+        .map_err(|e| t!("Failed to parse import data: {}", e))?;
+
+    let imported_tasks = import_export_data(conn, &export_data)?;
+
+    info!("Imported {} tasks", imported_tasks.len());
+    Ok(imported_tasks)
+}
+
+// Lists are kept by id; tasks always get new ids, so importing twice duplicates them
+pub fn import_export_data(conn: &Connection, export_data: &ExportData) -> Result<Vec<Task>, String> {
+    let mut imported_tasks = Vec::new();
+
+    for list in &export_data.lists {
+        let mut stmt = conn
+            .prepare("SELECT id FROM lists WHERE id = ?1")
+            .map_err(|e| e.to_string())?;
+        let exists: Result<String, _> = stmt.query_row([&list.id], |row| row.get(0));
+
+        if exists.is_err() {
+            conn.execute(
+                "INSERT INTO lists (id, name, color, icon, is_default, created_at, order_index) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                (&list.id, &list.name, &list.color, &list.icon, &list.is_default, &list.created_at, &list.order),
+            ).map_err(|e| e.to_string())?;
+        }
+    }
+
+    for task in &export_data.tasks {
+        let new_id = Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO tasks (id, title, content, is_completed, is_important, due_date, start_date, remind_time, repeat_rule, list_id, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            (
+                &new_id,
+                &task.title,
+                &task.content,
+                &task.is_completed,
+                &task.is_important,
+                &task.due_date,
                 &task.start_date,
                 &task.remind_time,
                 &task.repeat_rule,
@@ -727,85 +1403,1660 @@ async fn import_tasks(json_data: String, db: State<'_, DbConnection>) -> Result<
         });
     }
 
-    info!("Imported {} tasks", imported_tasks.len());
     Ok(imported_tasks)
 }
 
 #[tauri::command]
 async fn export_tasks_to_path(file_path: String, list_id: Option<String>, db: State<'_, DbConnection>) -> Result<bool, String> {
-    let (tasks, lists) = {
-        let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    write_json_export(&conn, &file_path, list_id.as_deref())
+}
 
-        let tasks = match list_id {
-            Some(lid) => {
-                let mut stmt = conn
-                    .prepare("SELECT id, title, content, is_completed, is_important, due_date, start_date, remind_time, repeat_rule, list_id, created_at, updated_at FROM tasks WHERE list_id = ?1 ORDER BY is_completed ASC, created_at DESC")
-                    .map_err(|e| e.to_string())?;
-                let result = stmt.query_map([&lid], row_to_task)
-                    .map_err(|e| e.to_string())?
-                    .collect::<Result<Vec<_>, _>>()
-                    .map_err(|e| e.to_string())?;
-                result
-            }
-            None => {
-                let mut stmt = conn
-                    .prepare("SELECT id, title, content, is_completed, is_important, due_date, start_date, remind_time, repeat_rule, list_id, created_at, updated_at FROM tasks ORDER BY is_completed ASC, created_at DESC")
-                    .map_err(|e| e.to_string())?;
-                let result = stmt.query_map([], row_to_task)
-                    .map_err(|e| e.to_string())?
-                    .collect::<Result<Vec<_>, _>>()
-                    .map_err(|e| e.to_string())?;
-                result
-            }
-        };
+fn write_json_export(conn: &Connection, file_path: &str, list_id: Option<&str>) -> Result<bool, String> {
+    let (tasks, lists) = load_export_data(conn, list_id)?;
 
-        let mut stmt = conn
-            .prepare("SELECT id, name, color, icon, is_default, created_at, order_index FROM lists")
-            .map_err(|e| e.to_string())?;
-        let lists = stmt.query_map([], |row| {
-            Ok(List {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                color: row.get(2)?,
-                icon: row.get(3)?,
-                is_default: row.get(4)?,
-                created_at: row.get(5)?,
-                order: row.get(6)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+    let export_data = ExportData {
+        version: "1.0".to_string(),
+        export_date: Utc::now().to_rfc3339(),
+        tasks,
+        lists,
+    };
+
+    let json_data = serde_json::to_string_pretty(&export_data).map_err(|e| e.to_string())?;
+
+    fs::write(file_path, json_data).map_err(|e| t!("Failed to write file: {}", e))?;
+    info!("Exported tasks to {:?}", file_path);
+    Ok(true)
+}
+
+// Same as `export_tasks_to_path`, with titles, notes and list names scrambled; the file imports
+// like any other export. Returns the seed used, a random one unless given; pass it again to get the
+// same tokens.
+#[tauri::command]
+async fn export_anonymized_to_path(
+    file_path: String,
+    list_id: Option<String>,
+    seed: Option<String>,
+    db: State<'_, DbConnection>,
+) -> Result<String, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    write_anonymized_export(&conn, &file_path, list_id.as_deref(), seed.as_deref())
+}
+
+fn write_anonymized_export(conn: &Connection, file_path: &str, list_id: Option<&str>, seed: Option<&str>) -> Result<String, String> {
+    let (tasks, lists) = load_export_data(conn, list_id)?;
+
+    let mut export_data = ExportData {
+        version: "1.0".to_string(),
+        export_date: Utc::now().to_rfc3339(),
+        tasks,
+        lists,
+    };
+    let seed = match seed.filter(|s| !s.is_empty()) {
+        Some(seed) => seed.to_string(),
+        None => anonymize::random_seed(),
+    };
+    anonymize::anonymize_export(&mut export_data, &seed);
+
+    let json_data = serde_json::to_string_pretty(&export_data).map_err(|e| e.to_string())?;
+
+    fs::write(file_path, json_data).map_err(|e| t!("Failed to write file: {}", e))?;
+    info!("Exported {} anonymized tasks to {:?}", export_data.tasks.len(), file_path);
+    Ok(seed)
+}
+
+// ============== Tauri Commands - iCalendar ==============
+
+#[tauri::command]
+async fn export_tasks_to_ics(file_path: String, list_id: Option<String>, db: State<'_, DbConnection>) -> Result<bool, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    write_ics_export(&conn, &file_path, list_id.as_deref())
+}
+
+fn write_ics_export(conn: &Connection, file_path: &str, list_id: Option<&str>) -> Result<bool, String> {
+    let (tasks, lists) = load_export_data(conn, list_id)?;
+
+    let lists: Vec<List> = match &list_id {
+        Some(lid) => lists.into_iter().filter(|l| &l.id == lid).collect(),
+        None => lists,
+    };
+    let ics_data = ical::export_calendars(&lists, &tasks);
+
+    fs::write(file_path, ics_data).map_err(|e| t!("Failed to write file: {}", e))?;
+    info!("Exported tasks as iCalendar to {:?}", file_path);
+    Ok(true)
+}
+
+#[tauri::command]
+async fn import_tasks_from_ics(ics_data: String, db: State<'_, DbConnection>) -> Result<Vec<Task>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    import_ics(&conn, &ics_data)
+}
+
+fn import_ics(conn: &Connection, ics_data: &str) -> Result<Vec<Task>, String> {
+    let calendars = ical::parse_calendars(ics_data)?;
+
+    let mut imported_tasks = Vec::new();
+
+    for calendar in calendars {
+        let list_id = match &calendar.name {
+            Some(name) => find_or_create_list(conn, name, calendar.color.clone())?,
+            None => default_list_id(conn)?,
+        };
+
+        for mut task in calendar.tasks {
+            task.list_id = list_id.clone();
+            insert_task(conn, &task)?;
+            imported_tasks.push(task);
+        }
+    }
+
+    info!("Imported {} tasks from iCalendar", imported_tasks.len());
+    Ok(imported_tasks)
+}
+
+// ============== Tauri Commands - CSV ==============
+
+#[tauri::command]
+async fn export_tasks_to_csv(file_path: String, list_id: Option<String>, delimiter: Option<String>, db: State<'_, DbConnection>) -> Result<bool, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    write_csv_export(&conn, &file_path, list_id.as_deref(), delimiter.as_deref())
+}
+
+fn write_csv_export(conn: &Connection, file_path: &str, list_id: Option<&str>, delimiter: Option<&str>) -> Result<bool, String> {
+    let delimiter = csv_io::parse_delimiter(delimiter)?;
+    let (tasks, lists) = load_export_data(conn, list_id)?;
+
+    let csv_data = csv_io::export_tasks(&tasks, &lists, delimiter)?;
+
+    fs::write(file_path, csv_data).map_err(|e| t!("Failed to write file: {}", e))?;
+    info!("Exported tasks as CSV to {:?}", file_path);
+    Ok(true)
+}
+
+#[tauri::command]
+async fn import_tasks_from_csv(csv_data: String, options: Option<csv_io::CsvImportOptions>, db: State<'_, DbConnection>) -> Result<csv_io::CsvImportReport, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    import_csv(&conn, &csv_data, options)
+}
+
+fn import_csv(conn: &Connection, csv_data: &str, options: Option<csv_io::CsvImportOptions>) -> Result<csv_io::CsvImportReport, String> {
+    let options = options.unwrap_or_default();
+    let (rows, errors) = csv_io::parse_tasks(csv_data, &options)?;
+
+    let fallback_list_id = match &options.list_id {
+        Some(id) => load_list(conn, id).map_err(|_| t!("List not found: {}", id))?.id,
+        None => default_list_id(conn)?,
+    };
+
+    // All rows or none, so a failure halfway leaves nothing to clean up
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut list_ids: HashMap<String, String> = HashMap::new();
+    let mut imported = Vec::new();
+    for row in rows {
+        let mut task = row.task;
+        task.list_id = match row.list_name {
+            Some(name) => cached_list_id(&tx, &mut list_ids, name, None)?,
+            None => fallback_list_id.clone(),
+        };
+        insert_task(&tx, &task)?;
+        imported.push(task);
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    info!("Imported {} tasks from CSV ({} rows skipped)", imported.len(), errors.len());
+    Ok(csv_io::CsvImportReport { imported, errors })
+}
+
+// ============== Tauri Commands - todo.txt ==============
+
+#[tauri::command]
+async fn export_tasks_to_todotxt(file_path: String, list_id: Option<String>, db: State<'_, DbConnection>) -> Result<bool, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    write_todotxt_export(&conn, &file_path, list_id.as_deref())
+}
+
+fn write_todotxt_export(conn: &Connection, file_path: &str, list_id: Option<&str>) -> Result<bool, String> {
+    let (tasks, lists) = load_export_data(conn, list_id)?;
+    let tags = load_task_tags(conn)?;
+
+    let todo_data = todotxt::export_tasks(&tasks, &lists, &tags);
+
+    fs::write(file_path, todo_data).map_err(|e| t!("Failed to write file: {}", e))?;
+    info!("Exported tasks as todo.txt to {:?}", file_path);
+    Ok(true)
+}
+
+#[tauri::command]
+async fn import_tasks_from_todotxt(todo_data: String, db: State<'_, DbConnection>) -> Result<Vec<Task>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    import_todotxt(&conn, &todo_data)
+}
+
+fn import_todotxt(conn: &Connection, todo_data: &str) -> Result<Vec<Task>, String> {
+    let items = todotxt::parse_tasks(todo_data);
+
+    let default_id = default_list_id(conn)?;
+
+    let mut list_ids: HashMap<String, String> = HashMap::new();
+    let mut imported_tasks = Vec::new();
+    for item in items {
+        let mut task = item.task;
+        task.list_id = match item.list_name {
+            Some(name) => cached_list_id(conn, &mut list_ids, name, None)?,
+            None => default_id.clone(),
+        };
+        insert_task(conn, &task)?;
+        replace_task_tags(conn, &task.id, &item.tags)?;
+        imported_tasks.push(task);
+    }
+
+    info!("Imported {} tasks from todo.txt", imported_tasks.len());
+    Ok(imported_tasks)
+}
+
+// ============== Tauri Commands - Markdown ==============
+
+#[tauri::command]
+async fn export_tasks_to_markdown(file_path: String, list_id: Option<String>, db: State<'_, DbConnection>) -> Result<bool, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    write_markdown_export(&conn, &file_path, list_id.as_deref())
+}
+
+fn write_markdown_export(conn: &Connection, file_path: &str, list_id: Option<&str>) -> Result<bool, String> {
+    let (tasks, lists) = load_export_data(conn, list_id)?;
+    let subtasks = load_subtasks(conn)?;
+
+    let lists: Vec<List> = match &list_id {
+        Some(lid) => lists.into_iter().filter(|l| &l.id == lid).collect(),
+        None => lists,
+    };
+    let markdown = markdown::export_lists(&lists, &tasks, &subtasks);
+
+    fs::write(file_path, markdown).map_err(|e| t!("Failed to write file: {}", e))?;
+    info!("Exported tasks as Markdown to {:?}", file_path);
+    Ok(true)
+}
+
+#[tauri::command]
+async fn import_tasks_from_markdown(markdown_data: String, list_id: Option<String>, db: State<'_, DbConnection>) -> Result<Vec<Task>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    import_markdown(&conn, &markdown_data, list_id.as_deref())
+}
+
+fn import_markdown(conn: &Connection, markdown_data: &str, list_id: Option<&str>) -> Result<Vec<Task>, String> {
+    let sections = markdown::parse_checklists(markdown_data);
+
+    // Items before the first heading go to the requested list
+    let fallback_list_id = match list_id {
+        Some(id) => id.to_string(),
+        None => default_list_id(conn)?,
+    };
+
+    let mut list_ids: HashMap<String, String> = HashMap::new();
+    let mut imported_tasks = Vec::new();
+    for section in sections {
+        let section_list_id = match section.name {
+            Some(name) => cached_list_id(conn, &mut list_ids, name, None)?,
+            None => fallback_list_id.clone(),
+        };
+
+        for item in section.tasks {
+            let mut task = item.task;
+            task.list_id = section_list_id.clone();
+            insert_task(conn, &task)?;
+            for subtask in &item.subtasks {
+                insert_subtask(conn, subtask)?;
+            }
+            imported_tasks.push(task);
+        }
+    }
+
+    info!("Imported {} tasks from Markdown", imported_tasks.len());
+    Ok(imported_tasks)
+}
+
+// ============== Tauri Commands - Org-mode ==============
+
+#[tauri::command]
+async fn export_tasks_to_org(file_path: String, list_id: Option<String>, db: State<'_, DbConnection>) -> Result<bool, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    write_org_export(&conn, &file_path, list_id.as_deref())
+}
+
+fn write_org_export(conn: &Connection, file_path: &str, list_id: Option<&str>) -> Result<bool, String> {
+    let (tasks, lists) = load_export_data(conn, list_id)?;
+    let subtasks = load_subtasks(conn)?;
+    let tags = load_task_tags(conn)?;
+
+    let lists: Vec<List> = match &list_id {
+        Some(lid) => lists.into_iter().filter(|l| &l.id == lid).collect(),
+        None => lists,
+    };
+    let org_data = org::export_lists(&lists, &tasks, &subtasks, &tags);
+
+    fs::write(file_path, org_data).map_err(|e| t!("Failed to write file: {}", e))?;
+    info!("Exported tasks as Org to {:?}", file_path);
+    Ok(true)
+}
+
+#[tauri::command]
+async fn import_tasks_from_org(org_data: String, list_id: Option<String>, db: State<'_, DbConnection>) -> Result<Vec<Task>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    import_org(&conn, &org_data, list_id.as_deref())
+}
+
+fn import_org(conn: &Connection, org_data: &str, list_id: Option<&str>) -> Result<Vec<Task>, String> {
+    let sections = org::parse_outline(org_data);
+
+    // Tasks outside a list heading go to the requested list
+    let fallback_list_id = match list_id {
+        Some(id) => id.to_string(),
+        None => default_list_id(conn)?,
+    };
+
+    let mut list_ids: HashMap<String, String> = HashMap::new();
+    let mut imported_tasks = Vec::new();
+    for section in sections {
+        let section_list_id = match section.name {
+            Some(name) => cached_list_id(conn, &mut list_ids, name, None)?,
+            None => fallback_list_id.clone(),
+        };
+
+        for item in section.tasks {
+            let mut task = item.task;
+            task.list_id = section_list_id.clone();
+            insert_task(conn, &task)?;
+            for subtask in &item.subtasks {
+                insert_subtask(conn, subtask)?;
+            }
+            replace_task_tags(conn, &task.id, &item.tags)?;
+            imported_tasks.push(task);
+        }
+    }
+
+    info!("Imported {} tasks from Org", imported_tasks.len());
+    Ok(imported_tasks)
+}
+
+// ============== Tauri Commands - Taskwarrior ==============
+
+#[tauri::command]
+async fn export_tasks_to_taskwarrior(file_path: String, list_id: Option<String>, db: State<'_, DbConnection>) -> Result<bool, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    write_taskwarrior_export(&conn, &file_path, list_id.as_deref())
+}
+
+fn write_taskwarrior_export(conn: &Connection, file_path: &str, list_id: Option<&str>) -> Result<bool, String> {
+    let (tasks, lists) = load_export_data(conn, list_id)?;
+    let tags = load_task_tags(conn)?;
+
+    let json_data = taskwarrior::export_tasks(&tasks, &lists, &tags)?;
+
+    fs::write(file_path, json_data).map_err(|e| t!("Failed to write file: {}", e))?;
+    info!("Exported tasks as Taskwarrior JSON to {:?}", file_path);
+    Ok(true)
+}
+
+// Tasks keep their Taskwarrior UUID as id, so importing the same export again updates them in place
+#[tauri::command]
+async fn import_tasks_from_taskwarrior(json_data: String, db: State<'_, DbConnection>) -> Result<Vec<Task>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    import_taskwarrior(&conn, &json_data)
+}
+
+fn import_taskwarrior(conn: &Connection, json_data: &str) -> Result<Vec<Task>, String> {
+    let items = taskwarrior::parse_tasks(json_data)?;
+
+    let default_id = default_list_id(conn)?;
+
+    let mut list_ids: HashMap<String, String> = HashMap::new();
+    let mut imported_tasks = Vec::new();
+    let mut deleted = 0;
+    for item in items {
+        let mut task = item.task;
+        if item.deleted {
+            conn.execute("DELETE FROM subtasks WHERE task_id = ?1", [&task.id])
+                .map_err(|e| e.to_string())?;
+            conn.execute("DELETE FROM task_tags WHERE task_id = ?1", [&task.id])
+                .map_err(|e| e.to_string())?;
+            deleted += conn
+                .execute("DELETE FROM tasks WHERE id = ?1", [&task.id])
+                .map_err(|e| e.to_string())?;
+            continue;
+        }
+
+        task.list_id = match item.list_name {
+            Some(name) => cached_list_id(conn, &mut list_ids, name, None)?,
+            None => default_id.clone(),
+        };
+        upsert_task(conn, &task)?;
+        replace_task_tags(conn, &task.id, &item.tags)?;
+        imported_tasks.push(task);
+    }
+
+    info!("Imported {} tasks from Taskwarrior, removed {} deleted ones", imported_tasks.len(), deleted);
+    Ok(imported_tasks)
+}
+
+// ============== Tauri Commands - HTML Report ==============
+
+// Renders the given lists (all of them when None) to one self-contained HTML page
+#[tauri::command]
+async fn export_tasks_to_html(file_path: String, list_ids: Option<Vec<String>>, title: Option<String>, db: State<'_, DbConnection>) -> Result<bool, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    write_html_report(&conn, &file_path, list_ids.as_deref(), title.as_deref())
+}
+
+fn write_html_report(conn: &Connection, file_path: &str, list_ids: Option<&[String]>, title: Option<&str>) -> Result<bool, String> {
+    let (tasks, lists) = load_export_data(conn, None)?;
+    let subtasks = load_subtasks(conn)?;
+
+    let lists: Vec<List> = match list_ids {
+        Some(ids) => lists.into_iter().filter(|l| ids.contains(&l.id)).collect(),
+        None => lists,
+    };
+    let title = title
+        .filter(|t| !t.trim().is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| match lists.as_slice() {
+            [list] => list.name.clone(),
+            _ => "iToDo".to_string(),
+        });
+    let html = html_report::render_report(&title, &lists, &tasks, &subtasks);
+
+    fs::write(file_path, html).map_err(|e| t!("Failed to write file: {}", e))?;
+    info!("Exported {} lists as HTML report to {:?}", lists.len(), file_path);
+    Ok(true)
+}
+
+// ============== Tauri Commands - Bundle ==============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleImportReport {
+    pub manifest: bundle::BundleManifest,
+    pub lists: usize,
+    pub tasks: usize,
+    pub subtasks: usize,
+    pub attachments: usize,
+    // As found in the bundle; applied to the stored settings when valid
+    pub settings: Option<serde_json::Value>,
+}
+
+// Writes a complete .itodo bundle, including the app settings
+fn load_bundle_data(conn: &Connection) -> Result<bundle::BundleData, String> {
+    let (tasks, lists) = load_export_data(conn, None)?;
+    let mut subtasks: Vec<Subtask> = load_subtasks(conn)?.into_values().flatten().collect();
+    subtasks.sort_by(|a, b| a.task_id.cmp(&b.task_id).then(a.created_at.cmp(&b.created_at)));
+    Ok(bundle::BundleData {
+        version: bundle::DATA_VERSION,
+        lists,
+        tasks,
+        subtasks,
+        tags: load_task_tags(conn)?,
+    })
+}
+
+#[tauri::command]
+async fn export_bundle(file_path: String, db: State<'_, DbConnection>) -> Result<bundle::BundleManifest, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    write_bundle_export(&conn, &file_path)
+}
+
+fn write_bundle_export(conn: &Connection, file_path: &str) -> Result<bundle::BundleManifest, String> {
+    let data = load_bundle_data(conn)?;
+
+    let settings = serde_json::to_value(load_settings()).map_err(|e| e.to_string())?;
+    let manifest = bundle::write_bundle(Path::new(&file_path), &data, Some(&settings), &get_attachments_dir())?;
+    info!("Exported bundle with {} tasks and {} attachments to {:?}", manifest.tasks, manifest.attachments, file_path);
+    Ok(manifest)
+}
+
+// Restores a bundle into the current database. Ids are kept, so records that already exist
+// are updated; the bundle's default list is merged into the local default list.
+#[tauri::command]
+async fn import_bundle(file_path: String, app: tauri::AppHandle, db: State<'_, DbConnection>) -> Result<BundleImportReport, String> {
+    let report = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        import_bundle_file(&conn, &file_path)?
+    };
+    if report.settings.is_some() {
+        let _ = app.emit("settings-changed", load_settings());
+    }
+    Ok(report)
+}
+
+fn import_bundle_file(conn: &Connection, file_path: &str) -> Result<BundleImportReport, String> {
+    let contents = bundle::read_bundle(Path::new(file_path))?;
+    let data = contents.data;
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let local_default = default_list_id(&tx)?;
+
+    let mut list_ids: HashMap<String, String> = HashMap::new();
+    for list in &data.lists {
+        if list.is_default {
+            list_ids.insert(list.id.clone(), local_default.clone());
+            continue;
+        }
+        tx.execute(
+            "INSERT INTO lists (id, name, color, icon, is_default, created_at, order_index) VALUES (?1, ?2, ?3, ?4, 0, ?5, ?6)
+             ON CONFLICT(id) DO UPDATE SET name = excluded.name, color = excluded.color, icon = excluded.icon, order_index = excluded.order_index",
+            params![list.id, list.name, list.color, list.icon, list.created_at, list.order],
+        ).map_err(|e| e.to_string())?;
+        list_ids.insert(list.id.clone(), list.id.clone());
+    }
+
+    for task in &data.tasks {
+        let mut task = task.clone();
+        task.list_id = list_ids.get(&task.list_id).cloned().unwrap_or_else(|| local_default.clone());
+        upsert_task(&tx, &task)?;
+    }
+
+    let task_ids: HashSet<&str> = data.tasks.iter().map(|t| t.id.as_str()).collect();
+    let subtasks: Vec<&Subtask> = data.subtasks.iter().filter(|s| task_ids.contains(s.task_id.as_str())).collect();
+    for subtask in &subtasks {
+        tx.execute(
+            "INSERT INTO subtasks (id, task_id, title, is_completed, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(id) DO UPDATE SET task_id = excluded.task_id, title = excluded.title, is_completed = excluded.is_completed, updated_at = excluded.updated_at",
+            params![
+                subtask.id,
+                subtask.task_id,
+                subtask.title,
+                subtask.is_completed as i32,
+                subtask.created_at,
+                subtask.updated_at
+            ],
+        ).map_err(|e| e.to_string())?;
+    }
+
+    for (task_id, tags) in data.tags.iter().filter(|(id, _)| task_ids.contains(id.as_str())) {
+        replace_task_tags(&tx, task_id, tags)?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    // Only once the data is in, so a failed import leaves no files behind
+    let attachments = bundle::write_attachments(&get_attachments_dir(), &contents.attachments)
+        .map_err(|e| t!("The tasks were imported, but not all attachments: {}", e))?;
+
+    if let Some(bundled) = &contents.settings {
+        match load_settings().merged(bundled) {
+            Ok(imported) => save_settings(conn, &imported)?,
+            Err(e) => error!("Settings in the bundle were not applied: {}", e),
+        }
+    }
+
+    info!("Imported bundle from {:?}: {} tasks, {} attachments", file_path, data.tasks.len(), attachments);
+    Ok(BundleImportReport {
+        manifest: contents.manifest,
+        lists: list_ids.len(),
+        tasks: data.tasks.len(),
+        subtasks: subtasks.len(),
+        attachments,
+        settings: contents.settings,
+    })
+}
+
+// ============== Tauri Commands - Importers ==============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImporterInfo {
+    pub id: String,
+    pub name: String,
+    pub extensions: Vec<String>,
+}
+
+#[tauri::command]
+fn get_importers() -> Vec<ImporterInfo> {
+    importers::all()
+        .iter()
+        .map(|importer| ImporterInfo {
+            id: importer.id().to_string(),
+            name: importer.name().to_string(),
+            extensions: importer.extensions().iter().map(|e| e.to_string()).collect(),
+        })
+        .collect()
+}
+
+#[tauri::command]
+async fn import_from_source(source: String, data: String, db: State<'_, DbConnection>) -> Result<Vec<Task>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    import_with_importer(&conn, &source, &data)
+}
+
+fn import_with_importer(conn: &Connection, source: &str, data: &str) -> Result<Vec<Task>, String> {
+    let importer = importers::find(source).ok_or_else(|| t!("Unknown import source: {}", source))?;
+    let lists = importer.parse(data)?;
+
+    let imported_tasks = store_imported_lists(conn, lists)?;
+
+    info!("Imported {} tasks from {}", imported_tasks.len(), importer.name());
+    Ok(imported_tasks)
+}
+
+// ============== Tauri Commands - Settings ==============
+
+#[tauri::command]
+fn get_settings() -> settings::Settings {
+    load_settings()
+}
+
+// Takes a partial update, e.g. `{"theme": "dark"}`, and returns the full settings
+#[tauri::command]
+fn update_settings(patch: serde_json::Value, app: tauri::AppHandle, db: State<DbConnection>) -> Result<settings::Settings, String> {
+    let updated = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        patch_settings(&conn, &patch)?
+    };
+    let _ = app.emit("settings-changed", &updated);
+    Ok(updated)
+}
+
+fn patch_settings(conn: &Connection, patch: &serde_json::Value) -> Result<settings::Settings, String> {
+    let updated = load_settings().merged(patch)?;
+    save_settings(conn, &updated)?;
+    info!("Settings updated");
+    Ok(updated)
+}
+
+// ============== Tauri Commands - Backups ==============
+
+// Takes a backup when the newest one is older than the configured interval, then prunes
+fn run_scheduled_backup(db_path: &Path) {
+    let config = load_settings().backup;
+    // A file opened from outside the data folder has no backup folder of its own
+    let Ok(backup_dir) = backup_dir_for(db_path) else {
+        return;
+    };
+    if !backup::is_backup_due(&backup_dir, &config) {
+        return;
+    }
+
+    let key = encryption::key_for(db_path);
+    match backup::create_backup(db_path, &backup_dir, key.as_ref()) {
+        Ok(created) => {
+            // Never let a bad copy push good backups out of the rotation
+            if let Err(e) = backup::verify_backup(Path::new(&created.path), key.as_ref()) {
+                error!("Scheduled backup {} is unusable, discarding it: {}", created.id, e);
+                let _ = fs::remove_file(&created.path);
+                return;
+            }
+            info!("Created scheduled backup {}", created.id);
+            match backup::prune_backups(&backup_dir, &config) {
+                Ok(removed) if !removed.is_empty() => info!("Pruned {} old backups", removed.len()),
+                Ok(_) => {}
+                Err(e) => error!("Failed to prune backups: {}", e),
+            }
+        }
+        Err(e) => error!("Scheduled backup failed: {}", e),
+    }
+}
+
+// Follows whichever database is open, and stays idle while the app is in recovery mode
+fn start_backup_scheduler(app: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        let db_path = {
+            let db = app.state::<DbConnection>();
+            let conn = db.0.lock();
+            conn.ok().and_then(|conn| live_db_path(&conn))
+        };
+        if let Some(db_path) = db_path {
+            run_scheduled_backup(&db_path);
+        }
+        std::thread::sleep(std::time::Duration::from_secs(15 * 60));
+    });
+}
+
+#[tauri::command]
+fn get_backup_config() -> backup::BackupConfig {
+    load_settings().backup
+}
+
+#[tauri::command]
+fn update_backup_config(config: backup::BackupConfig, app: tauri::AppHandle, db: State<DbConnection>) -> Result<backup::BackupConfig, String> {
+    let config = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        save_backup_config(&conn, config)?
+    };
+    let _ = app.emit("settings-changed", load_settings());
+    Ok(config)
+}
+
+fn save_backup_config(conn: &Connection, config: backup::BackupConfig) -> Result<backup::BackupConfig, String> {
+    config.validate()?;
+    let mut settings = load_settings();
+    settings.backup = config.clone();
+    save_settings(conn, &settings)?;
+    Ok(config)
+}
+
+#[tauri::command]
+async fn list_backups(db: State<'_, DbConnection>) -> Result<Vec<backup::BackupInfo>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    list_current_backups(&conn)
+}
+
+fn list_current_backups(conn: &Connection) -> Result<Vec<backup::BackupInfo>, String> {
+    backup::list_backups(&get_backup_dir(conn)?)
+}
+
+#[tauri::command]
+async fn create_backup(db: State<'_, DbConnection>) -> Result<backup::BackupInfo, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    backup_now(&conn)
+}
+
+fn backup_now(conn: &Connection) -> Result<backup::BackupInfo, String> {
+    let db_path = {
+        live_db_path(conn).ok_or_else(|| t!("No database is open"))?
+    };
+    let backup_dir = get_backup_dir(conn)?;
+    let created = backup::create_backup(&db_path, &backup_dir, encryption::key_for(&db_path).as_ref())?;
+    backup::prune_backups(&backup_dir, &load_settings().backup)?;
+    info!("Created backup {}", created.id);
+    Ok(created)
+}
+
+// Replaces the live database with a backup. The current state is backed up first and returned,
+// so a restore can itself be undone.
+#[tauri::command]
+async fn restore_backup(id: String, db: State<'_, DbConnection>) -> Result<backup::BackupInfo, String> {
+    // Holding the lock keeps every other command out until the swap is done
+    let mut conn = db.0.lock().map_err(|e| e.to_string())?;
+    restore_from_backup(&mut conn, &id)
+}
+
+fn restore_from_backup(conn: &mut Connection, id: &str) -> Result<backup::BackupInfo, String> {
+    let db_path = live_db_path(conn).ok_or_else(|| t!("No database is open"))?;
+    let backup_dir = backup_dir_for(&db_path)?;
+    let source = backup::find_backup(&backup_dir, id)?;
+
+    let key = encryption::key_for(&db_path);
+    backup::verify_backup(&source, key.as_ref())?;
+    let previous = backup::create_backup(&db_path, &backup_dir, key.as_ref())?;
+    let source_conn = encryption::open_file(&source, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY, key.as_ref())?;
+    rusqlite::backup::Backup::new(&source_conn, conn)
+        .and_then(|b| b.run_to_completion(256, std::time::Duration::ZERO, None))
+        .map_err(|e| t!("Failed to restore backup: {}", e))?;
+    // Older backups may predate tables added since
+    init_database(conn).map_err(|e| e.to_string())?;
+
+    info!("Restored backup {}, previous state saved as {}", id, previous.id);
+    Ok(previous)
+}
 
-        (tasks, lists)
+// ============== Tauri Commands - Maintenance ==============
+
+#[tauri::command]
+async fn check_database(db: State<'_, DbConnection>) -> Result<integrity::IntegrityReport, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    run_integrity_check(&conn)
+}
+
+fn run_integrity_check(conn: &Connection) -> Result<integrity::IntegrityReport, String> {
+    let report = integrity::check(conn)?;
+    info!("Database check finished, ok: {}", report.is_ok);
+    Ok(report)
+}
+
+#[tauri::command]
+async fn repair_database(db: State<'_, DbConnection>) -> Result<integrity::RepairReport, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    run_repair(&conn)
+}
+
+fn run_repair(conn: &Connection) -> Result<integrity::RepairReport, String> {
+    // A damaged database can fail to back up; the repair itself is still worth trying
+    let db_path = live_db_path(conn).ok_or_else(|| t!("No database is open"))?;
+    let created = backup_dir_for(&db_path).and_then(|dir| backup::create_backup(&db_path, &dir, encryption::key_for(&db_path).as_ref()));
+    let backup_id = match created {
+        Ok(created) => Some(created.id),
+        Err(e) => {
+            error!("Backup before repair failed: {}", e);
+            None
+        }
     };
 
-    let export_data = ExportData {
-        version: "1.0".to_string(),
-        export_date: Utc::now().to_rfc3339(),
-        tasks,
-        lists,
+    let missing_default = integrity::check(conn)?.default_lists == 0;
+    init_database(conn).map_err(|e| e.to_string())?;
+    let mut report = integrity::repair(conn)?;
+    report.fixed_default_list |= missing_default;
+    report.backup_id = backup_id;
+
+    info!(
+        "Database repaired: {} tasks re-homed, {} subtasks and {} tags removed, {} dates and {} repeat rules cleared",
+        report.rehomed_tasks, report.removed_subtasks, report.removed_tags, report.cleared_dates, report.cleared_repeat_rules
+    );
+    Ok(report)
+}
+
+// Everything a bug report needs in one zip. Also works in recovery mode, where the schema and
+// integrity parts record why they failed; `include_data` adds the data with the text hashed.
+#[tauri::command]
+async fn create_diagnostics_bundle(
+    file_path: String,
+    include_data: bool,
+    app: tauri::AppHandle,
+    db: State<'_, DbConnection>,
+    recovery: State<'_, RecoveryState>,
+) -> Result<diagnostics::Diagnostics, String> {
+    let startup_error = recovery.0.lock().map_err(|e| e.to_string())?.as_ref().map(|f| f.error.clone());
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    write_diagnostics_bundle(&conn, &file_path, include_data, get_about_info(app), startup_error)
+}
+
+fn write_diagnostics_bundle(
+    conn: &Connection,
+    file_path: &str,
+    include_data: bool,
+    about: AboutInfo,
+    startup_error: Option<String>,
+) -> Result<diagnostics::Diagnostics, String> {
+    let schema = diagnostics::read_schema(conn)
+        .map_err(|e| error!("Failed to read the schema for diagnostics: {}", e))
+        .ok();
+    let data = if include_data {
+        let mut data = load_bundle_data(conn)?;
+        // The seed isn't kept, so the text can't be read back from the bundle
+        anonymize::anonymize_bundle_data(&mut data, &anonymize::random_seed());
+        Some(data)
+    } else {
+        None
+    };
+    let integrity = integrity::check(conn);
+    let db_path = live_db_path(conn);
+
+    let log_files = logging::log_files(&get_log_dir());
+    let report = diagnostics::Diagnostics {
+        created_at: Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        about,
+        workspace: workspaces::WorkspaceRegistry::load(&get_data_dir()).last_used,
+        encrypted: db_path.as_deref().is_some_and(encryption::is_encrypted),
+        startup_error,
+        schema,
+        settings: load_settings(),
+        log_files: log_files
+            .iter()
+            .filter_map(|f| f.file_name().map(|n| n.to_string_lossy().to_string()))
+            .collect(),
+        includes_data: data.is_some(),
     };
 
-    let json_data = serde_json::to_string_pretty(&export_data).map_err(|e| e.to_string())?;
+    diagnostics::write_diagnostics(Path::new(file_path), &report, &integrity, &log_files, data.as_ref())?;
+    info!("Created diagnostics bundle at {:?} ({} log files, data: {})", file_path, log_files.len(), include_data);
+    Ok(report)
+}
 
-    fs::write(&file_path, json_data).map_err(|e| format!("Failed to write file: {}", e))?;
-    info!("Exported tasks to {:?}", file_path);
+// ============== Tauri Commands - Recovery ==============
+
+pub struct StartupFailure {
+    pub db_path: PathBuf,
+    pub error: String,
+}
+
+// Set when the database could not be opened at startup; the window then shows the recovery screen
+pub struct RecoveryState(pub Mutex<Option<StartupFailure>>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupStatus {
+    pub recovery: bool,
+    // The database is encrypted and waits for its passphrase, see `unlock_database`
+    pub locked: bool,
+    pub error: Option<String>,
+    pub db_path: String,
+    pub latest_backup: Option<backup::BackupInfo>,
+}
+
+fn startup_status(recovery: &RecoveryState, conn: &Connection) -> Result<StartupStatus, String> {
+    let failure = recovery.0.lock().map_err(|e| e.to_string())?;
+    let db_path = match failure.as_ref() {
+        Some(f) => Some(f.db_path.clone()),
+        None => live_db_path(conn),
+    };
+    let locked = failure
+        .as_ref()
+        .is_some_and(|f| encryption::is_encrypted(&f.db_path) && encryption::key_for(&f.db_path).is_none());
+    let latest_backup = match db_path.as_deref().map(backup_dir_for) {
+        Some(Ok(backup_dir)) => backup::list_backups(&backup_dir)?.into_iter().next(),
+        _ => None,
+    };
+    Ok(StartupStatus {
+        recovery: failure.is_some(),
+        locked,
+        error: failure.as_ref().map(|f| f.error.clone()),
+        db_path: db_path.unwrap_or_default().to_string_lossy().to_string(),
+        latest_backup,
+    })
+}
+
+// Swaps the opened database in and leaves recovery mode
+fn finish_recovery(new_conn: Connection, recovery: &RecoveryState, db: &DbConnection) -> Result<StartupStatus, String> {
+    let mut conn = db.0.lock().map_err(|e| e.to_string())?;
+    *conn = new_conn;
+    *recovery.0.lock().map_err(|e| e.to_string())? = None;
+    info!("Database opened from {:?}", live_db_path(&conn));
+    startup_status(recovery, &conn)
+}
+
+// Moves a database that failed to open (and its journal files) aside as "<name>.broken-<time>"
+fn preserve_broken_file(path: &Path) -> Result<Option<PathBuf>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let preserved = path.with_file_name(format!("{}.broken-{}", file_name, stamp));
+
+    fs::rename(path, &preserved).map_err(|e| t!("Failed to move the damaged database aside: {}", e))?;
+    for suffix in ["-wal", "-shm", "-journal"] {
+        let sibling = path.with_file_name(format!("{}{}", file_name, suffix));
+        if sibling.exists() {
+            let target = preserved.with_file_name(format!("{}.broken-{}{}", file_name, stamp, suffix));
+            fs::rename(&sibling, &target).map_err(|e| t!("Failed to move {} aside: {}", sibling.display(), e))?;
+        }
+    }
+    // Copied rather than moved: a restored backup needs the same key
+    if encryption::is_encrypted(path) {
+        fs::copy(encryption::key_file_path(path), encryption::key_file_path(&preserved))
+            .map_err(|e| t!("Failed to keep the key parameters: {}", e))?;
+    }
+    info!("Preserved damaged database as {:?}", preserved);
+    Ok(Some(preserved))
+}
+
+// The recovery actions only apply to a database that failed to open, never to the live one
+fn failed_db_path(recovery: &RecoveryState) -> Result<PathBuf, String> {
+    let failure = recovery.0.lock().map_err(|e| e.to_string())?;
+    failure
+        .as_ref()
+        .map(|f| f.db_path.clone())
+        .ok_or_else(|| t!("The app is not in recovery mode"))
+}
+
+#[tauri::command]
+fn get_startup_status(recovery: State<RecoveryState>, db: State<DbConnection>) -> Result<StartupStatus, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    startup_status(&recovery, &conn)
+}
+
+#[tauri::command]
+async fn retry_open_database(recovery: State<'_, RecoveryState>, db: State<'_, DbConnection>) -> Result<StartupStatus, String> {
+    let conn = open_database(&failed_db_path(&recovery)?)?;
+    finish_recovery(conn, &recovery, &db)
+}
+
+#[tauri::command]
+async fn unlock_database(passphrase: String, recovery: State<'_, RecoveryState>, db: State<'_, DbConnection>) -> Result<StartupStatus, String> {
+    let db_path = failed_db_path(&recovery)?;
+    unlock_with_passphrase(&db_path, &passphrase)?;
+    let conn = open_database(&db_path)?;
+    finish_recovery(conn, &recovery, &db)
+}
+
+// Replaces the damaged database with the newest backup that passes verification
+#[tauri::command]
+async fn restore_latest_backup(recovery: State<'_, RecoveryState>, db: State<'_, DbConnection>) -> Result<StartupStatus, String> {
+    let db_path = failed_db_path(&recovery)?;
+    let key = encryption::key_for(&db_path);
+    let latest = backup::list_backups(&backup_dir_for(&db_path)?)?
+        .into_iter()
+        .find(|b| backup::verify_backup(Path::new(&b.path), key.as_ref()).is_ok())
+        .ok_or_else(|| t!("No usable backup was found"))?;
+
+    preserve_broken_file(&db_path)?;
+    fs::copy(&latest.path, &db_path).map_err(|e| t!("Failed to copy backup: {}", e))?;
+    let conn = open_database(&db_path)?;
+    info!("Restored backup {} after a failed startup", latest.id);
+    finish_recovery(conn, &recovery, &db)
+}
+
+#[tauri::command]
+async fn open_database_file(file_path: String, recovery: State<'_, RecoveryState>, db: State<'_, DbConnection>) -> Result<StartupStatus, String> {
+    failed_db_path(&recovery)?;
+    let path = Path::new(&file_path);
+    check_itodo_database(path)?;
+    let conn = open_database(path)?;
+    finish_recovery(conn, &recovery, &db)
+}
+
+// Looks for our tables without writing, so a file picked by mistake isn't given our schema
+fn check_itodo_database(path: &Path) -> Result<(), String> {
+    let not_ours = |reason: String| t!("Not an iToDo database: {}", reason);
+    if !path.is_file() {
+        return Err(not_ours(path.display().to_string()));
+    }
+    let key = encryption::key_for(path);
+    if encryption::is_encrypted(path) && key.is_none() {
+        return Err(t!("The database is encrypted. Enter the passphrase to unlock it."));
+    }
+    let conn = encryption::open_file(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY, key.as_ref())?;
+    let tables: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name IN ('lists', 'tasks')",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| not_ours(e.to_string()))?;
+    if tables < 2 {
+        return Err(not_ours(path.display().to_string()));
+    }
+    Ok(())
+}
+
+// Starts over with an empty database at the usual location; the damaged file is kept next to it
+#[tauri::command]
+async fn start_fresh_database(recovery: State<'_, RecoveryState>, db: State<'_, DbConnection>) -> Result<StartupStatus, String> {
+    let db_path = failed_db_path(&recovery)?;
+    preserve_broken_file(&db_path)?;
+    // The new database starts out unencrypted
+    let _ = fs::remove_file(encryption::key_file_path(&db_path));
+    let conn = open_database(&db_path)?;
+    finish_recovery(conn, &recovery, &db)
+}
+
+// ============== Tauri Commands - Encryption ==============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionStatus {
+    // Whether this build includes SQLCipher
+    pub supported: bool,
+    pub encrypted: bool,
+}
+
+// Derives the key for an encrypted database and keeps it for this session if it opens the file
+fn unlock_with_passphrase(db_path: &Path, passphrase: &str) -> Result<encryption::DbKey, String> {
+    let params = encryption::load_params(db_path)?;
+    let key = encryption::derive_key(passphrase, &params)?;
+    encryption::open_file(db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY, Some(&key))?;
+    encryption::remember_key(db_path, key.clone());
+    Ok(key)
+}
+
+#[tauri::command]
+fn get_encryption_status(db: State<DbConnection>) -> Result<EncryptionStatus, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    encryption_status(&conn)
+}
+
+fn encryption_status(conn: &Connection) -> Result<EncryptionStatus, String> {
+    Ok(EncryptionStatus {
+        supported: encryption::is_supported(),
+        encrypted: live_db_path(conn).is_some_and(|p| encryption::is_encrypted(&p)),
+    })
+}
+
+// Migrates the open plaintext database, and its backups, to an encrypted one
+#[tauri::command]
+async fn enable_encryption(passphrase: String, db: State<'_, DbConnection>) -> Result<EncryptionStatus, String> {
+    if !encryption::is_supported() {
+        return Err(t!("This build of iToDo was made without encryption support"));
+    }
+    encryption::validate_passphrase(&passphrase)?;
+
+    let mut conn = db.0.lock().map_err(|e| e.to_string())?;
+    let db_path = live_db_path(&conn).ok_or_else(|| t!("No database is open"))?;
+    if encryption::is_encrypted(&db_path) {
+        return Err(t!("The database is already encrypted"));
+    }
+
+    let params = encryption::KeyParams::generate();
+    let key = encryption::derive_key(&passphrase, &params)?;
+    let encrypted = db_path.with_extension("encrypting");
+    encryption::export_encrypted(&conn, &encrypted, &key)?;
+    if let Err(e) = encryption::open_file(&encrypted, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY, Some(&key)) {
+        let _ = fs::remove_file(&encrypted);
+        return Err(t!("The encrypted copy could not be verified: {}", e));
+    }
+
+    // Close the plaintext file before it is replaced
+    *conn = Connection::open_in_memory().map_err(|e| e.to_string())?;
+    let replaced = encryption::save_params(&db_path, &params).and_then(|_| {
+        fs::rename(&encrypted, &db_path).map_err(|e| t!("Failed to replace the database: {}", e))
+    });
+    if let Err(e) = replaced {
+        let _ = fs::remove_file(encryption::key_file_path(&db_path));
+        let _ = fs::remove_file(&encrypted);
+        *conn = open_database(&db_path)?;
+        return Err(e);
+    }
+    let file_name = db_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    for suffix in ["-wal", "-shm", "-journal"] {
+        let _ = fs::remove_file(db_path.with_file_name(format!("{}{}", file_name, suffix)));
+    }
+    encryption::remember_key(&db_path, key.clone());
+    *conn = open_database(&db_path)?;
+
+    // Plaintext backups would defeat the purpose
+    for existing in backup::list_backups(&backup_dir_for(&db_path)?)? {
+        if let Err(e) = encryption::encrypt_file(Path::new(&existing.path), &key) {
+            error!("Failed to encrypt backup {}: {}", existing.id, e);
+        }
+    }
+
+    info!("Encrypted database {:?}", db_path);
+    Ok(EncryptionStatus { supported: true, encrypted: true })
+}
+
+// Re-keys the open database and its backups with a key derived from the new passphrase
+#[tauri::command]
+async fn change_passphrase(current: String, new_passphrase: String, db: State<'_, DbConnection>) -> Result<(), String> {
+    encryption::validate_passphrase(&new_passphrase)?;
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let db_path = live_db_path(&conn).ok_or_else(|| t!("No database is open"))?;
+    if !encryption::is_encrypted(&db_path) {
+        return Err(t!("The database is not encrypted"));
+    }
+    let old_params = encryption::load_params(&db_path)?;
+    let old_key = encryption::derive_key(&current, &old_params)?;
+    if encryption::key_for(&db_path).as_ref() != Some(&old_key) {
+        return Err(t!("The current passphrase is wrong"));
+    }
+
+    let params = encryption::KeyParams::generate();
+    let key = encryption::derive_key(&new_passphrase, &params)?;
+    encryption::rekey(&conn, &key)?;
+    if let Err(e) = encryption::save_params(&db_path, &params) {
+        // Without the new parameters the database couldn't be opened again
+        encryption::rekey(&conn, &old_key)?;
+        return Err(e);
+    }
+    encryption::remember_key(&db_path, key.clone());
+
+    for existing in backup::list_backups(&backup_dir_for(&db_path)?)? {
+        if let Err(e) = encryption::rekey_file(Path::new(&existing.path), &old_key, &key) {
+            error!("Failed to re-key backup {}: {}", existing.id, e);
+        }
+    }
+
+    info!("Changed the passphrase of {:?}", db_path);
+    Ok(())
+}
+
+// ============== Tauri Commands - App Lock ==============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockStatus {
+    pub enabled: bool,
+    pub locked: bool,
+    pub idle_minutes: u32,
+}
+
+fn lock_status(lock: &app_lock::AppLock) -> Result<LockStatus, String> {
+    // Damaged lock settings count as a lock that is on
+    let config = app_lock::LockConfig::load(&get_lock_config_path());
+    let state = lock.0.lock().map_err(|e| e.to_string())?;
+    Ok(LockStatus {
+        enabled: config.as_ref().map_or(true, |c| c.is_enabled()),
+        locked: state.locked,
+        idle_minutes: config.map_or(0, |c| c.idle_minutes),
+    })
+}
+
+// Locks the app once nothing has happened for the configured time
+fn start_idle_lock(app: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(15));
+        let Some(timeout) = app_lock::LockConfig::load(&get_lock_config_path()).ok().and_then(|c| c.idle_timeout()) else { continue };
+        let lock = app.state::<app_lock::AppLock>();
+        let Ok(mut state) = lock.0.lock() else { continue };
+        if !state.locked && state.last_activity.elapsed() >= timeout {
+            state.locked = true;
+            drop(state);
+            info!("Locked after {} minutes of inactivity", timeout.as_secs() / 60);
+            let _ = app.emit("app-locked", ());
+        }
+    });
+}
+
+#[tauri::command]
+fn get_lock_status(lock: State<app_lock::AppLock>) -> Result<LockStatus, String> {
+    lock_status(&lock)
+}
+
+// Returns whether the passphrase was right; the app is unlocked if it was
+#[tauri::command]
+async fn verify_unlock(passphrase: String, lock: State<'_, app_lock::AppLock>) -> Result<bool, String> {
+    let config = app_lock::LockConfig::load(&get_lock_config_path())?;
+    if !config.verify(&passphrase) {
+        info!("Unlock attempt with a wrong passphrase");
+        return Ok(false);
+    }
+    let mut state = lock.0.lock().map_err(|e| e.to_string())?;
+    state.locked = false;
+    state.last_activity = std::time::Instant::now();
     Ok(true)
 }
 
+// Sets or changes the lock passphrase; changing it needs the current one
+#[tauri::command]
+async fn set_lock_passphrase(current: Option<String>, passphrase: String, lock: State<'_, app_lock::AppLock>) -> Result<LockStatus, String> {
+    let path = get_lock_config_path();
+    let mut config = app_lock::LockConfig::load(&path)?;
+    if config.is_enabled() && !config.verify(current.as_deref().unwrap_or_default()) {
+        return Err(t!("The current passphrase is wrong"));
+    }
+    config.password_hash = Some(app_lock::hash_passphrase(&passphrase)?);
+    config.save(&path)?;
+    info!("App lock passphrase set");
+    lock_status(&lock)
+}
+
+#[tauri::command]
+async fn remove_lock_passphrase(current: String, lock: State<'_, app_lock::AppLock>) -> Result<LockStatus, String> {
+    let path = get_lock_config_path();
+    let mut config = app_lock::LockConfig::load(&path)?;
+    if !config.verify(&current) {
+        return Err(t!("The current passphrase is wrong"));
+    }
+    config.password_hash = None;
+    config.save(&path)?;
+    info!("App lock removed");
+    lock_status(&lock)
+}
+
+#[tauri::command]
+fn set_lock_idle_minutes(minutes: u32, lock: State<app_lock::AppLock>) -> Result<LockStatus, String> {
+    app_lock::LockConfig::validate_idle_minutes(minutes)?;
+    let path = get_lock_config_path();
+    let mut config = app_lock::LockConfig::load(&path)?;
+    config.idle_minutes = minutes;
+    config.save(&path)?;
+    lock_status(&lock)
+}
+
+#[tauri::command]
+fn lock_app(app: tauri::AppHandle, lock: State<app_lock::AppLock>) -> Result<LockStatus, String> {
+    if !app_lock::LockConfig::load(&get_lock_config_path())?.is_enabled() {
+        return Err(t!("No lock passphrase is set"));
+    }
+    lock.0.lock().map_err(|e| e.to_string())?.locked = true;
+    let _ = app.emit("app-locked", ());
+    lock_status(&lock)
+}
+
+// Called by the window on user input, so reading without clicking doesn't count as idle
+#[tauri::command]
+fn report_activity() {}
+
+// ============== Tauri Commands - Data Location ==============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataLocationInfo {
+    pub dir: String,
+    pub source: data_location::DataDirSource,
+    pub default_dir: String,
+    pub db_path: Option<String>,
+}
+
+fn data_location_info(conn: &Connection) -> DataLocationInfo {
+    let location = current_data_location();
+    DataLocationInfo {
+        dir: location.dir.to_string_lossy().to_string(),
+        source: location.source,
+        default_dir: data_location::default_dir().to_string_lossy().to_string(),
+        db_path: live_db_path(conn).map(|p| p.to_string_lossy().to_string()),
+    }
+}
+
+#[tauri::command]
+fn get_data_location(db: State<DbConnection>) -> Result<DataLocationInfo, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    Ok(data_location_info(&conn))
+}
+
+// Moves the data folder: the database is copied with the backup API, the other files are copied
+// as they are, and the app switches over. The old folder is left untouched.
+#[tauri::command]
+async fn relocate_data_dir(target_dir: String, db: State<'_, DbConnection>) -> Result<DataLocationInfo, String> {
+    let current = current_data_location();
+    if current.source.is_override() {
+        return Err(t!(
+            "The data folder is set by --data-dir, {} or portable mode and can't be changed from the app",
+            data_location::ENV_VAR
+        ));
+    }
+
+    let target = PathBuf::from(target_dir.trim());
+    if !target.is_absolute() {
+        return Err(t!("Please choose an absolute folder path"));
+    }
+    fs::create_dir_all(&target).map_err(|e| t!("Failed to create {}: {}", target.display(), e))?;
+    if fs::canonicalize(&target).ok() == fs::canonicalize(&current.dir).ok() {
+        return Err(t!("The data is already stored in this folder"));
+    }
+    // The open workspace keeps its place relative to the data folder
+    let registry = workspaces::WorkspaceRegistry::load(&current.dir);
+    let live_relative = workspaces::db_path(Path::new(""), &registry.last_used, DB_FILE_NAME);
+    let target_db = target.join(&live_relative);
+    if target.join(DB_FILE_NAME).exists() || target_db.exists() {
+        return Err(t!("{} already contains an iToDo database", target.display()));
+    }
+    if let Some(parent) = target_db.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let mut conn = db.0.lock().map_err(|e| e.to_string())?;
+    let key = live_db_path(&conn).and_then(|p| encryption::key_for(&p));
+    copy_live_database(&conn, &target_db, key.as_ref()).map_err(|e| t!("Failed to copy the database: {}", e))?;
+    if let Err(e) = backup::verify_backup(&target_db, key.as_ref()) {
+        let _ = fs::remove_file(&target_db);
+        return Err(t!("The copied database could not be verified: {}", e));
+    }
+    let copied = data_location::copy_data_files(&current.dir, &target, &live_relative)?;
+    // The key parameters sit next to the database, so they were skipped above
+    if let Some(key) = key {
+        fs::copy(encryption::key_file_path(&current.dir.join(&live_relative)), encryption::key_file_path(&target_db))
+            .map_err(|e| t!("Failed to copy the key parameters: {}", e))?;
+        encryption::remember_key(&target_db, key);
+    }
+    let new_conn = open_database(&target_db)?;
+
+    let is_default = fs::canonicalize(&target).ok() == fs::canonicalize(data_location::default_dir()).ok();
+    data_location::save_setting(if is_default { None } else { Some(&target) })?;
+    *conn = new_conn;
+    if let Ok(mut cached) = DATA_LOCATION.write() {
+        *cached = Some(data_location::DataLocation {
+            dir: target.clone(),
+            source: if is_default { data_location::DataDirSource::Default } else { data_location::DataDirSource::Setting },
+        });
+    }
+
+    info!("Relocated data from {:?} to {:?} ({} other files copied)", current.dir, target, copied);
+    Ok(data_location_info(&conn))
+}
+
+// ============== Tauri Commands - Workspaces ==============
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspacesInfo {
+    pub active: String,
+    pub workspaces: Vec<workspaces::Workspace>,
+}
+
+#[tauri::command]
+fn list_workspaces() -> WorkspacesInfo {
+    let registry = workspaces::WorkspaceRegistry::load(&get_data_dir());
+    WorkspacesInfo {
+        active: registry.last_used,
+        workspaces: registry.workspaces,
+    }
+}
+
+#[tauri::command]
+async fn create_workspace(name: String) -> Result<workspaces::Workspace, String> {
+    add_workspace(name)
+}
+
+fn add_workspace(name: String) -> Result<workspaces::Workspace, String> {
+    let data_dir = get_data_dir();
+    let mut registry = workspaces::WorkspaceRegistry::load(&data_dir);
+    let workspace = registry.create(&name)?;
+
+    // Created right away, so the new workspace starts with its default list
+    let db_path = workspaces::db_path(&data_dir, &workspace.id, DB_FILE_NAME);
+    if let Some(parent) = db_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    open_database(&db_path)?;
+    registry.save(&data_dir)?;
+
+    info!("Created workspace {} ({})", workspace.name, workspace.id);
+    Ok(workspace)
+}
+
+#[tauri::command]
+fn rename_workspace(id: String, name: String) -> Result<workspaces::Workspace, String> {
+    let data_dir = get_data_dir();
+    let mut registry = workspaces::WorkspaceRegistry::load(&data_dir);
+    let workspace = registry.rename(&id, &name)?;
+    registry.save(&data_dir)?;
+    Ok(workspace)
+}
+
+// Opens the workspace's database and swaps it in; the choice is remembered for the next launch
+#[tauri::command]
+async fn switch_workspace(
+    id: String,
+    passphrase: Option<String>,
+    recovery: State<'_, RecoveryState>,
+    db: State<'_, DbConnection>,
+) -> Result<workspaces::Workspace, String> {
+    let mut conn = db.0.lock().map_err(|e| e.to_string())?;
+    let workspace = open_workspace(&mut conn, &id, passphrase.as_deref())?;
+    // A workspace that opens fine also ends a failed startup
+    *recovery.0.lock().map_err(|e| e.to_string())? = None;
+    Ok(workspace)
+}
+
+// Replaces `conn` with the workspace's database and remembers it for the next launch
+fn open_workspace(conn: &mut Connection, id: &str, passphrase: Option<&str>) -> Result<workspaces::Workspace, String> {
+    let data_dir = get_data_dir();
+    let mut registry = workspaces::WorkspaceRegistry::load(&data_dir);
+    let workspace = registry
+        .find(id)
+        .cloned()
+        .ok_or_else(|| t!("Workspace not found: {}", id))?;
+    let db_path = workspaces::db_path(&data_dir, id, DB_FILE_NAME);
+    if let Some(passphrase) = passphrase.filter(|_| encryption::is_encrypted(&db_path)) {
+        unlock_with_passphrase(&db_path, passphrase)?;
+    }
+    // Keeps the caller's timeout, which is longer for the RPC server
+    let busy_ms: u64 = conn.query_row("PRAGMA busy_timeout", [], |row| row.get(0)).map_err(|e| e.to_string())?;
+    let new_conn = open_database_waiting(&db_path, std::time::Duration::from_millis(busy_ms))?;
+
+    registry.last_used = id.to_string();
+    registry.save(&data_dir)?;
+    *conn = new_conn;
+
+    info!("Switched to workspace {} ({})", workspace.name, workspace.id);
+    Ok(workspace)
+}
+
+#[tauri::command]
+async fn delete_workspace(id: String) -> Result<(), String> {
+    remove_workspace(id)
+}
+
+fn remove_workspace(id: String) -> Result<(), String> {
+    let data_dir = get_data_dir();
+    let mut registry = workspaces::WorkspaceRegistry::load(&data_dir);
+    let workspace = registry.remove(&id)?;
+    let db_path = workspaces::db_path(&data_dir, &id, DB_FILE_NAME);
+    workspaces::delete_files(&db_path)?;
+    let _ = fs::remove_file(encryption::key_file_path(&db_path));
+    registry.save(&data_dir)?;
+
+    info!("Deleted workspace {} ({})", workspace.name, workspace.id);
+    Ok(())
+}
+
+// ============== Tauri Commands - HTTP API ==============
+
+// The running server, if any, with the thread answering its requests
+pub struct ApiServer(pub Mutex<Option<(Arc<tiny_http::Server>, std::thread::JoinHandle<()>)>>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    pub token: String,
+    pub url: String,
+}
+
+fn api_status(api: &ApiServer) -> Result<ApiStatus, String> {
+    let config = http_api::ApiConfig::load(&get_api_config_path());
+    let running = api.0.lock().map_err(|e| e.to_string())?.is_some();
+    Ok(ApiStatus {
+        enabled: config.enabled,
+        running,
+        port: config.port,
+        url: format!("http://127.0.0.1:{}/api", config.port),
+        token: config.token,
+    })
+}
+
+// Stops the server that is running, then starts one for `config` if the API is enabled
+fn restart_api_server(app: &tauri::AppHandle, config: &http_api::ApiConfig) -> Result<(), String> {
+    let api = app.state::<ApiServer>();
+    let mut current = api.0.lock().map_err(|e| e.to_string())?;
+    if let Some((server, thread)) = current.take() {
+        server.unblock();
+        let _ = thread.join();
+        info!("API server stopped");
+    }
+    if !config.enabled {
+        return Ok(());
+    }
+
+    let server = Arc::new(http_api::bind(config.port)?);
+    let (handle, token, serving) = (app.clone(), config.token.clone(), server.clone());
+    let thread = std::thread::spawn(move || {
+        http_api::serve(&serving, &token, |method, url, body| handle_api_request(&handle, method, url, body));
+    });
+    *current = Some((server, thread));
+    info!("API server listening on 127.0.0.1:{}", config.port);
+    Ok(())
+}
+
+fn handle_api_request(app: &tauri::AppHandle, method: &str, url: &str, body: &str) -> http_api::ApiResponse {
+    // The lock screen covers the API too
+    let locked = app.state::<app_lock::AppLock>().0.lock().map(|state| state.locked).unwrap_or(true);
+    // In recovery mode the connection is an empty in-memory placeholder
+    let recovering = app.state::<RecoveryState>().0.lock().map(|failure| failure.is_some()).unwrap_or(true);
+
+    let db = app.state::<DbConnection>();
+    let response = match db.0.lock() {
+        Ok(conn) => http_api::respond(&conn, locked, recovering, method, url, body),
+        Err(e) => http_api::ApiResponse::error(500, e.to_string()),
+    };
+    // Lets the window reload what it shows
+    if method != "GET" && response.is_success() {
+        let _ = app.emit("tasks-changed", ());
+    }
+    response
+}
+
+#[tauri::command]
+fn get_api_status(api: State<ApiServer>) -> Result<ApiStatus, String> {
+    api_status(&api)
+}
+
+#[tauri::command]
+async fn update_api_config(enabled: bool, port: Option<u16>, app: tauri::AppHandle) -> Result<ApiStatus, String> {
+    let path = get_api_config_path();
+    let mut config = http_api::ApiConfig::load(&path);
+    if let Some(port) = port {
+        http_api::ApiConfig::validate_port(port)?;
+        config.port = port;
+    }
+    config.enabled = enabled;
+    if config.enabled && config.token.is_empty() {
+        config.token = http_api::generate_token();
+    }
+    restart_api_server(&app, &config)?;
+    config.save(&path)?;
+    api_status(&app.state::<ApiServer>())
+}
+
+// Clients holding the old token are refused from now on
+#[tauri::command]
+async fn regenerate_api_token(app: tauri::AppHandle) -> Result<ApiStatus, String> {
+    let path = get_api_config_path();
+    let mut config = http_api::ApiConfig::load(&path);
+    config.token = http_api::generate_token();
+    restart_api_server(&app, &config)?;
+    config.save(&path)?;
+    info!("API token regenerated");
+    api_status(&app.state::<ApiServer>())
+}
+
 // ============== App Setup ==============
 
 pub fn run() {
-    let _ = env_logger::try_init();
+    // Editor plugins start the binary to talk JSON-RPC over stdio, without opening a window
+    if std::env::args().skip(1).any(|arg| arg == rpc::FLAG) {
+        std::process::exit(rpc::run());
+    }
+
+    let settings = load_settings();
+    if let Err(e) = logging::init(&get_log_dir(), settings.log_level.filter()) {
+        let _ = env_logger::try_init();
+        error!("Logging to stderr only: {}", e);
+    }
     info!("Starting iToDo application");
 
-    let data_dir = get_data_dir();
-    let db_path = data_dir.join("itodo.db");
+    let location = current_data_location();
+    info!("Data folder: {:?} ({:?})", location.dir, location.source);
+    i18n::set_language(settings.language);
+    let db_path = get_db_path();
     info!("Database path: {:?}", db_path);
 
-    let conn = Connection::open(&db_path).expect("Failed to open database");
-    init_database(&conn).expect("Failed to initialize database");
+    let (conn, failure) = match open_database(&db_path) {
+        Ok(conn) => (conn, None),
+        Err(e) => {
+            error!("Starting in recovery mode: {}", e);
+            // Left without tables so task commands fail instead of writing to a throwaway database
+            let placeholder = Connection::open_in_memory().expect("Failed to open in-memory database");
+            (placeholder, Some(StartupFailure { db_path, error: e }))
+        }
+    };
+
+    let lock_config = app_lock::LockConfig::load(&get_lock_config_path());
+    if let Err(e) = &lock_config {
+        error!("{}", e);
+    }
+    let app_lock = app_lock::AppLock::new(lock_config.map_or(true, |c| c.is_enabled()));
+    let gate = app_lock.0.clone();
+    let handler = tauri::generate_handler![
+        get_lists,
+        create_list,
+        update_list,
+        delete_list,
+        get_tasks,
+        get_important_tasks,
+        get_today_tasks,
+        get_planned_tasks,
+        get_completed_tasks,
+        search_tasks,
+        create_task,
+        update_task,
+        delete_task,
+        toggle_task_important,
+        toggle_task_completed,
+        get_subtasks,
+        get_all_subtasks,
+        create_subtask,
+        update_subtask,
+        delete_subtask,
+        toggle_subtask_completed,
+        get_task_tags,
+        set_task_tags,
+        export_tasks_to_file,
+        import_tasks,
+        export_tasks_to_path,
+        export_anonymized_to_path,
+        export_tasks_to_ics,
+        import_tasks_from_ics,
+        export_tasks_to_csv,
+        import_tasks_from_csv,
+        export_tasks_to_todotxt,
+        import_tasks_from_todotxt,
+        export_tasks_to_markdown,
+        import_tasks_from_markdown,
+        export_tasks_to_org,
+        import_tasks_from_org,
+        export_tasks_to_taskwarrior,
+        import_tasks_from_taskwarrior,
+        export_tasks_to_html,
+        export_bundle,
+        import_bundle,
+        get_importers,
+        import_from_source,
+        get_settings,
+        update_settings,
+        get_backup_config,
+        update_backup_config,
+        list_backups,
+        create_backup,
+        restore_backup,
+        check_database,
+        repair_database,
+        create_diagnostics_bundle,
+        get_startup_status,
+        retry_open_database,
+        restore_latest_backup,
+        open_database_file,
+        start_fresh_database,
+        unlock_database,
+        get_encryption_status,
+        enable_encryption,
+        change_passphrase,
+        get_lock_status,
+        verify_unlock,
+        set_lock_passphrase,
+        remove_lock_passphrase,
+        set_lock_idle_minutes,
+        lock_app,
+        report_activity,
+        get_data_location,
+        relocate_data_dir,
+        list_workspaces,
+        create_workspace,
+        rename_workspace,
+        switch_workspace,
+        delete_workspace,
+        get_api_status,
+        update_api_config,
+        regenerate_api_token,
+        get_log_path,
+        get_about_info,
+    ];
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
@@ -813,29 +3064,34 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .manage(DbConnection(Mutex::new(conn)))
-        .invoke_handler(tauri::generate_handler![
-            get_lists,
-            create_list,
-            update_list,
-            delete_list,
-            get_tasks,
-            get_important_tasks,
-            get_today_tasks,
-            get_planned_tasks,
-            get_completed_tasks,
-            search_tasks,
-            create_task,
-            update_task,
-            delete_task,
-            toggle_task_important,
-            toggle_task_completed,
-            export_tasks_to_file,
-            import_tasks,
-            export_tasks_to_path,
-            get_log_path,
-            get_about_info,
-        ])
-        .setup(|_app| {
+        .manage(RecoveryState(Mutex::new(failure)))
+        .manage(app_lock)
+        .manage(ApiServer(Mutex::new(None)))
+        // While locked only the lock screen's commands get through; every other call counts as activity
+        .invoke_handler(move |invoke| {
+            if let Ok(mut state) = gate.lock() {
+                if state.locked && !app_lock::is_allowed_while_locked(invoke.message.command()) {
+                    invoke.resolver.reject(app_lock::locked_error());
+                    return true;
+                }
+                state.last_activity = std::time::Instant::now();
+            }
+            handler(invoke)
+        })
+        .setup(|app| {
+            start_backup_scheduler(app.handle().clone());
+            start_idle_lock(app.handle().clone());
+            // An API turned on by editing api.json has no token yet; it is written back for clients to read
+            let mut api_config = http_api::ApiConfig::load(&get_api_config_path());
+            if api_config.enabled && api_config.token.is_empty() {
+                api_config.token = http_api::generate_token();
+                if let Err(e) = api_config.save(&get_api_config_path()) {
+                    error!("{}", e);
+                }
+            }
+            if let Err(e) = restart_api_server(app.handle(), &api_config) {
+                error!("API server not started: {}", e);
+            }
             info!("App setup complete");
             Ok(())
         })
@@ -0,0 +1,195 @@
+// Log files in the `logs` folder of the data directory, so users have something to send with bug
+// reports.
+//
+// Everything goes to `itodo.log` and is echoed to stderr. The file is rotated to
+// `itodo-YYYY-MM-DD.N.log` when it grows past `MAX_FILE_SIZE` or the day changes, and only the
+// newest `KEEP_FILES` rotated files are kept. A panic hook writes crash details and a backtrace.
+
+use chrono::{Local, NaiveDate};
+use log::{LevelFilter, Log, Metadata, Record};
+use std::backtrace::Backtrace;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const LOG_FILE_NAME: &str = "itodo.log";
+const ROTATED_PREFIX: &str = "itodo-";
+const MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+const KEEP_FILES: usize = 10;
+
+struct LogFile {
+    file: File,
+    // Day of the first line written to the file
+    date: NaiveDate,
+    size: u64,
+}
+
+struct FileLogger {
+    dir: PathBuf,
+    current: Mutex<Option<LogFile>>,
+}
+
+impl FileLogger {
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        let mut current = self.current.lock().map_err(|_| io::Error::other("log file lock poisoned"))?;
+        let today = Local::now().date_naive();
+        let needs_rotation = current
+            .as_ref()
+            .is_some_and(|log| log.date != today || log.size + line.len() as u64 > MAX_FILE_SIZE);
+        if needs_rotation {
+            if let Some(log) = current.take() {
+                drop(log.file);
+                rotate(&self.dir, log.date)?;
+            }
+        }
+        if current.is_none() {
+            *current = Some(open_log_file(&self.dir)?);
+        }
+
+        let log = current.as_mut().expect("log file was just opened");
+        log.file.write_all(line.as_bytes())?;
+        log.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} {}: {}\n",
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        eprint!("{}", line);
+        // Nowhere left to report a failing log file
+        let _ = self.write_line(&line);
+    }
+
+    fn flush(&self) {
+        if let Ok(mut current) = self.current.lock() {
+            if let Some(log) = current.as_mut() {
+                let _ = log.file.flush();
+            }
+        }
+    }
+}
+
+pub fn init(dir: &Path, level: LevelFilter) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create log folder: {}", e))?;
+    // An existing file from an earlier day is rotated on the first write
+    let current = open_log_file(dir).map_err(|e| format!("Failed to open log file: {}", e))?;
+    let logger = FileLogger {
+        dir: dir.to_path_buf(),
+        current: Mutex::new(Some(current)),
+    };
+    log::set_logger(Box::leak(Box::new(logger))).map_err(|e| e.to_string())?;
+    log::set_max_level(level);
+    install_panic_hook();
+    Ok(())
+}
+
+pub fn set_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
+
+// The current file first, then rotated files from newest to oldest
+pub fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = rotated_files(dir);
+    files.reverse();
+    let current = dir.join(LOG_FILE_NAME);
+    if current.is_file() {
+        files.insert(0, current);
+    }
+    files
+}
+
+fn open_log_file(dir: &Path) -> io::Result<LogFile> {
+    let path = dir.join(LOG_FILE_NAME);
+    let file = OpenOptions::new().create(true).append(true).open(&path)?;
+    let metadata = file.metadata()?;
+    let date = metadata
+        .modified()
+        .ok()
+        .filter(|_| metadata.len() > 0)
+        .map(|modified| chrono::DateTime::<Local>::from(modified).date_naive())
+        .unwrap_or_else(|| Local::now().date_naive());
+    Ok(LogFile {
+        file,
+        date,
+        size: metadata.len(),
+    })
+}
+
+fn rotate(dir: &Path, date: NaiveDate) -> io::Result<()> {
+    let stem = format!("{}{}", ROTATED_PREFIX, date.format("%Y-%m-%d"));
+    let mut index = 1;
+    let target = loop {
+        let candidate = dir.join(format!("{}.{}.log", stem, index));
+        if !candidate.exists() {
+            break candidate;
+        }
+        index += 1;
+    };
+    fs::rename(dir.join(LOG_FILE_NAME), target)?;
+
+    let rotated = rotated_files(dir);
+    for old in rotated.iter().take(rotated.len().saturating_sub(KEEP_FILES)) {
+        let _ = fs::remove_file(old);
+    }
+    Ok(())
+}
+
+// Rotated files from oldest to newest
+fn rotated_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut files: Vec<(NaiveDate, u32, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let rest = name.strip_prefix(ROTATED_PREFIX)?.strip_suffix(".log")?;
+            let (date, index) = rest.split_once('.')?;
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+            Some((date, index.parse().ok()?, entry.path()))
+        })
+        .collect();
+    files.sort();
+    files.into_iter().map(|(_, _, path)| path).collect()
+}
+
+// Logs the panic with a backtrace, then hands over to the default hook, which prints it and lets
+// the process go down as before
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let thread = std::thread::current();
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_else(|| "unknown location".to_string());
+        log::error!(
+            "Panic in thread '{}' at {}: {}\n{}",
+            thread.name().unwrap_or("<unnamed>"),
+            location,
+            message,
+            Backtrace::force_capture()
+        );
+        log::logger().flush();
+        default_hook(info);
+    }));
+}
@@ -0,0 +1,286 @@
+// Markdown checklist export and import.
+//
+// Each list is a `#` heading followed by `- [ ]` / `- [x]` items. Subtasks are nested items,
+// notes are indented lines under the task, and the due date follows the title as "(due: YYYY-MM-DD)".
+// Exported notes are quoted with "> " so a note reading "# Plan" or "- [ ] milk" stays a note.
+
+use crate::{List, Subtask, Task};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const IMPORTANT_MARK: &str = "⭐";
+const NOTE_PREFIX: &str = "> ";
+
+pub struct MarkdownSection {
+    pub name: Option<String>,
+    pub tasks: Vec<MarkdownTask>,
+}
+
+pub struct MarkdownTask {
+    pub task: Task,
+    pub subtasks: Vec<Subtask>,
+}
+
+// ============== Export ==============
+
+pub fn export_lists(lists: &[List], tasks: &[Task], subtasks: &HashMap<String, Vec<Subtask>>) -> String {
+    let mut out = String::new();
+
+    for list in lists {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("# {}\n\n", single_line(&list.name)));
+
+        for task in tasks.iter().filter(|t| t.list_id == list.id) {
+            let mut line = format!("- [{}] {}", check(task.is_completed), single_line(&task.title));
+            if task.is_important {
+                line.push(' ');
+                line.push_str(IMPORTANT_MARK);
+            }
+            if let Some(due) = task.due_date.as_deref().and_then(date_part) {
+                line.push_str(&format!(" (due: {})", due));
+            }
+            out.push_str(&line);
+            out.push('\n');
+
+            if let Some(content) = task.content.as_deref() {
+                for note in content.lines().filter(|l| !l.trim().is_empty()) {
+                    out.push_str(&format!("  {}{}\n", NOTE_PREFIX, note.trim_end()));
+                }
+            }
+            for subtask in subtasks.get(&task.id).into_iter().flatten() {
+                out.push_str(&format!("  - [{}] {}\n", check(subtask.is_completed), single_line(&subtask.title)));
+            }
+        }
+    }
+
+    out
+}
+
+fn check(done: bool) -> char {
+    if done {
+        'x'
+    } else {
+        ' '
+    }
+}
+
+fn single_line(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn date_part(value: &str) -> Option<String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc).format("%Y-%m-%d").to_string());
+    }
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d")
+        .ok()
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+// ============== Import ==============
+
+pub fn parse_checklists(data: &str) -> Vec<MarkdownSection> {
+    let mut sections = vec![MarkdownSection { name: None, tasks: Vec::new() }];
+    // Indentation of the current top-level item; anything deeper belongs to it
+    let mut task_indent: Option<usize> = None;
+    let mut in_code_block = false;
+
+    for raw in data.lines() {
+        let line = raw.trim_end();
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block || trimmed.is_empty() {
+            continue;
+        }
+
+        let indent = indent_width(line);
+        // Anything indented under a task belongs to it, even a line that looks like a heading
+        if let (Some(parent_indent), Some(parent)) = (task_indent, sections.last_mut().and_then(|s| s.tasks.last_mut())) {
+            if indent > parent_indent {
+                if let Some((done, text)) = parse_item(trimmed) {
+                    let now = Utc::now().to_rfc3339();
+                    parent.subtasks.push(Subtask {
+                        id: Uuid::new_v4().to_string(),
+                        task_id: parent.task.id.clone(),
+                        title: text.to_string(),
+                        is_completed: done,
+                        created_at: now.clone(),
+                        updated_at: now,
+                    });
+                } else {
+                    let notes = parent.task.content.get_or_insert_with(String::new);
+                    if !notes.is_empty() {
+                        notes.push('\n');
+                    }
+                    notes.push_str(unquote_note(trimmed));
+                }
+                continue;
+            }
+        }
+
+        if let Some(heading) = parse_heading(trimmed) {
+            sections.push(MarkdownSection { name: Some(heading), tasks: Vec::new() });
+            task_indent = None;
+            continue;
+        }
+
+        let section = sections.last_mut().expect("at least one section");
+        if let Some((done, text)) = parse_item(trimmed) {
+            section.tasks.push(MarkdownTask { task: new_task(text, done), subtasks: Vec::new() });
+            task_indent = Some(indent);
+            continue;
+        }
+        task_indent = None;
+    }
+
+    sections.retain(|s| !s.tasks.is_empty());
+    sections
+}
+
+fn new_task(text: &str, done: bool) -> Task {
+    let (title, is_important, due_date) = split_title(text);
+    let now = Utc::now().to_rfc3339();
+    Task {
+        id: Uuid::new_v4().to_string(),
+        title,
+        content: None,
+        is_completed: done,
+        is_important,
+        due_date,
+        start_date: None,
+        remind_time: None,
+        repeat_rule: None,
+        list_id: String::new(),
+        created_at: now.clone(),
+        updated_at: now,
+    }
+}
+
+// Pulls the "(due: ...)" suffix and the important mark off an item title
+fn split_title(text: &str) -> (String, bool, Option<String>) {
+    let mut title = text.trim().to_string();
+    let mut due_date = None;
+
+    if let Some(start) = title.rfind("(due:") {
+        if title.ends_with(')') {
+            let value = title[start + 5..title.len() - 1].trim();
+            if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+                due_date = Some(format!("{}T00:00:00Z", date.format("%Y-%m-%d")));
+                title.truncate(start);
+            }
+        }
+    }
+
+    let trimmed = title.trim_end();
+    let is_important = trimmed.ends_with(IMPORTANT_MARK);
+    let title = trimmed.trim_end_matches(IMPORTANT_MARK).trim().to_string();
+    (title, is_important, due_date)
+}
+
+// Exported notes start with "> "; hand-written ones may not
+fn unquote_note(trimmed: &str) -> &str {
+    trimmed
+        .strip_prefix(NOTE_PREFIX)
+        .or_else(|| trimmed.strip_prefix(NOTE_PREFIX.trim_end()))
+        .unwrap_or(trimmed)
+}
+
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+fn parse_heading(trimmed: &str) -> Option<String> {
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.starts_with(' ') {
+        return None;
+    }
+    let name = rest.trim().trim_end_matches('#').trim();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+// "- [ ] text", "* [x] text", "1. [ ] text" or a plain "- text" bullet
+fn parse_item(trimmed: &str) -> Option<(bool, &str)> {
+    let rest = if let Some(rest) = trimmed.strip_prefix(['-', '*', '+']) {
+        rest
+    } else {
+        let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        trimmed[digits..].strip_prefix(['.', ')'])?
+    };
+    let rest = rest.strip_prefix(' ')?.trim_start();
+
+    let (done, text) = if let Some(text) = rest.strip_prefix("[ ]") {
+        (false, text)
+    } else if let Some(text) = rest.strip_prefix("[x]").or_else(|| rest.strip_prefix("[X]")) {
+        (true, text)
+    } else {
+        (false, rest)
+    };
+
+    let text = text.trim();
+    if text.is_empty() {
+        None
+    } else {
+        Some((done, text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notes_that_look_like_markdown_stay_notes() {
+        let list = List {
+            id: "list".to_string(),
+            name: "Home".to_string(),
+            color: None,
+            icon: None,
+            is_default: true,
+            created_at: String::new(),
+            order: 0,
+        };
+        let mut task = new_task("Plan the move", false);
+        task.list_id = list.id.clone();
+        task.content = Some("# Boxes\n- [ ] tape\n> quoted\nplain".to_string());
+
+        let markdown = export_lists(&[list], &[task], &HashMap::new());
+        let sections = parse_checklists(&markdown);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].name.as_deref(), Some("Home"));
+        let imported = &sections[0].tasks;
+        assert_eq!(imported.len(), 1);
+        assert!(imported[0].subtasks.is_empty());
+        assert_eq!(imported[0].task.content.as_deref(), Some("# Boxes\n- [ ] tape\n> quoted\nplain"));
+    }
+
+    #[test]
+    fn reads_hand_written_notes_and_subtasks() {
+        let sections = parse_checklists("- [ ] Pack\n  fragile first\n  # not a list\n  - [x] Kitchen\n# Work\n- [ ] Report\n");
+        assert_eq!(sections.len(), 2);
+        let pack = &sections[0].tasks[0];
+        assert_eq!(pack.task.content.as_deref(), Some("fragile first\n# not a list"));
+        assert_eq!(pack.subtasks.len(), 1);
+        assert!(pack.subtasks[0].is_completed);
+        assert_eq!(sections[1].name.as_deref(), Some("Work"));
+    }
+}
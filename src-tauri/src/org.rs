@@ -0,0 +1,506 @@
+// Org-mode export and import.
+//
+// Lists are top-level headings and tasks are `TODO`/`DONE` headlines below them, with
+// `DEADLINE:`/`SCHEDULED:` planning lines, `[#A]` for important tasks, repeaters such as `+1w`
+// and the notes as indented body text. Subtasks are third-level headlines and tags are headline
+// tags. Planning lines and drawers only count right below a headline, so notes that look like
+// them stay notes.
+
+use crate::repeat::{RepeatKind, RepeatRule};
+use crate::{List, Subtask, Task};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const DONE_KEYWORDS: [&str; 3] = ["DONE", "CANCELLED", "CANCELED"];
+const OPEN_KEYWORDS: [&str; 4] = ["TODO", "NEXT", "WAITING", "HOLD"];
+const PLANNING_KEYWORDS: [&str; 3] = ["DEADLINE:", "SCHEDULED:", "CLOSED:"];
+// Holds the name of a list whose heading would otherwise be read as a task, e.g. "TODO later"
+const LIST_PROPERTY: &str = ":ITODO_LIST:";
+
+pub struct OrgSection {
+    // None for tasks that are not under a list heading
+    pub name: Option<String>,
+    pub tasks: Vec<OrgTask>,
+}
+
+pub struct OrgTask {
+    pub task: Task,
+    pub subtasks: Vec<Subtask>,
+    pub tags: Vec<String>,
+}
+
+// ============== Export ==============
+
+pub fn export_lists(
+    lists: &[List],
+    tasks: &[Task],
+    subtasks: &HashMap<String, Vec<Subtask>>,
+    tags: &HashMap<String, Vec<String>>,
+) -> String {
+    let mut out = String::from("#+TITLE: iToDo\n");
+
+    for list in lists {
+        let name = single_line(&list.name);
+        out.push_str(&format!("\n* {}\n", name));
+        if split_headline(&name).keyword.is_some() {
+            out.push_str(&format!(":PROPERTIES:\n{} {}\n:END:\n", LIST_PROPERTY, name));
+        }
+
+        for task in tasks.iter().filter(|t| t.list_id == list.id) {
+            let mut headline = format!("** {} ", keyword(task.is_completed));
+            if task.is_important {
+                headline.push_str("[#A] ");
+            }
+            headline.push_str(&single_line(&task.title));
+            let task_tags: Vec<String> = tags.get(&task.id).into_iter().flatten().map(|t| tag_name(t)).collect();
+            if !task_tags.is_empty() {
+                headline.push_str(&format!(" :{}:", task_tags.join(":")));
+            }
+            out.push_str(&headline);
+            out.push('\n');
+
+            let planning = planning_line(task);
+            if !planning.is_empty() {
+                out.push_str(&planning);
+                out.push('\n');
+            }
+
+            if let Some(content) = task.content.as_deref() {
+                let mut lines = content.trim_end().lines().skip_while(|l| l.trim().is_empty()).peekable();
+                // A blank line ends the headline's planning and drawers, so the notes can't be
+                // read back as either
+                if lines.peek().is_some_and(|l| is_planning(l.trim()) || is_drawer_start(l.trim())) {
+                    out.push('\n');
+                }
+                for line in lines {
+                    // Indented, so a line starting with "*" isn't read back as a headline
+                    if !line.trim().is_empty() {
+                        out.push_str("  ");
+                        out.push_str(line.trim_end());
+                    }
+                    out.push('\n');
+                }
+            }
+
+            for subtask in subtasks.get(&task.id).into_iter().flatten() {
+                out.push_str(&format!("*** {} {}\n", keyword(subtask.is_completed), single_line(&subtask.title)));
+            }
+        }
+    }
+
+    out
+}
+
+fn keyword(done: bool) -> &'static str {
+    if done {
+        "DONE"
+    } else {
+        "TODO"
+    }
+}
+
+fn single_line(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Org tags may only hold letters, digits, "_", "@", "#" and "%"
+fn tag_name(tag: &str) -> String {
+    tag.trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || "_@#%".contains(c) { c } else { '_' })
+        .collect()
+}
+
+fn planning_line(task: &Task) -> String {
+    // The repeater goes on the deadline, or on the scheduled date if there is no deadline
+    let repeater = task.repeat_rule.as_deref().and_then(RepeatRule::parse).map(|r| to_repeater(&r));
+    let due = task.due_date.as_deref().and_then(parse_date);
+    let start = task.start_date.as_deref().and_then(parse_date);
+
+    let mut parts = Vec::new();
+    if task.is_completed {
+        if let Ok(closed) = DateTime::parse_from_rfc3339(&task.updated_at) {
+            parts.push(format!("CLOSED: [{}]", closed.with_timezone(&Utc).format("%Y-%m-%d %a %H:%M")));
+        }
+    }
+    if let Some(date) = due {
+        parts.push(format!("DEADLINE: {}", timestamp(date, repeater.as_deref())));
+    }
+    if let Some(date) = start {
+        let repeater = if due.is_none() { repeater.as_deref() } else { None };
+        parts.push(format!("SCHEDULED: {}", timestamp(date, repeater)));
+    }
+    parts.join(" ")
+}
+
+fn timestamp(date: NaiveDate, repeater: Option<&str>) -> String {
+    match repeater {
+        Some(r) => format!("<{} {}>", date.format("%Y-%m-%d %a"), r),
+        None => format!("<{}>", date.format("%Y-%m-%d %a")),
+    }
+}
+
+// Weekday and month-day selections have no Org equivalent; only the period is kept
+fn to_repeater(rule: &RepeatRule) -> String {
+    let unit = match rule.kind {
+        RepeatKind::Daily => 'd',
+        RepeatKind::Weekly => 'w',
+        RepeatKind::Monthly => 'm',
+        RepeatKind::Yearly => 'y',
+    };
+    format!("+{}{}", rule.interval(), unit)
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc).date_naive());
+    }
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+// ============== Import ==============
+
+pub fn parse_outline(data: &str) -> Vec<OrgSection> {
+    let mut sections = vec![OrgSection { name: None, tasks: Vec::new() }];
+    // Whether body lines currently belong to the last task (and not to a list or subtask)
+    let mut in_task_body = false;
+    let mut in_drawer = false;
+    // Whether the task's planning line and drawers may still follow its headline
+    let mut in_head = false;
+    let mut at_planning = false;
+    // Headlines deeper than the last task are its subtasks
+    let mut task_level = usize::MAX;
+
+    let lines: Vec<&str> = data.lines().collect();
+    for (i, raw) in lines.iter().enumerate() {
+        let line = raw.trim_end();
+
+        if let Some((level, text)) = parse_headline(line) {
+            in_drawer = false;
+            in_task_body = false;
+            let headline = split_headline(text);
+
+            // A top-level headline without a TODO keyword is a list; one with a keyword is a
+            // task outside any list, unless it carries the list property
+            if level == 1 {
+                let name = list_property(&lines[i + 1..])
+                    .or_else(|| headline.keyword.is_none().then(|| headline.title.clone()));
+                let is_list = name.is_some();
+                sections.push(OrgSection { name, tasks: Vec::new() });
+                if is_list {
+                    continue;
+                }
+            }
+
+            let section = sections.last_mut().expect("at least one section");
+            match section.tasks.last_mut() {
+                Some(parent) if level > task_level => {
+                    let now = Utc::now().to_rfc3339();
+                    parent.subtasks.push(Subtask {
+                        id: Uuid::new_v4().to_string(),
+                        task_id: parent.task.id.clone(),
+                        title: headline.title,
+                        is_completed: headline.done,
+                        created_at: now.clone(),
+                        updated_at: now,
+                    });
+                }
+                _ => {
+                    section.tasks.push(OrgTask {
+                        task: new_task(headline.title, headline.done, headline.important),
+                        subtasks: Vec::new(),
+                        tags: headline.tags,
+                    });
+                    task_level = level;
+                    in_task_body = true;
+                    in_head = true;
+                    at_planning = true;
+                }
+            }
+            continue;
+        }
+
+        if !in_task_body {
+            continue;
+        }
+        let Some(task) = sections.last_mut().and_then(|s| s.tasks.last_mut()).map(|t| &mut t.task) else {
+            continue;
+        };
+
+        let trimmed = line.trim();
+        if in_drawer {
+            in_drawer = !trimmed.eq_ignore_ascii_case(":END:");
+            continue;
+        }
+        if in_head {
+            // Planning goes on the first line only; drawers may follow it
+            if std::mem::take(&mut at_planning) && apply_planning(task, trimmed) {
+                continue;
+            }
+            if is_drawer_start(trimmed) {
+                in_drawer = true;
+                continue;
+            }
+            in_head = false;
+        }
+
+        let notes = task.content.get_or_insert_with(String::new);
+        if !notes.is_empty() || !trimmed.is_empty() {
+            if !notes.is_empty() {
+                notes.push('\n');
+            }
+            notes.push_str(line);
+        }
+    }
+
+    for section in &mut sections {
+        for item in &mut section.tasks {
+            item.task.content = item.task.content.take().map(|c| dedent(&c)).filter(|c| !c.is_empty());
+        }
+    }
+    sections.retain(|s| !s.tasks.is_empty());
+    sections
+}
+
+struct Headline {
+    keyword: Option<String>,
+    done: bool,
+    important: bool,
+    title: String,
+    tags: Vec<String>,
+}
+
+fn parse_headline(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '*').count();
+    if level == 0 {
+        return None;
+    }
+    let rest = line[level..].strip_prefix(' ')?;
+    Some((level, rest.trim()))
+}
+
+fn split_headline(text: &str) -> Headline {
+    let mut rest = text;
+
+    let mut keyword = None;
+    let (first, tail) = rest.split_once(' ').unwrap_or((rest, ""));
+    if DONE_KEYWORDS.contains(&first) || OPEN_KEYWORDS.contains(&first) {
+        keyword = Some(first.to_string());
+        rest = tail.trim_start();
+    }
+    let done = keyword.as_deref().is_some_and(|k| DONE_KEYWORDS.contains(&k));
+
+    let mut important = false;
+    if let Some(tail) = rest.strip_prefix("[#") {
+        if let Some((priority, tail)) = tail.split_once(']') {
+            important = priority == "A";
+            rest = tail.trim_start();
+        }
+    }
+
+    // Trailing ":tag1:tag2:" block
+    let mut tags = Vec::new();
+    let (title, last) = rest.rsplit_once(' ').unwrap_or(("", rest));
+    if last.len() > 2 && last.starts_with(':') && last.ends_with(':') {
+        tags = last.trim_matches(':').split(':').filter(|t| !t.is_empty()).map(str::to_string).collect();
+        rest = title.trim_end();
+    }
+
+    Headline {
+        keyword,
+        done,
+        important,
+        title: rest.to_string(),
+        tags,
+    }
+}
+
+fn new_task(title: String, done: bool, important: bool) -> Task {
+    let now = Utc::now().to_rfc3339();
+    Task {
+        id: Uuid::new_v4().to_string(),
+        title,
+        content: None,
+        is_completed: done,
+        is_important: important,
+        due_date: None,
+        start_date: None,
+        remind_time: None,
+        repeat_rule: None,
+        list_id: String::new(),
+        created_at: now.clone(),
+        updated_at: now,
+    }
+}
+
+// Removes the indentation all note lines share, keeping the rest of it
+fn dedent(notes: &str) -> String {
+    let indent = notes
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let lines: Vec<&str> = notes.lines().map(|l| l.get(indent..).unwrap_or_else(|| l.trim_start())).collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+// The list name from a property drawer right below a top-level headline
+fn list_property(lines: &[&str]) -> Option<String> {
+    let mut lines = lines.iter().map(|l| l.trim());
+    if !lines.next()?.eq_ignore_ascii_case(":PROPERTIES:") {
+        return None;
+    }
+    lines
+        .take_while(|l| !l.eq_ignore_ascii_case(":END:"))
+        .find_map(|l| l.strip_prefix(LIST_PROPERTY))
+        .map(|name| name.trim().to_string())
+}
+
+fn is_planning(trimmed: &str) -> bool {
+    PLANNING_KEYWORDS.iter().any(|k| trimmed.starts_with(k))
+}
+
+fn is_drawer_start(trimmed: &str) -> bool {
+    trimmed.len() > 2
+        && trimmed.starts_with(':')
+        && trimmed.ends_with(':')
+        && trimmed[1..trimmed.len() - 1].chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+// Reads a "DEADLINE: <...> SCHEDULED: <...> CLOSED: [...]" line; returns false for other text
+fn apply_planning(task: &mut Task, trimmed: &str) -> bool {
+    if !is_planning(trimmed) {
+        return false;
+    }
+
+    let mut rest = trimmed;
+    while let Some((keyword, tail)) = rest.split_once(':') {
+        let tail = tail.trim_start();
+        let close = match tail.chars().next() {
+            Some('<') => '>',
+            Some('[') => ']',
+            _ => break,
+        };
+        let Some(end) = tail.find(close) else { break };
+        let stamp = parse_timestamp(&tail[1..end]);
+        rest = tail[end + 1..].trim_start();
+
+        let Some(stamp) = stamp else { continue };
+        let day = format!("{}T00:00:00Z", stamp.date.format("%Y-%m-%d"));
+        match keyword.trim() {
+            "DEADLINE" => task.due_date = Some(day),
+            "SCHEDULED" => task.start_date = Some(day),
+            "CLOSED" => {
+                let time = stamp.time.unwrap_or(NaiveTime::MIN);
+                task.updated_at = NaiveDateTime::new(stamp.date, time).and_utc().to_rfc3339();
+            }
+            _ => {}
+        }
+        if let Some(rule) = stamp.repeat {
+            // The deadline's repeater wins over the scheduled one
+            if keyword.trim() == "DEADLINE" || task.repeat_rule.is_none() {
+                task.repeat_rule = Some(rule.to_json());
+            }
+        }
+    }
+    true
+}
+
+struct OrgTimestamp {
+    date: NaiveDate,
+    time: Option<NaiveTime>,
+    repeat: Option<RepeatRule>,
+}
+
+// "2026-02-01 Sun 10:00 +1w -2d": date, optional day name, time, repeater and warning period
+fn parse_timestamp(value: &str) -> Option<OrgTimestamp> {
+    let mut parts = value.split_whitespace();
+    let date = NaiveDate::parse_from_str(parts.next()?, "%Y-%m-%d").ok()?;
+    let mut time = None;
+    let mut repeat = None;
+
+    for part in parts {
+        if let Some(t) = part.split('-').next().and_then(|t| NaiveTime::parse_from_str(t, "%H:%M").ok()) {
+            time = Some(t);
+        } else if part.starts_with('+') || part.starts_with(".+") {
+            repeat = parse_repeater(part.trim_start_matches(['.', '+']));
+        }
+    }
+
+    Some(OrgTimestamp { date, time, repeat })
+}
+
+fn parse_repeater(value: &str) -> Option<RepeatRule> {
+    let unit = value.chars().last()?;
+    let interval: u32 = value[..value.len() - unit.len_utf8()].parse().ok()?;
+    let kind = match unit {
+        'd' => RepeatKind::Daily,
+        'w' => RepeatKind::Weekly,
+        'm' => RepeatKind::Monthly,
+        'y' => RepeatKind::Yearly,
+        _ => return None,
+    };
+    let mut rule = RepeatRule::new(kind);
+    rule.interval = Some(interval).filter(|i| *i > 1);
+    Some(rule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(id: &str, name: &str) -> List {
+        List {
+            id: id.to_string(),
+            name: name.to_string(),
+            color: None,
+            icon: None,
+            is_default: false,
+            created_at: String::new(),
+            order: 0,
+        }
+    }
+
+    #[test]
+    fn notes_keep_their_indentation_and_stay_notes() {
+        let notes = [
+            "  Steps:\n1. sand\n    carefully\n\n* not a headline",
+            "SCHEDULED: when it's dry\n:NOTE:\nDEADLINE: soon",
+        ];
+        let mut planned = new_task("Paint".to_string(), false, false);
+        planned.due_date = Some("2026-11-01T00:00:00Z".to_string());
+        planned.content = Some(notes[0].to_string());
+        let mut unplanned = new_task("Varnish".to_string(), false, false);
+        unplanned.content = Some(notes[1].to_string());
+        for task in [&mut planned, &mut unplanned] {
+            task.list_id = "home".to_string();
+        }
+
+        let org = export_lists(&[list("home", "Home")], &[planned, unplanned], &HashMap::new(), &HashMap::new());
+        let sections = parse_outline(&org);
+        assert_eq!(sections.len(), 1);
+        let tasks = &sections[0].tasks;
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].task.due_date.as_deref(), Some("2026-11-01T00:00:00Z"));
+        assert_eq!(tasks[0].task.content.as_deref(), Some(notes[0]));
+        assert_eq!(tasks[1].task.due_date, None);
+        assert_eq!(tasks[1].task.start_date, None);
+        assert_eq!(tasks[1].task.content.as_deref(), Some(notes[1]));
+    }
+
+    #[test]
+    fn lists_named_like_tasks_stay_lists() {
+        let lists = [list("later", "TODO later"), list("deals", "DONE deals")];
+        let mut task = new_task("Call back".to_string(), false, false);
+        task.list_id = "later".to_string();
+
+        let org = export_lists(&lists, &[task], &HashMap::new(), &HashMap::new());
+        let sections = parse_outline(&org);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].name.as_deref(), Some("TODO later"));
+        assert_eq!(sections[0].tasks[0].task.title, "Call back");
+        assert!(parse_outline("* TODO Top-level task\n")[0].name.is_none());
+    }
+}
@@ -0,0 +1,76 @@
+// Structured form of the `repeat_rule` column.
+//
+// The frontend stores repeat rules as JSON, e.g. `{"type":"weekly","days":["Mon","Thu"]}`.
+// Older rows may only hold the bare type name (`"daily"`), which is still accepted.
+
+use serde::{Deserialize, Serialize};
+
+pub const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepeatKind {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl RepeatKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "daily" => Some(RepeatKind::Daily),
+            "weekly" => Some(RepeatKind::Weekly),
+            "monthly" => Some(RepeatKind::Monthly),
+            "yearly" => Some(RepeatKind::Yearly),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepeatRule {
+    #[serde(rename = "type")]
+    pub kind: RepeatKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days_of_month: Option<Vec<u32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub month_days: Option<Vec<u32>>,
+    // Not set by the UI yet, but kept so "every 2 weeks" survives imports and exports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<u32>,
+}
+
+impl RepeatRule {
+    pub fn new(kind: RepeatKind) -> Self {
+        RepeatRule {
+            kind,
+            days: None,
+            days_of_month: None,
+            month_days: None,
+            interval: None,
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        if let Ok(rule) = serde_json::from_str::<RepeatRule>(value) {
+            return Some(rule);
+        }
+        RepeatKind::from_name(value).map(RepeatRule::new)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn interval(&self) -> u32 {
+        self.interval.unwrap_or(1).max(1)
+    }
+}
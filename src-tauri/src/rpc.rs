@@ -0,0 +1,463 @@
+// JSON-RPC 2.0 over stdin and stdout for editor plugins, started with `itodo --rpc`.
+//
+// Each line on stdin is a request, or a batch array of them, and each reply is one line on stdout;
+// logs go to stderr. Like `itodo-cli` it works on the database of the last-used workspace, also
+// while the app is open, honours `--data-dir` and `--portable`, and unlocks an encrypted database
+// with the passphrase in ITODO_PASSPHRASE.
+//
+// Methods are the app's commands, with the same names and params. Params are passed by name, in
+// snake_case or in the camelCase the window's `invoke` uses; inputs are the app's input types:
+//
+//   {"jsonrpc": "2.0", "id": 1, "method": "create_task", "params": {"input": {"list_id": "...", "title": "Call Sam"}}}
+//
+//   Lists        get_lists, create_list {input}, update_list {input}, delete_list {id}
+//   Tasks        get_tasks {list_id?}, get_important_tasks, get_today_tasks, get_planned_tasks,
+//                get_completed_tasks, search_tasks {query}, create_task {input},
+//                update_task {input}, delete_task {id}, toggle_task_important {id},
+//                toggle_task_completed {id}
+//   Subtasks     get_subtasks {task_id}, get_all_subtasks, create_subtask {input},
+//                update_subtask {input}, delete_subtask {id}, toggle_subtask_completed {id}
+//   Tags         get_task_tags {task_id}, set_task_tags {task_id, tags}
+//   Export       export_tasks_to_file {list_id?}, export_tasks_to_path, export_tasks_to_ics, export_tasks_to_todotxt,
+//                export_tasks_to_markdown, export_tasks_to_org, export_tasks_to_taskwarrior
+//                {file_path, list_id?}; export_tasks_to_csv {file_path, list_id?, delimiter?};
+//                export_anonymized_to_path {file_path, list_id?, seed?} (returns the seed);
+//                export_tasks_to_html {file_path, list_ids?, title?}; export_bundle {file_path}
+//   Import       import_tasks {json_data}, import_tasks_from_ics {ics_data},
+//                import_tasks_from_csv {csv_data, options?}, import_tasks_from_todotxt {todo_data},
+//                import_tasks_from_markdown {markdown_data, list_id?},
+//                import_tasks_from_org {org_data, list_id?},
+//                import_tasks_from_taskwarrior {json_data}, get_importers,
+//                import_from_source {source, data}, import_bundle {file_path}
+//   Settings     get_settings, update_settings {patch}, get_backup_config,
+//                update_backup_config {config}
+//   Maintenance  list_backups, create_backup, restore_backup {id}, check_database,
+//                repair_database, create_diagnostics_bundle {file_path, include_data?},
+//                get_encryption_status, get_data_location, get_log_path
+//   Workspaces   list_workspaces, create_workspace {name}, rename_workspace {id, name},
+//                delete_workspace {id}, switch_workspace {id, passphrase?}
+//
+// switch_workspace and restore_backup swap this server's own connection; an open app window keeps
+// its own until it is switched or restored there too.
+//
+// Commands tied to the running app answer "method not found" with a note:
+//   - startup recovery: this server exits instead of starting without a database
+//   - enable_encryption, change_passphrase: they rewrite the file under the app's open connection
+//   - the app lock: it is the window's in-memory state
+//   - relocate_data_dir: it moves files the app has open
+//   - the HTTP API settings: the API server runs inside the app
+//   - get_about_info: it is read from the app bundle's resources
+//
+// The server sends one notification:
+//
+//   tasks_changed {method}   lists, tasks, subtasks or tags changed; `method` is the call that
+//                            changed them, or null when the app or another program did
+//
+// Errors from the operations themselves have code -32000 and the app's message.
+
+use crate::DbConnection;
+use rusqlite::Connection;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::io::{self, BufRead, Write};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+pub const FLAG: &str = "--rpc";
+const PASSPHRASE_VAR: &str = "ITODO_PASSPHRASE";
+// Same as itodo-cli's: an editor would rather wait for the app's write than fail
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+// How often the database is checked for changes made by other programs
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const COMMAND_ERROR: i64 = -32000;
+
+// Successful calls to these send `tasks_changed`
+const CHANGING_METHODS: [&str; 25] = [
+    "create_list",
+    "update_list",
+    "delete_list",
+    "create_task",
+    "update_task",
+    "delete_task",
+    "toggle_task_important",
+    "toggle_task_completed",
+    "create_subtask",
+    "update_subtask",
+    "delete_subtask",
+    "toggle_subtask_completed",
+    "set_task_tags",
+    "import_tasks",
+    "import_tasks_from_ics",
+    "import_tasks_from_csv",
+    "import_tasks_from_todotxt",
+    "import_tasks_from_markdown",
+    "import_tasks_from_org",
+    "import_tasks_from_taskwarrior",
+    "import_from_source",
+    "import_bundle",
+    "restore_backup",
+    "repair_database",
+    "switch_workspace",
+];
+
+const WINDOW_ONLY_METHODS: [&str; 20] = [
+    "get_startup_status",
+    "retry_open_database",
+    "restore_latest_backup",
+    "open_database_file",
+    "start_fresh_database",
+    "unlock_database",
+    "enable_encryption",
+    "change_passphrase",
+    "get_lock_status",
+    "verify_unlock",
+    "set_lock_passphrase",
+    "remove_lock_passphrase",
+    "set_lock_idle_minutes",
+    "lock_app",
+    "report_activity",
+    "relocate_data_dir",
+    "get_api_status",
+    "update_api_config",
+    "regenerate_api_token",
+    "get_about_info",
+];
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+struct Params(Map<String, Value>);
+
+impl Params {
+    // A missing param reads as null, so optional ones come out as None
+    fn get<T: DeserializeOwned>(&self, name: &str) -> Result<T, RpcError> {
+        let value = self
+            .0
+            .get(name)
+            .or_else(|| self.0.get(&camel_case(name)))
+            .cloned()
+            .unwrap_or(Value::Null);
+        serde_json::from_value(value).map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid param {}: {}", name, e)))
+    }
+}
+
+struct Server {
+    db: DbConnection,
+    out: Mutex<io::Stdout>,
+}
+
+// Serves until stdin is closed and returns the exit code
+pub fn run() -> i32 {
+    let _ = env_logger::try_init();
+    let passphrase = std::env::var(PASSPHRASE_VAR).ok();
+    let conn = match crate::open_current_database(passphrase.as_deref(), BUSY_TIMEOUT) {
+        Ok(conn) => conn,
+        Err(e) => {
+            eprintln!("itodo: {}", e);
+            return 1;
+        }
+    };
+
+    let server = Arc::new(Server {
+        db: DbConnection(Mutex::new(conn)),
+        out: Mutex::new(io::stdout()),
+    });
+    watch_changes(server.clone());
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        // Nobody is listening any more
+        if server.handle_line(&line).is_err() {
+            break;
+        }
+    }
+    0
+}
+
+// Other connections' commits change `PRAGMA data_version`; our own don't, those are reported
+// by `handle_line`
+fn watch_changes(server: Arc<Server>) {
+    std::thread::spawn(move || {
+        let mut last = server.data_version();
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let version = server.data_version();
+            if version.is_none() || version == last {
+                continue;
+            }
+            if last.is_some() && server.send(&tasks_changed(None)).is_err() {
+                break;
+            }
+            last = version;
+        }
+    });
+}
+
+impl Server {
+    fn send(&self, message: &Value) -> io::Result<()> {
+        let mut out = self.out.lock().map_err(|_| io::Error::other("stdout lock poisoned"))?;
+        writeln!(out, "{}", message)?;
+        out.flush()
+    }
+
+    fn data_version(&self) -> Option<i64> {
+        let conn = self.db.0.lock().ok()?;
+        conn.query_row("PRAGMA data_version", [], |row| row.get(0)).ok()
+    }
+
+    fn handle_line(&self, line: &str) -> io::Result<()> {
+        let mut changed = Vec::new();
+        let reply = match serde_json::from_str::<Value>(line) {
+            Err(e) => Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, format!("Parse error: {}", e)))),
+            Ok(Value::Array(batch)) if batch.is_empty() => {
+                Some(error_response(Value::Null, RpcError::new(INVALID_REQUEST, "Empty batch")))
+            }
+            Ok(Value::Array(batch)) => {
+                let replies: Vec<Value> = batch.into_iter().filter_map(|request| self.handle(request, &mut changed)).collect();
+                (!replies.is_empty()).then_some(Value::Array(replies))
+            }
+            Ok(request) => self.handle(request, &mut changed),
+        };
+
+        if let Some(reply) = reply {
+            self.send(&reply)?;
+        }
+        for method in changed {
+            self.send(&tasks_changed(Some(method)))?;
+        }
+        Ok(())
+    }
+
+    // Returns the response, or None when the request was a notification
+    fn handle(&self, request: Value, changed: &mut Vec<String>) -> Option<Value> {
+        let invalid = || Some(error_response(Value::Null, RpcError::new(INVALID_REQUEST, "Invalid request")));
+        let Value::Object(mut request) = request else { return invalid() };
+        let id = request.remove("id");
+        if !matches!(id, None | Some(Value::Null | Value::Number(_) | Value::String(_))) {
+            return invalid();
+        }
+        let method = match request.remove("method") {
+            Some(Value::String(method)) if request.get("jsonrpc").and_then(Value::as_str) == Some("2.0") => method,
+            _ => return Some(error_response(id.unwrap_or(Value::Null), RpcError::new(INVALID_REQUEST, "Invalid request"))),
+        };
+
+        let result = match request.remove("params") {
+            None | Some(Value::Null) => self.call(&method, &Params(Map::new())),
+            Some(Value::Object(params)) => self.call(&method, &Params(params)),
+            Some(_) => Err(RpcError::new(INVALID_PARAMS, "Params must be an object")),
+        };
+        if result.is_ok() && CHANGING_METHODS.contains(&method.as_str()) {
+            changed.push(method);
+        }
+
+        let id = id?;
+        Some(match result {
+            Ok(value) => json!({ "jsonrpc": "2.0", "id": id, "result": value }),
+            Err(e) => error_response(id, e),
+        })
+    }
+
+    fn call(&self, method: &str, p: &Params) -> Result<Value, RpcError> {
+        let db = &self.db;
+        match method {
+            "get_lists" => reply(crate::load_lists(&*lock(db)?)),
+            "create_list" => reply(crate::add_list(&*lock(db)?, p.get("input")?)),
+            "update_list" => reply(crate::edit_list(&*lock(db)?, p.get("input")?)),
+            "delete_list" => reply(crate::remove_list(&*lock(db)?, &p.get::<String>("id")?)),
+
+            "get_tasks" => reply(crate::load_tasks(&*lock(db)?, p.get::<Option<String>>("list_id")?.as_deref())),
+            "get_important_tasks" => reply(crate::load_important_tasks(&*lock(db)?)),
+            "get_today_tasks" => reply(crate::load_today_tasks(&*lock(db)?)),
+            "get_planned_tasks" => reply(crate::load_planned_tasks(&*lock(db)?)),
+            "get_completed_tasks" => reply(crate::load_completed_tasks(&*lock(db)?)),
+            "search_tasks" => reply(crate::find_tasks(&*lock(db)?, &p.get::<String>("query")?)),
+            "create_task" => reply(crate::add_task(&*lock(db)?, p.get("input")?)),
+            "update_task" => reply(crate::edit_task(&*lock(db)?, p.get("input")?)),
+            "delete_task" => reply(crate::remove_task(&*lock(db)?, &p.get::<String>("id")?)),
+            "toggle_task_important" => reply(crate::toggle_important(&*lock(db)?, &p.get::<String>("id")?)),
+            "toggle_task_completed" => reply(crate::toggle_completed(&*lock(db)?, &p.get::<String>("id")?)),
+
+            "get_subtasks" => reply(crate::load_task_subtasks(&*lock(db)?, &p.get::<String>("task_id")?)),
+            "get_all_subtasks" => reply(crate::load_all_subtasks(&*lock(db)?)),
+            "create_subtask" => reply(crate::add_subtask(&*lock(db)?, p.get("input")?)),
+            "update_subtask" => reply(crate::edit_subtask(&*lock(db)?, p.get("input")?)),
+            "delete_subtask" => reply(crate::remove_subtask(&*lock(db)?, &p.get::<String>("id")?)),
+            "toggle_subtask_completed" => reply(crate::toggle_subtask(&*lock(db)?, &p.get::<String>("id")?)),
+
+            "get_task_tags" => reply(crate::load_tags(&*lock(db)?, &p.get::<String>("task_id")?)),
+            "set_task_tags" => {
+                let tags: Vec<String> = p.get("tags")?;
+                reply(crate::store_tags(&*lock(db)?, &p.get::<String>("task_id")?, &tags))
+            }
+
+            "export_tasks_to_file" => {
+                reply(crate::write_export_to_data_dir(&*lock(db)?, p.get::<Option<String>>("list_id")?.as_deref()))
+            }
+            "export_tasks_to_path" => reply(crate::write_json_export(
+                &*lock(db)?,
+                &p.get::<String>("file_path")?,
+                p.get::<Option<String>>("list_id")?.as_deref(),
+            )),
+            "export_anonymized_to_path" => reply(crate::write_anonymized_export(
+                &*lock(db)?,
+                &p.get::<String>("file_path")?,
+                p.get::<Option<String>>("list_id")?.as_deref(),
+                p.get::<Option<String>>("seed")?.as_deref(),
+            )),
+            "export_tasks_to_ics" => reply(crate::write_ics_export(
+                &*lock(db)?,
+                &p.get::<String>("file_path")?,
+                p.get::<Option<String>>("list_id")?.as_deref(),
+            )),
+            "export_tasks_to_csv" => reply(crate::write_csv_export(
+                &*lock(db)?,
+                &p.get::<String>("file_path")?,
+                p.get::<Option<String>>("list_id")?.as_deref(),
+                p.get::<Option<String>>("delimiter")?.as_deref(),
+            )),
+            "export_tasks_to_todotxt" => reply(crate::write_todotxt_export(
+                &*lock(db)?,
+                &p.get::<String>("file_path")?,
+                p.get::<Option<String>>("list_id")?.as_deref(),
+            )),
+            "export_tasks_to_markdown" => reply(crate::write_markdown_export(
+                &*lock(db)?,
+                &p.get::<String>("file_path")?,
+                p.get::<Option<String>>("list_id")?.as_deref(),
+            )),
+            "export_tasks_to_org" => reply(crate::write_org_export(
+                &*lock(db)?,
+                &p.get::<String>("file_path")?,
+                p.get::<Option<String>>("list_id")?.as_deref(),
+            )),
+            "export_tasks_to_taskwarrior" => reply(crate::write_taskwarrior_export(
+                &*lock(db)?,
+                &p.get::<String>("file_path")?,
+                p.get::<Option<String>>("list_id")?.as_deref(),
+            )),
+            "export_tasks_to_html" => reply(crate::write_html_report(
+                &*lock(db)?,
+                &p.get::<String>("file_path")?,
+                p.get::<Option<Vec<String>>>("list_ids")?.as_deref(),
+                p.get::<Option<String>>("title")?.as_deref(),
+            )),
+            "export_bundle" => reply(crate::write_bundle_export(&*lock(db)?, &p.get::<String>("file_path")?)),
+
+            "import_tasks" => reply(crate::import_json(&*lock(db)?, &p.get::<String>("json_data")?)),
+            "import_tasks_from_ics" => reply(crate::import_ics(&*lock(db)?, &p.get::<String>("ics_data")?)),
+            "import_tasks_from_csv" => {
+                reply(crate::import_csv(&*lock(db)?, &p.get::<String>("csv_data")?, p.get("options")?))
+            }
+            "import_tasks_from_todotxt" => reply(crate::import_todotxt(&*lock(db)?, &p.get::<String>("todo_data")?)),
+            "import_tasks_from_markdown" => reply(crate::import_markdown(
+                &*lock(db)?,
+                &p.get::<String>("markdown_data")?,
+                p.get::<Option<String>>("list_id")?.as_deref(),
+            )),
+            "import_tasks_from_org" => reply(crate::import_org(
+                &*lock(db)?,
+                &p.get::<String>("org_data")?,
+                p.get::<Option<String>>("list_id")?.as_deref(),
+            )),
+            "import_tasks_from_taskwarrior" => {
+                reply(crate::import_taskwarrior(&*lock(db)?, &p.get::<String>("json_data")?))
+            }
+            "get_importers" => reply(Ok(crate::get_importers())),
+            "import_from_source" => reply(crate::import_with_importer(
+                &*lock(db)?,
+                &p.get::<String>("source")?,
+                &p.get::<String>("data")?,
+            )),
+            "import_bundle" => reply(crate::import_bundle_file(&*lock(db)?, &p.get::<String>("file_path")?)),
+
+            "get_settings" => reply(Ok(crate::get_settings())),
+            "update_settings" => reply(crate::patch_settings(&*lock(db)?, &p.get::<Value>("patch")?)),
+            "get_backup_config" => reply(Ok(crate::get_backup_config())),
+            "update_backup_config" => reply(crate::save_backup_config(&*lock(db)?, p.get("config")?)),
+            "list_backups" => reply(crate::list_current_backups(&*lock(db)?)),
+            "create_backup" => reply(crate::backup_now(&*lock(db)?)),
+            "restore_backup" => reply(crate::restore_from_backup(&mut *lock(db)?, &p.get::<String>("id")?)),
+            "check_database" => reply(crate::run_integrity_check(&*lock(db)?)),
+            "repair_database" => reply(crate::run_repair(&*lock(db)?)),
+            "create_diagnostics_bundle" => reply(crate::write_diagnostics_bundle(
+                &*lock(db)?,
+                &p.get::<String>("file_path")?,
+                p.get::<Option<bool>>("include_data")?.unwrap_or(false),
+                crate::load_about_config(),
+                None,
+            )),
+            "get_encryption_status" => reply(crate::encryption_status(&*lock(db)?)),
+            "get_data_location" => reply(Ok(crate::data_location_info(&*lock(db)?))),
+            "get_log_path" => reply(Ok(crate::get_log_path())),
+
+            "list_workspaces" => reply(Ok(crate::list_workspaces())),
+            "create_workspace" => reply(crate::add_workspace(p.get("name")?)),
+            "rename_workspace" => reply(crate::rename_workspace(p.get("id")?, p.get("name")?)),
+            "delete_workspace" => reply(crate::remove_workspace(p.get("id")?)),
+            "switch_workspace" => reply(crate::open_workspace(
+                &mut *lock(db)?,
+                &p.get::<String>("id")?,
+                p.get::<Option<String>>("passphrase")?.as_deref(),
+            )),
+
+            _ if WINDOW_ONLY_METHODS.contains(&method) => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("{} is only available in the app window", method),
+            )),
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        }
+    }
+}
+
+fn lock(db: &DbConnection) -> Result<MutexGuard<'_, Connection>, RpcError> {
+    db.0.lock().map_err(|e| RpcError::new(COMMAND_ERROR, e.to_string()))
+}
+
+fn reply<T: Serialize>(result: Result<T, String>) -> Result<Value, RpcError> {
+    let value = result.map_err(|e| RpcError::new(COMMAND_ERROR, e))?;
+    serde_json::to_value(value).map_err(|e| RpcError::new(COMMAND_ERROR, e.to_string()))
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": error.code, "message": error.message } })
+}
+
+fn tasks_changed(method: Option<String>) -> Value {
+    json!({ "jsonrpc": "2.0", "method": "tasks_changed", "params": { "method": method } })
+}
+
+fn camel_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for ch in name.chars() {
+        if ch == '_' {
+            upper = true;
+        } else if upper {
+            out.push(ch.to_ascii_uppercase());
+            upper = false;
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
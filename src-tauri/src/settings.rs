@@ -0,0 +1,162 @@
+// App-wide settings, kept in `settings.toml` in the data folder and shared by all workspaces.
+//
+// Missing keys take their defaults, so files written by older versions keep loading. Updates are
+// partial JSON objects merged over the current settings, then validated as a whole. A file that
+// can't be parsed is renamed before the next save, so hand edits gone wrong aren't lost.
+
+use crate::backup::BackupConfig;
+use crate::i18n::t;
+use serde::{Deserialize, Serialize};
+use log::warn;
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+    System,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn filter(self) -> log::LevelFilter {
+        match self {
+            LogLevel::Off => log::LevelFilter::Off,
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub language: Language,
+    pub theme: Theme,
+    // Level for the log files in the `logs` folder
+    pub log_level: LogLevel,
+    pub backup: BackupConfig,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            language: Language::ZhCn,
+            theme: Theme::Light,
+            log_level: LogLevel::Info,
+            backup: BackupConfig::default(),
+        }
+    }
+}
+
+impl Settings {
+    // Ok(None) when there is no file yet
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+        toml::from_str(&content).map(Some).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if Settings::load(path).is_err() {
+            let moved = move_aside(path)?;
+            warn!("Moved unreadable settings to {:?} before saving new ones", moved);
+        }
+        let content = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| t!("Failed to save settings: {}", e))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.backup.validate()
+    }
+
+    // Applies a partial update such as `{"theme": "dark"}`; unknown keys are rejected so typos
+    // don't go unnoticed
+    pub fn merged(&self, patch: &Value) -> Result<Settings, String> {
+        let mut value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        merge(&mut value, patch, "")?;
+        let settings: Settings = serde_json::from_value(value).map_err(|e| t!("Invalid settings: {}", e))?;
+        settings.validate()?;
+        Ok(settings)
+    }
+}
+
+fn merge(target: &mut Value, patch: &Value, prefix: &str) -> Result<(), String> {
+    let (Some(target), Some(patch)) = (target.as_object_mut(), patch.as_object()) else {
+        return Err(t!("Expected an object for \"{}\"", prefix));
+    };
+    for (key, value) in patch {
+        let name = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match target.get_mut(key) {
+            Some(existing) if existing.is_object() => merge(existing, value, &name)?,
+            Some(existing) => *existing = value.clone(),
+            None => return Err(t!("Unknown setting: {}", name)),
+        }
+    }
+    Ok(())
+}
+
+// Keeps a file that can't be read next to where it was, for whoever wants to recover it by hand
+pub(crate) fn move_aside(path: &Path) -> Result<PathBuf, String> {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let target = path.with_file_name(format!("{}.broken-{}", file_name, stamp));
+    fs::rename(path, &target).map_err(|e| t!("Failed to move {} aside: {}", path.display(), e))?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_an_unreadable_file_when_saving() {
+        let dir = std::env::temp_dir().join(format!("itodo-settings-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.toml");
+        assert_eq!(Settings::load(&path).map(|s| s.is_none()), Ok(true));
+
+        fs::write(&path, "theme = \"dark\"\nlanguage = ").unwrap();
+        assert!(Settings::load(&path).is_err());
+
+        Settings::default().save(&path).unwrap();
+        assert!(Settings::load(&path).unwrap().is_some());
+        let moved: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("settings.toml.broken-"))
+            .collect();
+        assert_eq!(moved.len(), 1);
+        assert_eq!(fs::read_to_string(dir.join(&moved[0])).unwrap(), "theme = \"dark\"\nlanguage = ");
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,322 @@
+// Taskwarrior JSON, as produced by `task export` and accepted by `task import`.
+//
+// Task UUIDs are carried over in both directions, so exchanging the same tasks repeatedly
+// updates them instead of creating duplicates.
+
+use crate::i18n::t;
+use crate::repeat::{RepeatKind, RepeatRule, WEEKDAYS};
+use crate::{List, Task};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const TW_DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TwTask {
+    #[serde(default)]
+    uuid: Option<String>,
+    description: String,
+    #[serde(default = "pending")]
+    status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entry: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scheduled: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wait: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    annotations: Vec<TwAnnotation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recur: Option<String>,
+    // Set on the instances Taskwarrior generates from a recurring template
+    #[serde(default, skip_serializing)]
+    parent: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TwAnnotation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entry: Option<String>,
+    description: String,
+}
+
+fn pending() -> String {
+    "pending".to_string()
+}
+
+pub struct TaskwarriorItem {
+    pub list_name: Option<String>,
+    pub tags: Vec<String>,
+    // Deleted in Taskwarrior; the matching iToDo task should be removed
+    pub deleted: bool,
+    pub task: Task,
+}
+
+// ============== Export ==============
+
+pub fn export_tasks(tasks: &[Task], lists: &[List], tags: &HashMap<String, Vec<String>>) -> Result<String, String> {
+    let lists: HashMap<&str, &List> = lists.iter().map(|l| (l.id.as_str(), l)).collect();
+
+    let exported: Vec<TwTask> = tasks
+        .iter()
+        .map(|task| {
+            let modified = to_tw_date(&task.updated_at);
+            let noted = DateTime::parse_from_rfc3339(&task.updated_at).map_or_else(|_| Utc::now(), |dt| dt.with_timezone(&Utc));
+            let due = task.due_date.as_deref().and_then(to_tw_date);
+            // Taskwarrior only accepts recurrence on tasks that have a due date
+            let recur = due
+                .as_ref()
+                .and(task.repeat_rule.as_deref())
+                .and_then(RepeatRule::parse)
+                .map(|r| to_recur(&r));
+            let status = match (task.is_completed, &recur) {
+                (true, _) => "completed",
+                (false, Some(_)) => "recurring",
+                (false, None) => "pending",
+            };
+
+            TwTask {
+                uuid: Some(task.id.clone()),
+                description: task.title.clone(),
+                status: status.to_string(),
+                entry: to_tw_date(&task.created_at),
+                end: modified.clone().filter(|_| task.is_completed),
+                modified: modified.clone(),
+                due,
+                scheduled: task.start_date.as_deref().and_then(to_tw_date),
+                wait: task.remind_time.as_deref().and_then(to_tw_date),
+                project: lists
+                    .get(task.list_id.as_str())
+                    .filter(|l| !l.is_default)
+                    .map(|l| l.name.clone()),
+                tags: tags.get(&task.id).cloned().unwrap_or_default(),
+                priority: if task.is_important { Some("H".to_string()) } else { None },
+                annotations: task
+                    .content
+                    .as_deref()
+                    .unwrap_or("")
+                    .lines()
+                    .filter(|l| !l.trim().is_empty())
+                    .enumerate()
+                    // Taskwarrior keys annotations by their time, so each line needs its own second
+                    .map(|(i, l)| TwAnnotation {
+                        entry: Some((noted + Duration::seconds(i as i64)).format(TW_DATE_FORMAT).to_string()),
+                        description: l.to_string(),
+                    })
+                    .collect(),
+                recur,
+                parent: None,
+            }
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&exported).map_err(|e| e.to_string())
+}
+
+fn to_recur(rule: &RepeatRule) -> String {
+    let interval = rule.interval();
+    let weekdays = ["Mon", "Tue", "Wed", "Thu", "Fri"];
+    match rule.kind {
+        RepeatKind::Weekly
+            if interval == 1
+                && rule.days.as_ref().is_some_and(|d| d.len() == 5 && weekdays.iter().all(|w| d.iter().any(|x| x == w))) =>
+        {
+            "weekdays".to_string()
+        }
+        RepeatKind::Daily if interval == 1 => "daily".to_string(),
+        RepeatKind::Weekly if interval == 1 => "weekly".to_string(),
+        RepeatKind::Monthly if interval == 1 => "monthly".to_string(),
+        RepeatKind::Yearly if interval == 1 => "yearly".to_string(),
+        RepeatKind::Daily => format!("{}d", interval),
+        RepeatKind::Weekly => format!("{}w", interval),
+        RepeatKind::Monthly => format!("{}mo", interval),
+        RepeatKind::Yearly => format!("{}y", interval),
+    }
+}
+
+fn to_tw_date(value: &str) -> Option<String> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc).format(TW_DATE_FORMAT).to_string())
+}
+
+// ============== Import ==============
+
+pub fn parse_tasks(data: &str) -> Result<Vec<TaskwarriorItem>, String> {
+    let data = data.trim();
+    // `task export` writes a JSON array; Taskwarrior 2.5 and older wrote one object per line
+    let tasks: Vec<TwTask> = if data.starts_with('[') {
+        serde_json::from_str(data).map_err(|e| t!("Failed to parse Taskwarrior data: {}", e))?
+    } else {
+        data.lines()
+            .map(|l| l.trim().trim_end_matches(','))
+            .filter(|l| !l.is_empty())
+            .enumerate()
+            .map(|(i, l)| {
+                serde_json::from_str(l).map_err(|e| t!("Failed to parse Taskwarrior data on line {}: {}", i + 1, e))
+            })
+            .collect::<Result<_, _>>()?
+    };
+
+    Ok(tasks
+        .into_iter()
+        // Generated recurrence instances are covered by their template
+        .filter(|t| t.parent.is_none())
+        .filter(|t| !t.description.trim().is_empty())
+        .map(convert_task)
+        .collect())
+}
+
+fn convert_task(mut source: TwTask) -> TaskwarriorItem {
+    let now = Utc::now().to_rfc3339();
+    let id = source
+        .uuid
+        .as_deref()
+        .and_then(|u| Uuid::parse_str(u).ok())
+        .unwrap_or_else(Uuid::new_v4)
+        .to_string();
+    let is_completed = source.status == "completed";
+    let created_at = source.entry.as_deref().and_then(from_tw_date).unwrap_or_else(|| now.clone());
+    let updated_at = source
+        .end
+        .as_deref()
+        .filter(|_| is_completed)
+        .or(source.modified.as_deref())
+        .and_then(from_tw_date)
+        .unwrap_or(now);
+
+    // Same-format timestamps sort by time; the sort is stable for annotations without one
+    source.annotations.sort_by(|a, b| a.entry.cmp(&b.entry));
+    let notes: Vec<&str> = source.annotations.iter().map(|a| a.description.as_str()).collect();
+
+    TaskwarriorItem {
+        list_name: source.project.clone().filter(|p| !p.trim().is_empty()),
+        tags: source.tags.clone(),
+        deleted: source.status == "deleted",
+        task: Task {
+            id,
+            title: source.description.trim().to_string(),
+            content: if notes.is_empty() { None } else { Some(notes.join("\n")) },
+            is_completed,
+            is_important: source.priority.as_deref() == Some("H"),
+            due_date: source.due.as_deref().and_then(from_tw_date_stored),
+            start_date: source.scheduled.as_deref().and_then(from_tw_date_stored),
+            remind_time: source.wait.as_deref().and_then(from_tw_date_stored),
+            repeat_rule: source.recur.as_deref().and_then(parse_recur).map(|r| r.to_json()),
+            list_id: String::new(),
+            created_at,
+            updated_at,
+        },
+    }
+}
+
+fn parse_tw_date(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value, TW_DATE_FORMAT) {
+        return Some(naive.and_utc());
+    }
+    DateTime::parse_from_rfc3339(value).ok().map(|dt| dt.with_timezone(&Utc))
+}
+
+fn from_tw_date(value: &str) -> Option<String> {
+    parse_tw_date(value).map(|dt| dt.to_rfc3339())
+}
+
+// Task dates use the same "YYYY-MM-DDTHH:MM:SSZ" form the UI writes
+fn from_tw_date_stored(value: &str) -> Option<String> {
+    parse_tw_date(value).map(|dt| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+}
+
+// Taskwarrior recurrence periods: named ones ("weekly", "biweekly", "weekdays", ...),
+// counted ones ("2d", "3weeks", "6mo") and ISO 8601 ("P2W")
+fn parse_recur(value: &str) -> Option<RepeatRule> {
+    let value = value.trim().to_ascii_lowercase();
+    let named = |kind: RepeatKind, interval: u32| {
+        let mut rule = RepeatRule::new(kind);
+        rule.interval = Some(interval).filter(|i| *i > 1);
+        Some(rule)
+    };
+
+    match value.as_str() {
+        "daily" | "day" => return named(RepeatKind::Daily, 1),
+        "weekly" | "week" => return named(RepeatKind::Weekly, 1),
+        "biweekly" | "fortnight" => return named(RepeatKind::Weekly, 2),
+        "monthly" | "month" => return named(RepeatKind::Monthly, 1),
+        "bimonthly" => return named(RepeatKind::Monthly, 2),
+        "quarterly" => return named(RepeatKind::Monthly, 3),
+        "semiannual" => return named(RepeatKind::Monthly, 6),
+        "yearly" | "annual" | "year" => return named(RepeatKind::Yearly, 1),
+        "biannual" | "biyearly" => return named(RepeatKind::Yearly, 2),
+        "weekdays" => {
+            let mut rule = RepeatRule::new(RepeatKind::Weekly);
+            rule.days = Some(WEEKDAYS[1..6].iter().map(|d| d.to_string()).collect());
+            return Some(rule);
+        }
+        _ => {}
+    }
+
+    let counted = value.strip_prefix('p').unwrap_or(&value);
+    let digits: String = counted.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let interval: u32 = if digits.is_empty() { 1 } else { digits.parse().ok()? };
+    let kind = match counted[digits.len()..].trim() {
+        "d" | "day" | "days" => RepeatKind::Daily,
+        "w" | "wk" | "wks" | "week" | "weeks" => RepeatKind::Weekly,
+        "mo" | "mos" | "m" | "month" | "months" => RepeatKind::Monthly,
+        "q" | "qtr" | "qtrs" | "quarter" | "quarters" => return named(RepeatKind::Monthly, interval.checked_mul(3)?),
+        "y" | "yr" | "yrs" | "year" | "years" => RepeatKind::Yearly,
+        _ => return None,
+    };
+    named(kind, interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_counted_periods() {
+        let rule = parse_recur("2qtrs").unwrap();
+        assert_eq!((rule.kind, rule.interval), (RepeatKind::Monthly, Some(6)));
+        assert_eq!(parse_recur("P3W").unwrap().interval, Some(3));
+        assert!(parse_recur("4000000000q").is_none());
+        assert!(parse_recur("99999999999d").is_none());
+    }
+
+    #[test]
+    fn keeps_every_line_of_a_note() {
+        let list = List {
+            id: "list".to_string(),
+            name: "Home".to_string(),
+            color: None,
+            icon: None,
+            is_default: true,
+            created_at: String::new(),
+            order: 0,
+        };
+        let mut task = convert_task(serde_json::from_str(r#"{"description": "Paint the shed"}"#).unwrap()).task;
+        task.list_id = list.id.clone();
+        task.content = Some("Buy primer\nSand the door\nTwo coats".to_string());
+
+        let exported = export_tasks(&[task], &[list], &HashMap::new()).unwrap();
+        let tasks: Vec<TwTask> = serde_json::from_str(&exported).unwrap();
+        let mut entries: Vec<_> = tasks[0].annotations.iter().map(|a| a.entry.clone()).collect();
+        entries.dedup();
+        assert_eq!(entries.len(), 3);
+
+        let imported = parse_tasks(&exported).unwrap();
+        assert_eq!(imported[0].task.content.as_deref(), Some("Buy primer\nSand the door\nTwo coats"));
+    }
+}
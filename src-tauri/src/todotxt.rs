@@ -0,0 +1,341 @@
+// todo.txt (https://github.com/todotxt/todo.txt) export and import.
+//
+// `+project` maps to the task's list, `@context` to tags, `(A)` to important and `due:` to the
+// due date. Tokens in the middle of a description also stay in the title, so it reads as written;
+// only the ones after the last plain word are taken out. Task notes travel as one `note:`
+// extension with whitespace percent-encoded. Anything else iToDo has no field for (other
+// priorities, extra projects, unknown extensions) is kept on a trailing "todo.txt:" line in the
+// notes and written back out on export, so files survive a round trip.
+
+use crate::{List, Task};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const EXTRAS_PREFIX: &str = "todo.txt:";
+const NOTE_KEY: &str = "note:";
+
+pub struct TodoTxtItem {
+    pub list_name: Option<String>,
+    pub tags: Vec<String>,
+    pub task: Task,
+}
+
+// ============== Export ==============
+
+pub fn export_tasks(tasks: &[Task], lists: &[List], tags: &HashMap<String, Vec<String>>) -> String {
+    let lists: HashMap<&str, &List> = lists.iter().map(|l| (l.id.as_str(), l)).collect();
+    let mut out = String::new();
+
+    for task in tasks {
+        let (notes, extras) = split_notes(task.content.as_deref().unwrap_or(""));
+        let mut extras: Vec<&str> = extras.split_whitespace().collect();
+        let mut parts: Vec<String> = Vec::new();
+
+        // Other priorities are carried through the notes as "pri:X"
+        let mut priority = if task.is_important { Some("A".to_string()) } else { None };
+        if let Some(pos) = extras.iter().position(|e| e.starts_with("pri:")) {
+            let kept = extras.remove(pos)[4..].to_string();
+            priority = priority.or(Some(kept));
+        }
+
+        if task.is_completed {
+            parts.push("x".to_string());
+            parts.push(date_part(&task.updated_at).unwrap_or_else(|| Utc::now().format("%Y-%m-%d").to_string()));
+            if let Some(created) = date_part(&task.created_at) {
+                parts.push(created);
+            }
+        } else {
+            if let Some(p) = &priority {
+                parts.push(format!("({})", p));
+            }
+            if let Some(created) = date_part(&task.created_at) {
+                parts.push(created);
+            }
+        }
+
+        let title = task.title.replace(['\r', '\n'], " ");
+        // Projects and contexts already in the title aren't repeated
+        let in_title = |word: &str| title.split_whitespace().any(|w| w == word);
+        let mut mentions = Vec::new();
+        if let Some(list) = lists.get(task.list_id.as_str()).filter(|l| !l.is_default) {
+            mentions.push(format!("+{}", token(&list.name)));
+        }
+        if let Some(task_tags) = tags.get(&task.id) {
+            mentions.extend(task_tags.iter().map(|t| format!("@{}", token(t))));
+        }
+        mentions.retain(|m| !in_title(m));
+        parts.push(title);
+        parts.extend(mentions);
+
+        if let Some(due) = task.due_date.as_deref().and_then(date_part) {
+            parts.push(format!("due:{}", due));
+        }
+        if task.is_completed {
+            if let Some(p) = &priority {
+                parts.push(format!("pri:{}", p));
+            }
+        }
+        if !notes.is_empty() {
+            parts.push(format!("{}{}", NOTE_KEY, encode_note(notes)));
+        }
+        parts.extend(extras.iter().map(|e| e.to_string()));
+
+        out.push_str(&parts.join(" "));
+        out.push('\n');
+    }
+
+    out
+}
+
+// Project and context names can't contain whitespace
+fn token(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+fn date_part(value: &str) -> Option<String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc).format("%Y-%m-%d").to_string());
+    }
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d")
+        .ok()
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+// Splits task notes into the user's text and the preserved todo.txt extras on their last line
+fn split_notes(content: &str) -> (&str, &str) {
+    match content.rfind(EXTRAS_PREFIX) {
+        Some(pos) if (pos == 0 || content[..pos].ends_with('\n')) && !content[pos..].contains('\n') => {
+            (&content[..pos.saturating_sub(1)], content[pos + EXTRAS_PREFIX.len()..].trim())
+        }
+        _ => (content, ""),
+    }
+}
+
+// Whitespace would end the token and '%' starts an escape, so both are written as %XX
+fn encode_note(notes: &str) -> String {
+    let mut out = String::new();
+    for c in notes.chars() {
+        if c == '%' || c.is_whitespace() || c.is_control() {
+            let mut buf = [0; 4];
+            for byte in c.encode_utf8(&mut buf).bytes() {
+                out.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn decode_note(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = value.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(h, 16).ok());
+        match escaped {
+            Some(byte) if bytes[i] == b'%' => {
+                out.push(byte);
+                i += 3;
+            }
+            _ => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+// ============== Import ==============
+
+pub fn parse_tasks(data: &str) -> Vec<TodoTxtItem> {
+    data.lines().filter_map(parse_line).collect()
+}
+
+fn parse_line(line: &str) -> Option<TodoTxtItem> {
+    let mut tokens = line.split_whitespace().peekable();
+    tokens.peek()?;
+
+    let mut is_completed = false;
+    let mut completed_on = None;
+    let mut priority = None;
+
+    if tokens.peek() == Some(&"x") {
+        tokens.next();
+        is_completed = true;
+        completed_on = tokens.next_if(|t| parse_date(t).is_some()).and_then(parse_date);
+    } else if let Some(p) = tokens.peek().and_then(|t| parse_priority(t)) {
+        tokens.next();
+        priority = Some(p);
+    }
+    let created_on = tokens.next_if(|t| parse_date(t).is_some()).and_then(parse_date);
+
+    let mut words = Vec::new();
+    let mut list_name = None;
+    let mut tags = Vec::new();
+    let mut notes = None;
+    let mut due_date = None;
+
+    for word in tokens {
+        if let Some(project) = word.strip_prefix('+').filter(|p| !p.is_empty()) {
+            list_name.get_or_insert_with(|| project.to_string());
+        } else if let Some(context) = word.strip_prefix('@').filter(|c| !c.is_empty()) {
+            if !tags.iter().any(|t| t == context) {
+                tags.push(context.to_string());
+            }
+        } else if let Some(note) = word.strip_prefix(NOTE_KEY).filter(|_| notes.is_none()) {
+            notes = Some(decode_note(note));
+            continue;
+        } else if let Some((key, value)) = split_extension(word) {
+            match key {
+                "due" if due_date.is_none() && parse_date(value).is_some() => {
+                    due_date = parse_date(value);
+                    continue;
+                }
+                // Completed tasks conventionally keep their priority as "pri:X"
+                "pri" if priority.is_none() && value.len() == 1 => {
+                    priority = value.chars().next();
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        words.push(word);
+    }
+
+    // Tokens after the last plain word are metadata rather than part of the text
+    let mut extras = Vec::new();
+    while let Some(word) = words.pop_if(|w| is_metadata(w)) {
+        let is_list = word.strip_prefix('+') == list_name.as_deref();
+        if !is_list && !word.starts_with('@') {
+            extras.insert(0, word.to_string());
+        }
+    }
+
+    let title = words.join(" ");
+    if title.is_empty() {
+        return None;
+    }
+
+    let is_important = priority == Some('A');
+    if let Some(p) = priority.filter(|p| *p != 'A') {
+        extras.insert(0, format!("pri:{}", p));
+    }
+    let extras = Some(extras).filter(|e| !e.is_empty()).map(|e| format!("{} {}", EXTRAS_PREFIX, e.join(" ")));
+    let content = match (notes.filter(|n| !n.is_empty()), extras) {
+        (Some(notes), Some(extras)) => Some(format!("{}\n{}", notes, extras)),
+        (notes, extras) => notes.or(extras),
+    };
+
+    let now = Utc::now().to_rfc3339();
+    let stored = |d: NaiveDate| d.and_time(NaiveTime::MIN).and_utc().to_rfc3339();
+    let created_at = created_on.map(stored).unwrap_or_else(|| now.clone());
+    let updated_at = completed_on.filter(|_| is_completed).map(stored).unwrap_or(now);
+
+    Some(TodoTxtItem {
+        list_name,
+        tags,
+        task: Task {
+            id: Uuid::new_v4().to_string(),
+            title,
+            content,
+            is_completed,
+            is_important,
+            due_date: due_date.map(|d| format!("{}T00:00:00Z", d.format("%Y-%m-%d"))),
+            start_date: None,
+            remind_time: None,
+            repeat_rule: None,
+            list_id: String::new(),
+            created_at,
+            updated_at,
+        },
+    })
+}
+
+fn is_metadata(word: &str) -> bool {
+    word.len() > 1 && (word.starts_with('+') || word.starts_with('@')) || split_extension(word).is_some()
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    if value.len() != 10 {
+        return None;
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+fn parse_priority(value: &str) -> Option<char> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next(), chars.next(), chars.next()) {
+        (Some('('), Some(p), Some(')'), None) if p.is_ascii_uppercase() => Some(p),
+        _ => None,
+    }
+}
+
+// "key:value" where the key starts with a letter and the value isn't empty; times like "9:30" and
+// URLs like "https://..." are left in the title
+fn split_extension(word: &str) -> Option<(&str, &str)> {
+    let (key, value) = word.split_once(':')?;
+    let is_key = key.starts_with(|c: char| c.is_alphabetic());
+    if !is_key || value.is_empty() || value.starts_with("//") || value.contains(':') {
+        return None;
+    }
+    Some((key, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(name: &str) -> List {
+        List {
+            id: "list".to_string(),
+            name: name.to_string(),
+            color: None,
+            icon: None,
+            is_default: false,
+            created_at: String::new(),
+            order: 0,
+        }
+    }
+
+    fn round_trip(line: &str) -> (String, TodoTxtItem) {
+        let item = parse_tasks(line).pop().unwrap();
+        let mut task = item.task;
+        task.list_id = "list".to_string();
+        let list = project(item.list_name.as_deref().unwrap_or("Inbox"));
+        let tags = HashMap::from([(task.id.clone(), item.tags)]);
+        let exported = export_tasks(&[task], &[list], &tags);
+        let again = parse_tasks(&exported).pop().unwrap();
+        (exported, again)
+    }
+
+    #[test]
+    fn keeps_projects_and_contexts_in_place() {
+        let line = "(B) 2026-10-01 Call +family about @phone plans at 9:30 due:2026-10-20 see:wiki +later\n";
+        let (exported, item) = round_trip(line);
+        assert_eq!(exported, line);
+        assert_eq!(item.task.title, "Call +family about @phone plans at 9:30");
+        assert_eq!(item.list_name.as_deref(), Some("family"));
+        assert_eq!(item.tags, vec!["phone"]);
+        assert_eq!(item.task.due_date.as_deref(), Some("2026-10-20T00:00:00Z"));
+        assert_eq!(item.task.content.as_deref(), Some("todo.txt: pri:B see:wiki +later"));
+    }
+
+    #[test]
+    fn carries_notes_through_the_note_extension() {
+        let notes = "Buy primer first\n  50% off:  at the corner shop\u{3000}today";
+        let mut task = parse_tasks("x 2026-10-02 2026-10-01 Paint the hall pri:C").pop().unwrap().task;
+        task.content = Some(format!("{}\n{}", notes, task.content.unwrap()));
+        task.list_id = "list".to_string();
+
+        let exported = export_tasks(&[task], &[project("Home")], &HashMap::new());
+        assert_eq!(exported.lines().count(), 1);
+        let item = parse_tasks(&exported).pop().unwrap();
+        assert_eq!(item.task.title, "Paint the hall");
+        assert!(item.task.is_completed);
+        assert_eq!(item.list_name.as_deref(), Some("Home"));
+        assert_eq!(item.task.content, Some(format!("{}\ntodo.txt: pri:C", notes)));
+    }
+}
@@ -0,0 +1,225 @@
+// Workspaces: separate databases for separate areas of life, e.g. personal and work.
+//
+// The default workspace keeps using `itodo.db` in the data folder, so existing data needs no
+// migration. Other workspaces live in `workspaces/<id>.db`. The registry (`workspaces.json`)
+// holds their names and which one was used last. A registry that can't be read is moved aside
+// before the next save, instead of being written over.
+
+use crate::i18n::t;
+use chrono::Utc;
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+pub const DEFAULT_ID: &str = "default";
+const REGISTRY_FILE: &str = "workspaces.json";
+const WORKSPACE_DIR: &str = "workspaces";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+}
+
+impl Workspace {
+    pub fn is_default(&self) -> bool {
+        self.id == DEFAULT_ID
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceRegistry {
+    pub last_used: String,
+    pub workspaces: Vec<Workspace>,
+}
+
+impl WorkspaceRegistry {
+    pub fn load(data_dir: &Path) -> Self {
+        let empty = || WorkspaceRegistry {
+            last_used: DEFAULT_ID.to_string(),
+            workspaces: Vec::new(),
+        };
+        let mut registry = match read(data_dir) {
+            Ok(Some(registry)) => registry,
+            Ok(None) => empty(),
+            Err(e) => {
+                error!("Failed to read {}, only the default workspace is available: {}", REGISTRY_FILE, e);
+                empty()
+            }
+        };
+
+        if !registry.workspaces.iter().any(Workspace::is_default) {
+            registry.workspaces.insert(
+                0,
+                Workspace {
+                    id: DEFAULT_ID.to_string(),
+                    name: "Default".to_string(),
+                    created_at: Utc::now().to_rfc3339(),
+                },
+            );
+        }
+        if registry.find(&registry.last_used).is_none() {
+            registry.last_used = DEFAULT_ID.to_string();
+        }
+        registry
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        if read(data_dir).is_err() {
+            let moved = crate::settings::move_aside(&data_dir.join(REGISTRY_FILE))?;
+            warn!("Moved unreadable workspace registry to {:?} before saving a new one", moved);
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(data_dir.join(REGISTRY_FILE), content).map_err(|e| t!("Failed to save workspaces: {}", e))
+    }
+
+    pub fn find(&self, id: &str) -> Option<&Workspace> {
+        self.workspaces.iter().find(|w| w.id == id)
+    }
+
+    pub fn create(&mut self, name: &str) -> Result<Workspace, String> {
+        let name = validate_name(self, name, None)?;
+        let workspace = Workspace {
+            id: Uuid::new_v4().to_string(),
+            name,
+            created_at: Utc::now().to_rfc3339(),
+        };
+        self.workspaces.push(workspace.clone());
+        Ok(workspace)
+    }
+
+    pub fn rename(&mut self, id: &str, name: &str) -> Result<Workspace, String> {
+        let name = validate_name(self, name, Some(id))?;
+        let workspace = self
+            .workspaces
+            .iter_mut()
+            .find(|w| w.id == id)
+            .ok_or_else(|| t!("Workspace not found: {}", id))?;
+        workspace.name = name;
+        Ok(workspace.clone())
+    }
+
+    // Workspace whose database is the file at `path`; None for a file opened from elsewhere
+    pub fn id_for_db_path(&self, data_dir: &Path, path: &Path, default_file: &str) -> Option<String> {
+        let canonical = |p: &Path| fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+        let wanted = canonical(path);
+        self.workspaces
+            .iter()
+            .find(|w| canonical(&db_path(data_dir, &w.id, default_file)) == wanted)
+            .map(|w| w.id.clone())
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<Workspace, String> {
+        if id == DEFAULT_ID {
+            return Err(t!("The default workspace can't be deleted"));
+        }
+        if id == self.last_used {
+            return Err(t!("Switch to another workspace before deleting this one"));
+        }
+        let index = self
+            .workspaces
+            .iter()
+            .position(|w| w.id == id)
+            .ok_or_else(|| t!("Workspace not found: {}", id))?;
+        Ok(self.workspaces.remove(index))
+    }
+}
+
+fn read(data_dir: &Path) -> Result<Option<WorkspaceRegistry>, String> {
+    match fs::read_to_string(data_dir.join(REGISTRY_FILE)) {
+        Ok(content) => serde_json::from_str(&content).map(Some).map_err(|e| e.to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn validate_name(registry: &WorkspaceRegistry, name: &str, except_id: Option<&str>) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(t!("Workspace name can't be empty"));
+    }
+    let taken = registry
+        .workspaces
+        .iter()
+        .any(|w| Some(w.id.as_str()) != except_id && w.name.eq_ignore_ascii_case(name));
+    if taken {
+        return Err(t!("A workspace named \"{}\" already exists", name));
+    }
+    Ok(name.to_string())
+}
+
+pub fn db_path(data_dir: &Path, id: &str, default_file: &str) -> PathBuf {
+    if id == DEFAULT_ID {
+        data_dir.join(default_file)
+    } else {
+        data_dir.join(WORKSPACE_DIR).join(format!("{}.db", id))
+    }
+}
+
+// Each workspace keeps its own backups, so a restore can't mix them up
+pub fn backup_dir(data_dir: &Path, id: &str) -> PathBuf {
+    if id == DEFAULT_ID {
+        data_dir.join("backups")
+    } else {
+        data_dir.join("backups").join(id)
+    }
+}
+
+// Removes a workspace's database files. Its backups are kept, so deleting is recoverable.
+pub fn delete_files(db_path: &Path) -> Result<(), String> {
+    let file_name = db_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    for suffix in ["", "-wal", "-shm", "-journal"] {
+        let path = db_path.with_file_name(format!("{}{}", file_name, suffix));
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(t!("Failed to delete {}: {}", path.display(), e));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_workspace_of_a_database_file() {
+        let data_dir = std::env::temp_dir().join(format!("itodo-workspaces-{}", std::process::id()));
+        let mut registry = WorkspaceRegistry::load(&data_dir);
+        let work = registry.create("Work").unwrap();
+        // Switching elsewhere must not change which workspace a file belongs to
+        registry.last_used = work.id.clone();
+
+        let default_db = db_path(&data_dir, DEFAULT_ID, "itodo.db");
+        let work_db = db_path(&data_dir, &work.id, "itodo.db");
+        assert_eq!(registry.id_for_db_path(&data_dir, &default_db, "itodo.db").as_deref(), Some(DEFAULT_ID));
+        assert_eq!(registry.id_for_db_path(&data_dir, &work_db, "itodo.db"), Some(work.id));
+        assert_eq!(registry.id_for_db_path(&data_dir, Path::new("/elsewhere/itodo.db"), "itodo.db"), None);
+    }
+
+    #[test]
+    fn keeps_an_unreadable_registry_when_saving() {
+        let data_dir = std::env::temp_dir().join(format!("itodo-registry-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&data_dir);
+        fs::create_dir_all(&data_dir).unwrap();
+        fs::write(data_dir.join(REGISTRY_FILE), "{\"last_used\": \"work\", \"workspaces\": [").unwrap();
+
+        let mut registry = WorkspaceRegistry::load(&data_dir);
+        assert_eq!(registry.workspaces.len(), 1);
+        registry.create("Home").unwrap();
+        registry.save(&data_dir).unwrap();
+
+        assert_eq!(WorkspaceRegistry::load(&data_dir).workspaces.len(), 2);
+        let moved = fs::read_dir(&data_dir)
+            .unwrap()
+            .filter(|e| e.as_ref().unwrap().file_name().to_string_lossy().starts_with("workspaces.json.broken-"))
+            .count();
+        assert_eq!(moved, 1);
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+}
@@ -0,0 +1,52 @@
+use itodo_lib::importers::{self, ImportedList};
+
+fn load_fixture() -> Vec<ImportedList> {
+    let importer = importers::find("google_tasks").expect("google tasks importer is registered");
+    importer
+        .parse(include_str!("fixtures/google_tasks.json"))
+        .expect("fixture should parse")
+}
+
+#[test]
+fn maps_task_lists() {
+    let lists = load_fixture();
+    let names: Vec<Option<&str>> = lists.iter().map(|l| l.name.as_deref()).collect();
+    assert_eq!(names, vec![Some("My Tasks"), Some("Shopping")]);
+}
+
+#[test]
+fn maps_tasks_in_position_order() {
+    let lists = load_fixture();
+    let tasks = &lists[0].tasks;
+    let titles: Vec<&str> = tasks.iter().map(|t| t.task.title.as_str()).collect();
+    // Deleted tasks are skipped
+    assert_eq!(titles, vec!["Send birthday card", "Book dentist"]);
+
+    let card = &tasks[0];
+    assert!(card.task.is_completed);
+    assert_eq!(card.task.updated_at, "2026-09-30T18:45:00+00:00");
+
+    let dentist = &tasks[1];
+    assert!(!dentist.task.is_completed);
+    assert_eq!(dentist.task.content.as_deref(), Some("Ask for a morning slot"));
+    assert_eq!(dentist.task.due_date.as_deref(), Some("2026-10-24T00:00:00Z"));
+    assert_eq!(dentist.task.created_at, "2026-09-20T10:00:00+00:00");
+}
+
+#[test]
+fn maps_child_tasks_to_subtasks() {
+    let lists = load_fixture();
+    let dentist = &lists[0].tasks[1];
+    assert_eq!(dentist.subtasks.len(), 1);
+    assert_eq!(dentist.subtasks[0].title, "Collect forms");
+    assert!(dentist.subtasks[0].is_completed);
+    assert_eq!(dentist.subtasks[0].task_id, dentist.task.id);
+}
+
+#[test]
+fn keeps_subtasks_whose_parent_is_missing() {
+    let lists = load_fixture();
+    let shopping = &lists[1].tasks;
+    assert_eq!(shopping.len(), 1);
+    assert_eq!(shopping[0].task.title, "Batteries");
+}
@@ -0,0 +1,104 @@
+use chrono::TimeZone;
+use itodo_lib::importers::microsoft_todo;
+use itodo_lib::importers::ImportedList;
+
+fn load_fixture() -> Vec<ImportedList> {
+    let data = include_str!("fixtures/microsoft_todo.json");
+    microsoft_todo::parse(data).expect("fixture should parse")
+}
+
+#[test]
+fn maps_lists_and_default_list() {
+    let lists = load_fixture();
+    assert_eq!(lists.len(), 3);
+    assert_eq!(lists[0].name, None);
+    assert_eq!(lists[1].name.as_deref(), Some("Work"));
+    assert_eq!(lists[2].name.as_deref(), Some("Flagged email"));
+    assert!(lists[2].tasks.is_empty());
+}
+
+#[test]
+fn maps_task_fields() {
+    let lists = load_fixture();
+    let passport = &lists[0].tasks[0];
+    assert_eq!(passport.task.title, "Renew passport");
+    assert_eq!(passport.task.content.as_deref(), Some("Bring two photos\nFees & forms online"));
+    assert!(passport.task.is_important);
+    assert!(!passport.task.is_completed);
+    assert_eq!(passport.task.due_date.as_deref(), Some("2026-10-20T00:00:00Z"));
+    assert_eq!(passport.task.remind_time.as_deref(), Some("2026-10-19T09:00:00Z"));
+    assert_eq!(passport.task.created_at, "2026-09-01T08:12:44.123456700+00:00");
+    assert_eq!(passport.tags, vec!["Personal".to_string()]);
+}
+
+#[test]
+fn maps_checklist_items_to_subtasks() {
+    let lists = load_fixture();
+    let passport = &lists[0].tasks[0];
+    let subtasks: Vec<(&str, bool)> = passport
+        .subtasks
+        .iter()
+        .map(|s| (s.title.as_str(), s.is_completed))
+        .collect();
+    assert_eq!(subtasks, vec![("Take photos", true), ("Fill in form", false)]);
+    assert!(passport.subtasks.iter().all(|s| s.task_id == passport.task.id));
+}
+
+#[test]
+fn maps_status_and_recurrence() {
+    let lists = load_fixture();
+    let plants = &lists[0].tasks[1];
+    assert!(plants.task.is_completed);
+    assert_eq!(plants.task.content, None);
+    assert_eq!(plants.task.remind_time, None);
+    assert_eq!(plants.task.updated_at, "2026-09-03T00:00:00Z");
+    assert_eq!(plants.task.repeat_rule.as_deref(), Some(r#"{"type":"weekly","days":["Mon","Thu"]}"#));
+
+    let report = &lists[1].tasks[0];
+    assert!(!report.task.is_important);
+    assert_eq!(report.task.start_date.as_deref(), Some("2026-10-01T00:00:00Z"));
+    assert_eq!(report.task.repeat_rule.as_deref(), Some(r#"{"type":"monthly","daysOfMonth":[31],"interval":3}"#));
+    assert_eq!(report.tags, vec!["Finance".to_string(), "Q4".to_string()]);
+}
+
+#[test]
+fn reads_times_in_their_time_zone() {
+    let data = r#"[{"displayName": "Trips", "tasks": [{
+        "title": "Check in",
+        "reminderDateTime": {"dateTime": "2026-11-02T18:30:00.0000000", "timeZone": "Pacific Standard Time"},
+        "dueDateTime": {"dateTime": "2026-11-03T08:00:00.0000000", "timeZone": "UTC"},
+        "recurrence": {"pattern": {"type": "everyOtherFullMoon", "interval": 1}}
+    }]}]"#;
+    let lists = microsoft_todo::parse(data).unwrap();
+    let task = &lists[0].tasks[0].task;
+
+    let reminder = chrono::NaiveDate::from_ymd_opt(2026, 11, 2).unwrap().and_hms_opt(18, 30, 0).unwrap();
+    let expected = chrono::Local.from_local_datetime(&reminder).earliest().unwrap().with_timezone(&chrono::Utc);
+    assert_eq!(task.remind_time.as_deref(), Some(expected.format("%Y-%m-%dT%H:%M:%SZ").to_string().as_str()));
+
+    let due = chrono::NaiveDate::from_ymd_opt(2026, 11, 3).unwrap().and_hms_opt(8, 0, 0).unwrap().and_utc();
+    let expected = format!("{}T00:00:00Z", due.with_timezone(&chrono::Local).format("%Y-%m-%d"));
+    assert_eq!(task.due_date.as_deref(), Some(expected.as_str()));
+
+    // An unknown recurrence type is dropped rather than guessed
+    assert_eq!(task.repeat_rule, None);
+}
+
+#[test]
+fn skips_untitled_tasks() {
+    let lists = load_fixture();
+    assert_eq!(lists[1].tasks.len(), 1);
+}
+
+#[test]
+fn rejects_invalid_json() {
+    assert!(microsoft_todo::parse("{\"value\": 3}").is_err());
+}
+
+#[test]
+fn is_registered_as_importer() {
+    let importer = itodo_lib::importers::find("microsoft_todo").expect("importer is registered");
+    assert_eq!(importer.name(), "Microsoft To Do");
+    let lists = importer.parse(include_str!("fixtures/microsoft_todo.json")).unwrap();
+    assert_eq!(lists.len(), 3);
+}
@@ -0,0 +1,344 @@
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// `itodo --rpc` on its own data folder, talked to through its stdin and stdout
+struct Client {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    messages: Receiver<Value>,
+    // Notifications that arrived while waiting for a response
+    notifications: Vec<Value>,
+    next_id: u64,
+}
+
+impl Client {
+    fn start(data_dir: &PathBuf) -> Client {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_itodo"))
+            .arg("--rpc")
+            .arg("--data-dir")
+            .arg(data_dir)
+            .env_remove("ITODO_PASSPHRASE")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("itodo starts");
+
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let (sender, messages) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                let message: Value = serde_json::from_str(&line).expect("every line is JSON");
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Client {
+            stdin: child.stdin.take(),
+            child,
+            messages,
+            notifications: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    fn send_line(&mut self, line: &str) {
+        let stdin = self.stdin.as_mut().expect("stdin is open");
+        writeln!(stdin, "{}", line).expect("request is written");
+        stdin.flush().expect("request is flushed");
+    }
+
+    fn next_message(&mut self) -> Value {
+        self.messages.recv_timeout(TIMEOUT).expect("a message arrives in time")
+    }
+
+    // Sends a request and returns the whole response
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send_line(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }).to_string());
+        loop {
+            let message = self.next_message();
+            if message.get("id") == Some(&json!(id)) {
+                return message;
+            }
+            self.notifications.push(message);
+        }
+    }
+
+    // Sends a request and returns its result, failing on an error response
+    fn call(&mut self, method: &str, params: Value) -> Value {
+        let response = self.request(method, params);
+        assert_eq!(response["jsonrpc"], "2.0");
+        assert!(response.get("error").is_none(), "{} failed: {}", method, response);
+        response["result"].clone()
+    }
+
+    fn error_code(&mut self, method: &str, params: Value) -> i64 {
+        let response = self.request(method, params);
+        response["error"]["code"].as_i64().unwrap_or_else(|| panic!("{} should fail: {}", method, response))
+    }
+
+    fn wait_for_notification(&mut self) -> Value {
+        if !self.notifications.is_empty() {
+            return self.notifications.remove(0);
+        }
+        self.next_message()
+    }
+
+    fn default_list_id(&mut self) -> String {
+        let lists = self.call("get_lists", json!({}));
+        let default = lists
+            .as_array()
+            .expect("lists are an array")
+            .iter()
+            .find(|l| l["is_default"] == true)
+            .expect("there is a default list");
+        default["id"].as_str().expect("list id is a string").to_string()
+    }
+
+    fn close(mut self) -> i32 {
+        drop(self.stdin.take());
+        self.child.wait().expect("itodo exits").code().unwrap_or(-1)
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn data_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("itodo-rpc-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("data folder is created");
+    dir
+}
+
+#[test]
+fn creates_finds_and_deletes_tasks() {
+    let dir = data_dir("tasks");
+    let mut client = Client::start(&dir);
+    let list_id = client.default_list_id();
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    let task = client.call(
+        "create_task",
+        json!({ "input": { "list_id": list_id, "title": "Write the release notes", "due_date": today } }),
+    );
+    let id = task["id"].as_str().expect("task id is a string").to_string();
+    assert_eq!(task["title"], "Write the release notes");
+    assert_eq!(task["is_completed"], false);
+
+    let today_tasks = client.call("get_today_tasks", json!(null));
+    assert!(today_tasks.as_array().unwrap().iter().any(|t| t["id"] == id.as_str()));
+    let found = client.call("search_tasks", json!({ "query": "release" }));
+    assert_eq!(found.as_array().unwrap().len(), 1);
+
+    let toggled = client.call("toggle_task_completed", json!({ "id": id }));
+    assert_eq!(toggled["is_completed"], true);
+    let updated = client.call("update_task", json!({ "input": { "id": id, "title": "Publish the release notes" } }));
+    assert_eq!(updated["title"], "Publish the release notes");
+
+    client.call("set_task_tags", json!({ "task_id": id, "tags": ["work", " docs "] }));
+    assert_eq!(client.call("get_task_tags", json!({ "task_id": id })), json!(["docs", "work"]));
+    client.call("create_subtask", json!({ "input": { "task_id": id, "title": "Proofread" } }));
+    assert_eq!(client.call("get_subtasks", json!({ "task_id": id })).as_array().unwrap().len(), 1);
+
+    assert_eq!(client.call("delete_task", json!({ "id": id })), Value::Null);
+    assert_eq!(client.call("get_tasks", json!({ "list_id": list_id })), json!([]));
+    assert_eq!(client.close(), 0);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn accepts_camel_case_params_like_invoke() {
+    let dir = data_dir("camel");
+    let mut client = Client::start(&dir);
+    let list = client.call("create_list", json!({ "input": { "name": "Errands" } }));
+    let list_id = list["id"].as_str().unwrap().to_string();
+    client.call("create_task", json!({ "input": { "list_id": list_id, "title": "Return the library books" } }));
+
+    let tasks = client.call("get_tasks", json!({ "listId": list_id }));
+    assert_eq!(tasks.as_array().unwrap().len(), 1);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn imports_csv_only_into_existing_lists() {
+    let dir = data_dir("csv");
+    let mut client = Client::start(&dir);
+    let list_id = client.default_list_id();
+    let csv_data = "title\nBuy stamps\nPost the parcel\n";
+
+    let params = json!({ "csv_data": csv_data, "options": { "list_id": "no-such-list" } });
+    assert_eq!(client.error_code("import_tasks_from_csv", params), -32000);
+    assert_eq!(client.call("get_tasks", json!({ "list_id": list_id })), json!([]));
+
+    let report = client.call("import_tasks_from_csv", json!({ "csv_data": csv_data, "options": { "list_id": list_id } }));
+    assert_eq!(report["imported"].as_array().unwrap().len(), 2);
+    assert_eq!(client.call("get_tasks", json!({ "list_id": list_id })).as_array().unwrap().len(), 2);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn notifies_after_changes() {
+    let dir = data_dir("notify");
+    let mut client = Client::start(&dir);
+    let list_id = client.default_list_id();
+
+    client.call("create_task", json!({ "input": { "list_id": list_id, "title": "Book flights" } }));
+    let notification = client.wait_for_notification();
+    assert_eq!(notification, json!({ "jsonrpc": "2.0", "method": "tasks_changed", "params": { "method": "create_task" } }));
+
+    // Reads don't notify, so the next message is the response
+    client.call("get_lists", json!({}));
+    assert!(client.notifications.is_empty());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn notifies_about_changes_from_other_programs() {
+    let dir = data_dir("external");
+    let mut watcher = Client::start(&dir);
+    let list_id = watcher.default_list_id();
+
+    let mut other = Client::start(&dir);
+    other.call("create_task", json!({ "input": { "list_id": list_id, "title": "Water the plants" } }));
+
+    let notification = watcher.wait_for_notification();
+    assert_eq!(notification["method"], "tasks_changed");
+    assert_eq!(notification["params"]["method"], Value::Null);
+    assert_eq!(watcher.call("get_tasks", json!({})).as_array().unwrap().len(), 1);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn reports_errors_with_json_rpc_codes() {
+    let dir = data_dir("errors");
+    let mut client = Client::start(&dir);
+    let list_id = client.default_list_id();
+
+    client.send_line("{not json");
+    let response = client.next_message();
+    assert_eq!(response["error"]["code"], -32700);
+    assert_eq!(response["id"], Value::Null);
+
+    client.send_line(r#"{"id": 7, "method": "get_lists"}"#);
+    let response = client.next_message();
+    assert_eq!(response["error"]["code"], -32600);
+    assert_eq!(response["id"], 7);
+
+    assert_eq!(client.error_code("get_everything", json!({})), -32601);
+    assert_eq!(client.error_code("lock_app", json!({})), -32601);
+    assert_eq!(client.error_code("delete_task", json!({})), -32602);
+    assert_eq!(client.error_code("get_lists", json!([1, 2])), -32602);
+    // The default list can't be deleted; the app's own error comes back
+    assert_eq!(client.error_code("delete_list", json!({ "id": list_id })), -32000);
+
+    // Still serving after all that
+    assert_eq!(client.call("get_lists", json!({})).as_array().unwrap().len(), 1);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn answers_batches_and_skips_notifications() {
+    let dir = data_dir("batch");
+    let mut client = Client::start(&dir);
+    let list_id = client.default_list_id();
+
+    // A request without an id is a notification and gets no response
+    client.send_line(
+        &json!({ "jsonrpc": "2.0", "method": "create_task", "params": { "input": { "list_id": list_id, "title": "Quiet" } } })
+            .to_string(),
+    );
+    client.send_line(
+        &json!([
+            { "jsonrpc": "2.0", "id": "a", "method": "get_tasks" },
+            { "jsonrpc": "2.0", "method": "get_lists" },
+            { "jsonrpc": "2.0", "id": "b", "method": "no_such_method" }
+        ])
+        .to_string(),
+    );
+
+    // The notification's change is announced, then the batch is answered
+    let changed = client.next_message();
+    assert_eq!(changed["params"]["method"], "create_task");
+    let batch = client.next_message();
+    let responses = batch.as_array().expect("a batch gets an array");
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["id"], "a");
+    assert_eq!(responses[0]["result"].as_array().unwrap().len(), 1);
+    assert_eq!(responses[1]["id"], "b");
+    assert_eq!(responses[1]["error"]["code"], -32601);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn exports_and_imports_through_files() {
+    let dir = data_dir("files");
+    let mut client = Client::start(&dir);
+    let list_id = client.default_list_id();
+    client.call("create_task", json!({ "input": { "list_id": list_id, "title": "Renew passport" } }));
+
+    let path = dir.join("export.json");
+    assert_eq!(client.call("export_tasks_to_path", json!({ "file_path": path })), true);
+    let exported = std::fs::read_to_string(&path).expect("export is written");
+    let imported = client.call("import_tasks", json!({ "json_data": exported }));
+    assert_eq!(imported.as_array().unwrap().len(), 1);
+    assert_eq!(client.call("get_tasks", json!({})).as_array().unwrap().len(), 2);
+
+    let report = client.call("check_database", json!({}));
+    assert_eq!(report["is_ok"], true);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn changes_settings_and_restores_backups() {
+    let dir = data_dir("maintenance");
+    let mut client = Client::start(&dir);
+    let list_id = client.default_list_id();
+
+    let settings = client.call("update_settings", json!({ "patch": { "theme": "dark" } }));
+    assert_eq!(settings["theme"], "dark");
+    assert_eq!(client.call("get_settings", json!({}))["theme"], "dark");
+
+    let backup = client.call("create_backup", json!({}));
+    client.call("create_task", json!({ "input": { "list_id": list_id, "title": "Not in the backup" } }));
+    client.call("restore_backup", json!({ "id": backup["id"] }));
+    assert_eq!(client.call("get_tasks", json!({})), json!([]));
+
+    let path = dir.join("diagnostics.zip");
+    client.call("create_diagnostics_bundle", json!({ "file_path": path }));
+    assert!(path.exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn switches_its_own_workspace() {
+    let dir = data_dir("workspaces");
+    let mut client = Client::start(&dir);
+    let list_id = client.default_list_id();
+    client.call("create_task", json!({ "input": { "list_id": list_id, "title": "Personal errand" } }));
+
+    let workspace = client.call("create_workspace", json!({ "name": "Work" }));
+    client.call("switch_workspace", json!({ "id": workspace["id"] }));
+    assert_eq!(client.call("get_tasks", json!({})), json!([]));
+    assert_eq!(client.close(), 0);
+
+    // The next start opens the workspace used last
+    let mut client = Client::start(&dir);
+    assert_eq!(client.call("get_tasks", json!({})), json!([]));
+    let _ = std::fs::remove_dir_all(&dir);
+}
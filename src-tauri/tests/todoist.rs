@@ -0,0 +1,81 @@
+use itodo_lib::importers::{self, ImportedList};
+
+fn parse(data: &str) -> Vec<ImportedList> {
+    let importer = importers::find("todoist").expect("todoist importer is registered");
+    importer.parse(data).expect("fixture should parse")
+}
+
+#[test]
+fn json_maps_projects_to_lists() {
+    let lists = parse(include_str!("fixtures/todoist.json"));
+    let names: Vec<Option<&str>> = lists.iter().map(|l| l.name.as_deref()).collect();
+    // The inbox goes to the default list and archived projects are skipped
+    assert_eq!(names, vec![None, Some("Home")]);
+}
+
+#[test]
+fn json_maps_item_fields() {
+    let lists = parse(include_str!("fixtures/todoist.json"));
+    let bank = &lists[0].tasks[0];
+    assert_eq!(bank.task.title, "Call the bank");
+    assert_eq!(bank.task.content.as_deref(), Some("Ask about the [new card](https://example.com/cards)"));
+    assert!(bank.task.is_important);
+    assert_eq!(bank.task.due_date.as_deref(), Some("2026-10-21T00:00:00Z"));
+    assert_eq!(bank.task.repeat_rule, None);
+    assert_eq!(bank.tags, vec!["phone".to_string()]);
+
+    let bins = &lists[1].tasks[0];
+    assert!(!bins.task.is_important);
+    assert_eq!(bins.task.due_date.as_deref(), Some("2026-10-20T00:00:00Z"));
+    assert_eq!(bins.task.repeat_rule.as_deref(), Some(r#"{"type":"weekly","days":["Mon","Thu"]}"#));
+}
+
+#[test]
+fn json_flattens_sub_items_into_subtasks() {
+    let lists = parse(include_str!("fixtures/todoist.json"));
+    let home = &lists[1];
+    assert_eq!(home.tasks.len(), 2);
+
+    let garden = &home.tasks[1];
+    assert_eq!(garden.task.title, "Plan garden work");
+    assert!(garden.task.is_completed);
+    assert_eq!(garden.task.repeat_rule.as_deref(), Some(r#"{"type":"weekly","interval":2}"#));
+    assert_eq!(garden.tags, vec!["errands".to_string()]);
+    let subtasks: Vec<(&str, bool)> = garden.subtasks.iter().map(|s| (s.title.as_str(), s.is_completed)).collect();
+    assert_eq!(subtasks, vec![("Buy seeds", true), ("Pick tomato varieties", false)]);
+}
+
+#[test]
+fn csv_template_maps_rows() {
+    let lists = parse(include_str!("fixtures/todoist.csv"));
+    assert_eq!(lists.len(), 1);
+    assert_eq!(lists[0].name.as_deref(), Some("Todoist"));
+
+    let tasks = &lists[0].tasks;
+    assert_eq!(tasks.len(), 3);
+
+    let milk = &tasks[0];
+    assert_eq!(milk.task.title, "Buy milk");
+    assert_eq!(milk.task.content.as_deref(), Some("Semi-skimmed\nCorner shop closes at 8"));
+    assert!(milk.task.is_important);
+    assert_eq!(milk.task.due_date.as_deref(), Some("2026-10-22T00:00:00Z"));
+    assert_eq!(milk.tags, vec!["shopping".to_string()]);
+    assert_eq!(milk.subtasks.len(), 1);
+    assert_eq!(milk.subtasks[0].title, "Check the date");
+
+    let weekly = &tasks[1];
+    assert!(!weekly.task.is_important);
+    assert_eq!(weekly.task.due_date, None);
+    assert_eq!(weekly.task.repeat_rule.as_deref(), Some(r#"{"type":"weekly","days":["Sat"]}"#));
+    assert_eq!(weekly.tags, vec!["shopping".to_string(), "car".to_string()]);
+
+    let rent = &tasks[2];
+    assert_eq!(rent.task.title, "Pay the rent");
+    assert_eq!(rent.task.repeat_rule.as_deref(), Some(r#"{"type":"monthly","daysOfMonth":[15]}"#));
+}
+
+#[test]
+fn csv_without_todoist_columns_is_rejected() {
+    let importer = importers::find("todoist").unwrap();
+    assert!(importer.parse("title,due\nSomething,2026-10-01\n").is_err());
+}
@@ -0,0 +1,54 @@
+use itodo_lib::importers::{self, ImportedList};
+
+fn load_fixture() -> ImportedList {
+    let importer = importers::find("trello").expect("trello importer is registered");
+    let mut lists = importer
+        .parse(include_str!("fixtures/trello.json"))
+        .expect("fixture should parse");
+    assert_eq!(lists.len(), 1);
+    lists.remove(0)
+}
+
+#[test]
+fn maps_board_to_list() {
+    let board = load_fixture();
+    assert_eq!(board.name.as_deref(), Some("Website relaunch"));
+    assert_eq!(board.color.as_deref(), Some("#0079BF"));
+}
+
+#[test]
+fn maps_open_cards_in_board_order() {
+    let board = load_fixture();
+    let titles: Vec<&str> = board.tasks.iter().map(|t| t.task.title.as_str()).collect();
+    assert_eq!(titles, vec!["Pick a colour palette", "Write copy for landing page", "Register domain"]);
+}
+
+#[test]
+fn maps_card_fields() {
+    let board = load_fixture();
+    let palette = &board.tasks[0];
+    assert_eq!(palette.task.content.as_deref(), Some("Stick to the brand guide.\nTwo accent colours max."));
+    assert_eq!(palette.task.due_date.as_deref(), Some("2026-10-30T00:00:00Z"));
+    assert_eq!(palette.task.start_date.as_deref(), Some("2026-10-20T00:00:00Z"));
+    assert!(!palette.task.is_completed);
+    // The column name comes first, unnamed labels are dropped
+    assert_eq!(palette.tags, vec!["To Do".to_string(), "design".to_string()]);
+
+    let domain = &board.tasks[2];
+    assert!(domain.task.is_completed);
+    assert_eq!(domain.tags, vec!["Done".to_string()]);
+}
+
+#[test]
+fn maps_checklists_to_subtasks() {
+    let board = load_fixture();
+    let subtasks: Vec<(&str, bool)> = board.tasks[0]
+        .subtasks
+        .iter()
+        .map(|s| (s.title.as_str(), s.is_completed))
+        .collect();
+    assert_eq!(
+        subtasks,
+        vec![("Collect examples", true), ("Check contrast", false), ("Get sign-off", false)]
+    );
+}
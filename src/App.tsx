@@ -1,22 +1,99 @@
 import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { useAppStore } from './store';
 import Sidebar from './components/Sidebar';
 import TaskList from './components/TaskList';
 import TaskDetail from './components/TaskDetail';
+import RecoveryScreen from './components/RecoveryScreen';
+import LockScreen from './components/LockScreen';
+import type { AppSettings, LockStatus, StartupStatus } from './types';
 
 function App() {
-  const { fetchLists, fetchTasks, fetchAllSubtasks, theme, selectedTask, toggleTaskCompleted, toggleTaskImportant, setSelectedTask } = useAppStore();
+  const { fetchLists, fetchTasks, fetchAllSubtasks, loadSettings, applySettings, theme, selectedTask, toggleTaskCompleted, toggleTaskImportant, setSelectedTask } = useAppStore();
   const [showMessage, setShowMessage] = useState<{title: string, message: string} | null>(null);
+  const [startupStatus, setStartupStatus] = useState<StartupStatus | null>(null);
+  const [locked, setLocked] = useState(false);
+
+  const loadData = async () => {
+    await fetchLists();
+    await fetchTasks();
+    await fetchAllSubtasks();
+  };
+
+  const startUp = async () => {
+    const status = await invoke<StartupStatus>('get_startup_status');
+    setStartupStatus(status);
+    if (!status.recovery) {
+      await loadData();
+    }
+  };
 
   useEffect(() => {
     const init = async () => {
-      await fetchLists();
-      await fetchTasks();
-      await fetchAllSubtasks();
+      await loadSettings();
+      const lock = await invoke<LockStatus>('get_lock_status');
+      setLocked(lock.locked);
+      if (!lock.locked) {
+        await startUp();
+      }
     };
     init();
   }, []);
 
+  // The backend locks the app after the idle time and tells every window
+  useEffect(() => {
+    const unlisten = listen('app-locked', () => setLocked(true));
+    return () => { unlisten.then((fn) => fn()); };
+  }, []);
+
+  // Settings changed in another window, or by an import
+  useEffect(() => {
+    const unlisten = listen<AppSettings>('settings-changed', (event) => applySettings(event.payload));
+    return () => { unlisten.then((fn) => fn()); };
+  }, []);
+
+  // Tasks changed outside the window, through the local API
+  useEffect(() => {
+    const unlisten = listen('tasks-changed', () => {
+      const { fetchLists, fetchFilteredTasks, fetchAllSubtasks, filter } = useAppStore.getState();
+      fetchLists();
+      fetchFilteredTasks(filter);
+      fetchAllSubtasks();
+    });
+    return () => { unlisten.then((fn) => fn()); };
+  }, []);
+
+  // Input counts as activity even when it doesn't call the backend
+  useEffect(() => {
+    let lastReport = 0;
+    const reportActivity = () => {
+      const now = Date.now();
+      if (now - lastReport > 30000) {
+        lastReport = now;
+        invoke('report_activity').catch(() => {});
+      }
+    };
+    window.addEventListener('mousemove', reportActivity);
+    window.addEventListener('keydown', reportActivity);
+    return () => {
+      window.removeEventListener('mousemove', reportActivity);
+      window.removeEventListener('keydown', reportActivity);
+    };
+  }, []);
+
+  const handleUnlocked = async () => {
+    setLocked(false);
+    await startUp();
+  };
+
+  const handleRecovered = async (status: StartupStatus) => {
+    setStartupStatus(status);
+    if (!status.recovery) {
+      await loadData();
+    }
+  };
+
   // Keyboard shortcuts
   useEffect(() => {
     const handleKeyDown = async (e: KeyboardEvent) => {
@@ -122,6 +199,14 @@ function App() {
     );
   };
 
+  if (locked) {
+    return <LockScreen onUnlocked={handleUnlocked} />;
+  }
+
+  if (startupStatus?.recovery) {
+    return <RecoveryScreen status={startupStatus} onRecovered={handleRecovered} />;
+  }
+
   return (
     <div className={`flex h-screen ${isDark ? 'bg-[#1f1f1f]' : 'bg-[#F3F2F1]'}`}>
       <MessageDialog />
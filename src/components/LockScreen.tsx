@@ -0,0 +1,68 @@
+import { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useAppStore } from '../store';
+
+interface LockScreenProps {
+  onUnlocked: () => void;
+}
+
+// Covers the window while the app lock is active; the backend refuses task commands until unlocked
+function LockScreen({ onUnlocked }: LockScreenProps) {
+  const { language, theme } = useAppStore();
+  const [passphrase, setPassphrase] = useState('');
+  const [busy, setBusy] = useState(false);
+  const [wrong, setWrong] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const isZh = language === 'zh-CN';
+  const isDark = theme === 'dark' || (theme === 'system' && window.matchMedia('(prefers-color-scheme: dark)').matches);
+
+  const unlock = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!passphrase) return;
+    setBusy(true);
+    try {
+      const ok = await invoke<boolean>('verify_unlock', { passphrase });
+      setPassphrase('');
+      setWrong(!ok);
+      setError(null);
+      if (ok) onUnlocked();
+    } catch (e) {
+      // E.g. damaged lock settings, which keep the app locked
+      setError(String(e));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div className={`flex h-screen items-center justify-center ${isDark ? 'bg-[#1f1f1f]' : 'bg-[#F3F2F1]'}`}>
+      <form onSubmit={unlock} className={`rounded-lg shadow-xl p-6 max-w-sm w-full mx-4 ${isDark ? 'bg-[#2d2d2d]' : 'bg-white'}`}>
+        <h2 className={`text-xl font-semibold mb-4 ${isDark ? 'text-white' : 'text-[#323130]'}`}>
+          {isZh ? 'iToDo 已锁定' : 'iToDo is locked'}
+        </h2>
+        <input
+          type="password"
+          autoFocus
+          value={passphrase}
+          placeholder={isZh ? '密码' : 'Passphrase'}
+          onChange={(e) => setPassphrase(e.target.value)}
+          className={`w-full px-3 py-2 rounded border mb-4 outline-none ${isDark ? 'bg-[#1f1f1f] border-[#404040] text-white' : 'border-[#E1DFDD] text-[#323130]'}`}
+        />
+        <button
+          type="submit"
+          disabled={busy || !passphrase}
+          className="w-full px-4 py-2 rounded bg-[#0078D4] hover:bg-[#106EBE] text-white disabled:opacity-50"
+        >
+          {isZh ? '解锁' : 'Unlock'}
+        </button>
+        {wrong && (
+          <p className="text-sm mt-4 text-[#A4262C]">{isZh ? '密码错误' : 'Wrong passphrase'}</p>
+        )}
+        {error && <p className="text-sm mt-4 text-[#A4262C]">{error}</p>}
+      </form>
+    </div>
+  );
+}
+
+export default LockScreen;
@@ -0,0 +1,146 @@
+import { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useAppStore } from '../store';
+import type { StartupStatus } from '../types';
+
+interface RecoveryScreenProps {
+  status: StartupStatus;
+  onRecovered: (status: StartupStatus) => void;
+}
+
+// Shown instead of the main window when the database could not be opened at startup
+function RecoveryScreen({ status, onRecovered }: RecoveryScreenProps) {
+  const { language, theme } = useAppStore();
+  const [busy, setBusy] = useState(false);
+  const [actionError, setActionError] = useState<string | null>(null);
+  const [passphrase, setPassphrase] = useState('');
+
+  const isZh = language === 'zh-CN';
+  const isDark = theme === 'dark' || (theme === 'system' && window.matchMedia('(prefers-color-scheme: dark)').matches);
+
+  const run = async (command: string, args?: Record<string, unknown>) => {
+    setBusy(true);
+    setActionError(null);
+    try {
+      const next = await invoke<StartupStatus>(command, args);
+      onRecovered(next);
+    } catch (error) {
+      setActionError(String(error));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const openOtherFile = async () => {
+    const { open } = await import('@tauri-apps/plugin-dialog');
+    const filePath = await open({
+      multiple: false,
+      filters: [{ name: 'SQLite', extensions: ['db', 'sqlite'] }]
+    });
+    if (filePath && typeof filePath === 'string') {
+      await run('open_database_file', { filePath });
+    }
+  };
+
+  const startFresh = async () => {
+    const confirmed = window.confirm(isZh
+      ? '将创建一个新的空数据库。损坏的文件会被重命名保留，不会删除。是否继续？'
+      : 'A new, empty database will be created. The damaged file is renamed and kept, not deleted. Continue?');
+    if (confirmed) {
+      await run('start_fresh_database');
+    }
+  };
+
+  const unlock = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (passphrase) {
+      await run('unlock_database', { passphrase });
+      setPassphrase('');
+    }
+  };
+
+  const backup = status.latest_backup;
+  const buttonClass = `w-full text-left px-4 py-3 rounded border disabled:opacity-50 ${isDark ? 'border-[#404040] hover:bg-[#3d3d3d] text-white' : 'border-[#E1DFDD] hover:bg-[#F3F2F1] text-[#323130]'}`;
+
+  if (status.locked) {
+    return (
+      <div className={`flex h-screen items-center justify-center ${isDark ? 'bg-[#1f1f1f]' : 'bg-[#F3F2F1]'}`}>
+        <form onSubmit={unlock} className={`rounded-lg shadow-xl p-6 max-w-sm w-full mx-4 ${isDark ? 'bg-[#2d2d2d]' : 'bg-white'}`}>
+          <h2 className={`text-xl font-semibold mb-2 ${isDark ? 'text-white' : 'text-[#323130]'}`}>
+            {isZh ? '数据库已加密' : 'Your database is encrypted'}
+          </h2>
+          <p className={`text-sm mb-4 ${isDark ? 'text-[#a0a0a0]' : 'text-[#605E5C]'}`}>
+            {isZh ? '请输入密码以解锁。' : 'Enter your passphrase to unlock it.'}
+          </p>
+          <input
+            type="password"
+            autoFocus
+            value={passphrase}
+            onChange={(e) => setPassphrase(e.target.value)}
+            className={`w-full px-3 py-2 rounded border mb-4 outline-none ${isDark ? 'bg-[#1f1f1f] border-[#404040] text-white' : 'border-[#E1DFDD] text-[#323130]'}`}
+          />
+          <button
+            type="submit"
+            disabled={busy || !passphrase}
+            className="w-full px-4 py-2 rounded bg-[#0078D4] hover:bg-[#106EBE] text-white disabled:opacity-50"
+          >
+            {isZh ? '解锁' : 'Unlock'}
+          </button>
+          {actionError && (
+            <p className="text-sm mt-4 text-[#A4262C] whitespace-pre-wrap">{actionError}</p>
+          )}
+        </form>
+      </div>
+    );
+  }
+
+  return (
+    <div className={`flex h-screen items-center justify-center ${isDark ? 'bg-[#1f1f1f]' : 'bg-[#F3F2F1]'}`}>
+      <div className={`rounded-lg shadow-xl p-6 max-w-lg w-full mx-4 ${isDark ? 'bg-[#2d2d2d]' : 'bg-white'}`}>
+        <h2 className={`text-xl font-semibold mb-2 ${isDark ? 'text-white' : 'text-[#323130]'}`}>
+          {isZh ? '无法打开数据库' : "The database couldn't be opened"}
+        </h2>
+        <p className={`text-sm mb-3 ${isDark ? 'text-[#a0a0a0]' : 'text-[#605E5C]'}`}>
+          {isZh ? '你的数据没有被修改。请选择下一步操作：' : 'Nothing has been changed. Choose how to continue:'}
+        </p>
+        <pre className={`text-xs p-3 rounded whitespace-pre-wrap mb-4 max-h-32 overflow-y-auto ${isDark ? 'bg-[#1f1f1f] text-[#ff8a80]' : 'bg-[#FDE7E9] text-[#A4262C]'}`}>
+          {status.error}
+          {'\n'}
+          {status.db_path}
+        </pre>
+
+        <div className="space-y-2">
+          <button className={buttonClass} disabled={busy} onClick={() => run('retry_open_database')}>
+            {isZh ? '重试' : 'Try again'}
+            <div className="text-xs opacity-70">
+              {isZh ? '如果数据库被其他程序占用，请先关闭它' : 'Close any other program that may be using the database first'}
+            </div>
+          </button>
+          <button className={buttonClass} disabled={busy || !backup} onClick={() => run('restore_latest_backup')}>
+            {isZh ? '恢复最近的备份' : 'Restore the latest backup'}
+            <div className="text-xs opacity-70">
+              {backup
+                ? new Date(backup.created_at).toLocaleString(isZh ? 'zh-CN' : 'en-US')
+                : (isZh ? '没有可用的备份' : 'No backups available')}
+            </div>
+          </button>
+          <button className={buttonClass} disabled={busy} onClick={openOtherFile}>
+            {isZh ? '打开其他数据库文件…' : 'Open a different database file…'}
+          </button>
+          <button className={buttonClass} disabled={busy} onClick={startFresh}>
+            {isZh ? '从空数据库开始' : 'Start fresh'}
+            <div className="text-xs opacity-70">
+              {isZh ? '损坏的文件会被保留' : 'The damaged file is kept'}
+            </div>
+          </button>
+        </div>
+
+        {actionError && (
+          <p className="text-sm mt-4 text-[#A4262C] whitespace-pre-wrap">{actionError}</p>
+        )}
+      </div>
+    </div>
+  );
+}
+
+export default RecoveryScreen;
@@ -0,0 +1,278 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useAppStore } from '../store';
+import type { EncryptionStatus, LockStatus } from '../types';
+
+interface SecurityDialogProps {
+  onClose: () => void;
+}
+
+// Database encryption and the app lock: each is turned on with a passphrase that can be changed later
+function SecurityDialog({ onClose }: SecurityDialogProps) {
+  const { language } = useAppStore();
+  const [encryption, setEncryption] = useState<EncryptionStatus | null>(null);
+  const [current, setCurrent] = useState('');
+  const [passphrase, setPassphrase] = useState('');
+  const [confirmation, setConfirmation] = useState('');
+  const [busy, setBusy] = useState(false);
+  const [lock, setLock] = useState<LockStatus | null>(null);
+  const [lockCurrent, setLockCurrent] = useState('');
+  const [lockPassphrase, setLockPassphrase] = useState('');
+  const [lockConfirmation, setLockConfirmation] = useState('');
+  const [idleMinutes, setIdleMinutes] = useState('');
+  const [error, setError] = useState<string | null>(null);
+  const [notice, setNotice] = useState<string | null>(null);
+
+  const isZh = language === 'zh-CN';
+
+  useEffect(() => {
+    invoke<EncryptionStatus>('get_encryption_status')
+      .then(setEncryption)
+      .catch((e) => setError(String(e)));
+    invoke<LockStatus>('get_lock_status')
+      .then((status) => {
+        setLock(status);
+        setIdleMinutes(String(status.idle_minutes));
+      })
+      .catch((e) => setError(String(e)));
+  }, []);
+
+  const run = async (action: () => Promise<void>, done: string) => {
+    setBusy(true);
+    setError(null);
+    setNotice(null);
+    try {
+      await action();
+      setCurrent('');
+      setPassphrase('');
+      setConfirmation('');
+      setLockCurrent('');
+      setLockPassphrase('');
+      setLockConfirmation('');
+      setNotice(done);
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const saveEncryption = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (passphrase !== confirmation) {
+      setError(isZh ? '两次输入的密码不一致' : 'The passphrases do not match');
+      return;
+    }
+    if (encryption?.encrypted) {
+      await run(
+        () => invoke('change_passphrase', { current, newPassphrase: passphrase }),
+        isZh ? '密码已更改' : 'Passphrase changed'
+      );
+    } else {
+      const confirmed = window.confirm(isZh
+        ? '数据库将用此密码加密。忘记密码将无法恢复数据。是否继续？'
+        : 'The database will be encrypted with this passphrase. If you forget it, your data cannot be recovered. Continue?');
+      if (!confirmed) return;
+      await run(async () => {
+        setEncryption(await invoke<EncryptionStatus>('enable_encryption', { passphrase }));
+      }, isZh ? '数据库已加密' : 'Database encrypted');
+    }
+  };
+
+  const saveLockPassphrase = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (lockPassphrase !== lockConfirmation) {
+      setError(isZh ? '两次输入的密码不一致' : 'The passphrases do not match');
+      return;
+    }
+    await run(async () => {
+      setLock(await invoke<LockStatus>('set_lock_passphrase', {
+        current: lock?.enabled ? lockCurrent : null,
+        passphrase: lockPassphrase,
+      }));
+    }, isZh ? '锁定密码已保存' : 'Lock passphrase saved');
+  };
+
+  const removeLockPassphrase = async () => {
+    await run(async () => {
+      setLock(await invoke<LockStatus>('remove_lock_passphrase', { current: lockCurrent }));
+    }, isZh ? '应用锁已关闭' : 'App lock turned off');
+  };
+
+  const saveIdleMinutes = async () => {
+    const minutes = Number(idleMinutes);
+    if (!Number.isInteger(minutes) || minutes < 0) {
+      setError(isZh ? '请输入分钟数' : 'Enter a number of minutes');
+      return;
+    }
+    await run(async () => {
+      setLock(await invoke<LockStatus>('set_lock_idle_minutes', { minutes }));
+    }, isZh ? '自动锁定时间已保存' : 'Auto-lock time saved');
+  };
+
+  // App listens for `app-locked` and shows the lock screen
+  const lockNow = async () => {
+    try {
+      await invoke('lock_app');
+      onClose();
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  const inputClass = 'w-full px-3 py-2 rounded border border-[#E1DFDD] text-sm text-[#323130] outline-none focus:border-[#0078D4] mb-2';
+
+  return (
+    <div className="fixed inset-0 bg-black bg-opacity-50 flex items-center justify-center z-50" onClick={onClose}>
+      <div className="bg-white rounded-lg shadow-xl p-6 max-w-md w-full mx-4" onClick={(e) => e.stopPropagation()}>
+        <h3 className="text-lg font-semibold mb-4 text-[#323130]">{isZh ? '安全' : 'Security'}</h3>
+
+        {/* Encryption */}
+        <h4 className="text-sm font-semibold mb-1 text-[#323130]">{isZh ? '数据库加密' : 'Database encryption'}</h4>
+        {encryption && !encryption.supported && (
+          <p className="text-sm mb-4 text-[#605E5C]">
+            {isZh ? '此版本的 iToDo 不支持加密。' : 'This build of iToDo was made without encryption support.'}
+          </p>
+        )}
+        {encryption?.supported && (
+          <form onSubmit={saveEncryption} className="mb-4">
+            <p className="text-sm mb-2 text-[#605E5C]">
+              {encryption.encrypted
+                ? (isZh ? '数据库已加密。' : 'The database is encrypted.')
+                : (isZh ? '数据库未加密。' : 'The database is not encrypted.')}
+            </p>
+            {encryption.encrypted && (
+              <input
+                type="password"
+                value={current}
+                placeholder={isZh ? '当前密码' : 'Current passphrase'}
+                onChange={(e) => setCurrent(e.target.value)}
+                className={inputClass}
+              />
+            )}
+            <input
+              type="password"
+              value={passphrase}
+              placeholder={isZh ? '新密码' : 'New passphrase'}
+              onChange={(e) => setPassphrase(e.target.value)}
+              className={inputClass}
+            />
+            <input
+              type="password"
+              value={confirmation}
+              placeholder={isZh ? '确认新密码' : 'Confirm new passphrase'}
+              onChange={(e) => setConfirmation(e.target.value)}
+              className={inputClass}
+            />
+            <button
+              type="submit"
+              disabled={busy || !passphrase || (encryption.encrypted && !current)}
+              className="px-4 py-2 rounded bg-[#0078D4] hover:bg-[#106EBE] text-white text-sm disabled:opacity-50"
+            >
+              {encryption.encrypted ? (isZh ? '更改密码' : 'Change passphrase') : (isZh ? '加密数据库' : 'Encrypt database')}
+            </button>
+          </form>
+        )}
+
+        {/* App lock */}
+        <h4 className="text-sm font-semibold mb-1 text-[#323130]">{isZh ? '应用锁' : 'App lock'}</h4>
+        {lock && (
+          <form onSubmit={saveLockPassphrase} className="mb-4">
+            <p className="text-sm mb-2 text-[#605E5C]">
+              {lock.enabled
+                ? (isZh ? '应用锁已开启，解锁需要输入密码。' : 'The app lock is on; unlocking asks for the passphrase.')
+                : (isZh ? '设置密码以开启应用锁。' : 'Set a passphrase to turn on the app lock.')}
+            </p>
+            {lock.enabled && (
+              <input
+                type="password"
+                value={lockCurrent}
+                placeholder={isZh ? '当前密码' : 'Current passphrase'}
+                onChange={(e) => setLockCurrent(e.target.value)}
+                className={inputClass}
+              />
+            )}
+            <input
+              type="password"
+              value={lockPassphrase}
+              placeholder={isZh ? '新密码' : 'New passphrase'}
+              onChange={(e) => setLockPassphrase(e.target.value)}
+              className={inputClass}
+            />
+            <input
+              type="password"
+              value={lockConfirmation}
+              placeholder={isZh ? '确认新密码' : 'Confirm new passphrase'}
+              onChange={(e) => setLockConfirmation(e.target.value)}
+              className={inputClass}
+            />
+            <div className="flex flex-wrap gap-2">
+              <button
+                type="submit"
+                disabled={busy || !lockPassphrase || (lock.enabled && !lockCurrent)}
+                className="px-4 py-2 rounded bg-[#0078D4] hover:bg-[#106EBE] text-white text-sm disabled:opacity-50"
+              >
+                {lock.enabled ? (isZh ? '更改密码' : 'Change passphrase') : (isZh ? '开启应用锁' : 'Turn on app lock')}
+              </button>
+              {lock.enabled && (
+                <>
+                  <button
+                    type="button"
+                    onClick={removeLockPassphrase}
+                    disabled={busy || !lockCurrent}
+                    className="px-4 py-2 rounded border border-[#E1DFDD] hover:bg-[#F3F2F1] text-[#A4262C] text-sm disabled:opacity-50"
+                  >
+                    {isZh ? '关闭应用锁' : 'Turn off'}
+                  </button>
+                  <button
+                    type="button"
+                    onClick={lockNow}
+                    disabled={busy}
+                    className="px-4 py-2 rounded border border-[#E1DFDD] hover:bg-[#F3F2F1] text-[#323130] text-sm disabled:opacity-50"
+                  >
+                    {isZh ? '立即锁定' : 'Lock now'}
+                  </button>
+                </>
+              )}
+            </div>
+            {lock.enabled && (
+              <div className="flex items-center gap-2 mt-3">
+                <label className="text-sm text-[#323130]">{isZh ? '空闲多少分钟后锁定（0 为不自动锁定）' : 'Lock after idle minutes (0 = never)'}</label>
+                <input
+                  type="number"
+                  min={0}
+                  max={1440}
+                  value={idleMinutes}
+                  onChange={(e) => setIdleMinutes(e.target.value)}
+                  className="w-20 px-2 py-1 rounded border border-[#E1DFDD] text-sm text-[#323130] outline-none focus:border-[#0078D4]"
+                />
+                <button
+                  type="button"
+                  onClick={saveIdleMinutes}
+                  disabled={busy || idleMinutes === String(lock.idle_minutes)}
+                  className="px-3 py-1 rounded border border-[#E1DFDD] hover:bg-[#F3F2F1] text-[#323130] text-sm disabled:opacity-50"
+                >
+                  {isZh ? '保存' : 'Save'}
+                </button>
+              </div>
+            )}
+          </form>
+        )}
+
+        {error && <p className="text-sm mb-4 text-[#A4262C]">{error}</p>}
+        {notice && <p className="text-sm mb-4 text-[#107C10]">{notice}</p>}
+
+        <div className="flex justify-end">
+          <button
+            onClick={onClose}
+            className="px-4 py-2 rounded border border-[#E1DFDD] hover:bg-[#F3F2F1] text-[#323130] text-sm"
+          >
+            {isZh ? '关闭' : 'Close'}
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+}
+
+export default SecurityDialog;
@@ -1,6 +1,7 @@
 import { useState, useRef, useEffect } from 'react';
 import { useAppStore } from '../store';
 import { invoke } from '@tauri-apps/api/core';
+import SecurityDialog from './SecurityDialog';
 import type { FilterType, List as ListType, Theme } from '../types';
 
 const filterItems: { id: FilterType; nameZh: string; nameEn: string; icon: string }[] = [
@@ -74,6 +75,11 @@ const icons: Record<string, JSX.Element> = {
       <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M7 21a4 4 0 01-4-4V5a2 2 0 012-2h4a2 2 0 012 2v12a4 4 0 01-4 4zm0 0h12a2 2 0 002-2v-4a2 2 0 00-2-2h-2.343M11 7.343l1.657-1.657a2 2 0 012.828 0l2.829 2.829a2 2 0 010 2.828l-8.486 8.485M7 17h.01" />
     </svg>
   ),
+  lock: (
+    <svg className="w-5 h-5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+      <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M12 15v2m-6 4h12a2 2 0 002-2v-6a2 2 0 00-2-2H6a2 2 0 00-2 2v6a2 2 0 002 2zm10-10V7a4 4 0 00-8 0v4h8z" />
+    </svg>
+  ),
   export: (
     <svg className="w-5 h-5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
       <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M4 16v1a3 3 0 003 3h10a3 3 0 003-3v-1m-4-8l-4-4m0 0L8 8m4-4v12" />
@@ -110,6 +116,7 @@ export default function Sidebar({ onShowMessage }: { onShowMessage?: (msg: {titl
   const [newListName, setNewListName] = useState('');
   const [contextMenu, setContextMenu] = useState<{ x: number; y: number; listId: string } | null>(null);
   const [showSettings, setShowSettings] = useState(false);
+  const [showSecurity, setShowSecurity] = useState(false);
   const settingsRef = useRef<HTMLDivElement>(null);
 
   const isZh = language === 'zh-CN';
@@ -382,6 +389,15 @@ export default function Sidebar({ onShowMessage }: { onShowMessage?: (msg: {titl
                 {isZh ? '显示日志' : 'Show Log'}
               </div>
 
+              {/* Security */}
+              <div
+                onClick={() => { setShowSettings(false); setShowSecurity(true); }}
+                className="flex items-center gap-2 px-3 py-2 text-sm text-[#323130] hover:bg-[#F3F2F1] rounded cursor-pointer"
+              >
+                {icons['lock']}
+                {isZh ? '安全' : 'Security'}
+              </div>
+
               {/* Export - Submenu */}
               <div className="relative group/popc">
                 <div className="flex items-center justify-between px-3 py-2 text-sm text-[#323130] hover:bg-[#F3F2F1] rounded cursor-pointer">
@@ -425,6 +441,8 @@ export default function Sidebar({ onShowMessage }: { onShowMessage?: (msg: {titl
         </div>
       </div>
 
+      {showSecurity && <SecurityDialog onClose={() => setShowSecurity(false)} />}
+
       {/* Context Menu */}
       {contextMenu && (
         <div
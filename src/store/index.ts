@@ -1,6 +1,6 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import type { Task, List, Subtask, CreateTaskInput, UpdateTaskInput, CreateListInput, UpdateListInput, CreateSubtaskInput, UpdateSubtaskInput, FilterType, Language } from '../types';
+import type { Task, List, Subtask, CreateTaskInput, UpdateTaskInput, CreateListInput, UpdateListInput, CreateSubtaskInput, UpdateSubtaskInput, FilterType, Language, AppSettings } from '../types';
 
 type Theme = 'light' | 'dark' | 'system';
 
@@ -44,6 +44,8 @@ interface AppState {
   setSelectedTask: (task: Task | null) => void;
   setFilter: (filter: FilterType) => void;
   setSearchQuery: (query: string) => void;
+  loadSettings: () => Promise<void>;
+  applySettings: (settings: AppSettings) => void;
   setLanguage: (language: Language) => void;
   setTheme: (theme: Theme) => void;
   clearError: () => void;
@@ -388,12 +390,28 @@ export const useAppStore = create<AppState>((set, get) => ({
     get().searchTasks(query);
   },
 
+  loadSettings: async () => {
+    try {
+      const settings = await invoke<AppSettings>('get_settings');
+      get().applySettings(settings);
+    } catch (error) {
+      set({ error: String(error) });
+    }
+  },
+
+  applySettings: (settings: AppSettings) => {
+    set({ language: settings.language, theme: settings.theme });
+  },
+
+  // Saved by the backend, which broadcasts the change to every window
   setLanguage: (language: Language) => {
     set({ language });
+    invoke('update_settings', { patch: { language } }).catch((error) => set({ error: String(error) }));
   },
 
   setTheme: (theme: Theme) => {
     set({ theme });
+    invoke('update_settings', { patch: { theme } }).catch((error) => set({ error: String(error) }));
   },
 
   clearError: () => {
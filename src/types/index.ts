@@ -80,6 +80,32 @@ export interface UpdateSubtaskInput {
   is_completed?: boolean;
 }
 
+export interface BackupInfo {
+  id: string;
+  created_at: string;
+  size: number;
+  path: string;
+}
+
+export interface StartupStatus {
+  recovery: boolean;
+  locked: boolean;
+  error?: string;
+  db_path: string;
+  latest_backup?: BackupInfo;
+}
+
+export interface EncryptionStatus {
+  supported: boolean;
+  encrypted: boolean;
+}
+
+export interface LockStatus {
+  enabled: boolean;
+  locked: boolean;
+  idle_minutes: number;
+}
+
 export type FilterType = 'all' | 'today' | 'planned' | 'important' | 'completed';
 
 export interface RepeatRule {
@@ -93,7 +119,26 @@ export type Language = 'zh-CN' | 'en-US';
 
 export type Theme = 'light' | 'dark' | 'system';
 
+export interface BackupConfig {
+  enabled: boolean;
+  interval_hours: number;
+  keep_daily: number;
+  keep_weekly: number;
+}
+
+export type LogLevel = 'off' | 'error' | 'warn' | 'info' | 'debug' | 'trace';
+
 export interface AppSettings {
   language: Language;
   theme: Theme;
+  log_level: LogLevel;
+  backup: BackupConfig;
+}
+
+export interface ApiStatus {
+  enabled: boolean;
+  running: boolean;
+  port: number;
+  token: string;
+  url: string;
 }